@@ -0,0 +1,329 @@
+use minecraft_diorama::{AmbientOcclusion, Integrator, MipSelection, PixelFilter, PixelSampler, SamplePattern, ToneMapper};
+
+/// Opciones de línea de comandos
+pub struct Options {
+    pub headless: bool,
+    pub width: u32,
+    pub height: u32,
+    // None = usar la cámara de la escena
+    pub yaw: Option<f32>,
+    pub pitch: Option<f32>,
+    pub radius: Option<f32>,
+    pub output: String,
+    pub scene: Option<String>,
+    pub integrator: Integrator,
+    pub spp: u32, // frames acumulados por el path tracer en modo headless
+    pub tone_mapper: ToneMapper,
+    pub exposure: f32,
+    pub sampler: PixelSampler,
+    pub mip_selection: MipSelection,
+    pub shadow_samples: u32,
+    pub ao: AmbientOcclusion,
+    pub env_samples: u32,
+    pub skybox: Option<String>, // fondo de un solo archivo (equirect o cruz)
+    pub skybox_rotation: f32,   // grados alrededor de +Y
+    pub time: Option<f32>, // hora del cielo procedural (None = cielo de la escena)
+    pub turbidity: f32,
+    pub export: Option<String>, // malla .obj/.gltf/.glb en vez de renderizar
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            headless: false,
+            width: 1300,
+            height: 900,
+            yaw: None,
+            pitch: None,
+            radius: None,
+            output: "render.png".to_string(),
+            scene: None,
+            integrator: Integrator::Whitted,
+            spp: 64,
+            tone_mapper: ToneMapper::Exposure,
+            exposure: 1.0,
+            sampler: PixelSampler::default(),
+            mip_selection: MipSelection::Footprint,
+            shadow_samples: 16,
+            ao: AmbientOcclusion::default(),
+            env_samples: 0,
+            skybox: None,
+            skybox_rotation: 0.0,
+            time: None,
+            turbidity: 2.5,
+            export: None,
+        }
+    }
+}
+
+pub const USAGE: &str = "\
+Uso: minecraft_diorama [opciones]
+
+  --scene <json>      Carga el diorama desde un archivo de escena
+  --headless          Renderiza un frame a archivo sin abrir ventana
+  --width <px>        Ancho de la imagen (default 1300)
+  --height <px>       Alto de la imagen (default 900)
+  --yaw <rad>         Ángulo horizontal de la cámara (default 1.0)
+  --pitch <rad>       Ángulo vertical de la cámara (default 0.35)
+  --radius <u>        Distancia de la cámara al centro (default 10)
+  -o, --output <img>  Archivo de salida en modo headless (default render.png;
+                      .hdr/.exr guardan la radiancia sin tone mapping)
+  --integrator <i>    whitted | path (default whitted; en ventana se alterna con P)
+  --spp <n>           Muestras por pixel del path tracer en headless (default 64)
+  --tonemap <t>       exposure | reinhard | aces (default exposure)
+  --exposure <f>      Multiplicador de exposición antes del tone mapping (default 1.0)
+  --aa <n>            Muestras de antialiasing por pixel (default 1)
+  --aa-pattern <p>    grid | jittered | halton | bluenoise (default grid)
+  --aa-filter <f>     box | tent | gaussian (default box)
+  --mip <m>           footprint | distance: cómo se elige el mip de las texturas
+                      trilineales (default footprint)
+  --shadow-samples <n> Rayos de sombra por luz de área en Whitted (default 16)
+  --ao-samples <n>    Rayos de oclusión ambiental por punto en Whitted (default 0:
+                      sólo la oclusión por vértice)
+  --ao-radius <u>     Distancia de la oclusión ambiental (default 1.0)
+  --no-voxel-ao       Sin la oclusión por vértice de los bloques
+  --env-samples <n>   Rayos hacia el skybox/cielo por punto en Whitted (default 0:
+                      ambiente plano con oclusión ambiental)
+  --skybox <img>      Reemplaza el fondo por un panorama equirectangular (2:1) o un
+                      cubemap en cruz (4:3 / 3:4); acepta .hdr y .exr
+  --skybox-rotation <deg> Gira el fondo alrededor del eje vertical (default 0)
+  --time <h>          Cielo procedural a esa hora (0-24; sol, luna y estrellas);
+                      reemplaza el skybox. En ventana, T avanza media hora
+  --turbidity <t>     Turbidez del cielo procedural (default 2.5; 2 limpio, 10 brumoso)
+  --export <archivo>  Exporta las caras visibles del mundo (.obj con .mtl, .gltf
+                      o .glb) en vez de renderizar
+  -h, --help          Muestra esta ayuda";
+
+impl Options {
+    /// Parsea los argumentos (sin el nombre del programa).
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut opts = Self::default();
+        let mut it = args.into_iter();
+
+        while let Some(arg) = it.next() {
+            match arg.as_str() {
+                "--headless" => opts.headless = true,
+                "--width" => opts.width = parse_value(&arg, it.next())?,
+                "--height" => opts.height = parse_value(&arg, it.next())?,
+                "--yaw" => opts.yaw = Some(parse_value(&arg, it.next())?),
+                "--pitch" => opts.pitch = Some(parse_value(&arg, it.next())?),
+                "--radius" => opts.radius = Some(parse_value(&arg, it.next())?),
+                "-o" | "--output" => opts.output = parse_value(&arg, it.next())?,
+                "--scene" => opts.scene = Some(parse_value(&arg, it.next())?),
+                "--integrator" => {
+                    let name: String = parse_value(&arg, it.next())?;
+                    opts.integrator = match name.as_str() {
+                        "whitted" => Integrator::Whitted,
+                        "path" => Integrator::PathTracer,
+                        _ => return Err(format!("Integrador desconocido: {} (whitted | path)", name)),
+                    };
+                }
+                "--spp" => opts.spp = parse_value(&arg, it.next())?,
+                "--tonemap" => {
+                    let name: String = parse_value(&arg, it.next())?;
+                    opts.tone_mapper = match name.as_str() {
+                        "exposure" => ToneMapper::Exposure,
+                        "reinhard" => ToneMapper::Reinhard,
+                        "aces" => ToneMapper::Aces,
+                        _ => return Err(format!("Tone mapper desconocido: {} (exposure | reinhard | aces)", name)),
+                    };
+                }
+                "--exposure" => opts.exposure = parse_value(&arg, it.next())?,
+                "--aa" => opts.sampler.samples = parse_value(&arg, it.next())?,
+                "--aa-pattern" => {
+                    let name: String = parse_value(&arg, it.next())?;
+                    opts.sampler.pattern = match name.as_str() {
+                        "grid" => SamplePattern::Grid,
+                        "jittered" => SamplePattern::Jittered,
+                        "halton" => SamplePattern::Halton,
+                        "bluenoise" => SamplePattern::BlueNoise,
+                        _ => return Err(format!("Patrón de muestreo desconocido: {} (grid | jittered | halton | bluenoise)", name)),
+                    };
+                }
+                "--ao-samples" => opts.ao.samples = parse_value(&arg, it.next())?,
+                "--ao-radius" => opts.ao.radius = parse_value(&arg, it.next())?,
+                "--no-voxel-ao" => opts.ao.voxel = false,
+                "--env-samples" => opts.env_samples = parse_value(&arg, it.next())?,
+                "--skybox" => opts.skybox = Some(parse_value(&arg, it.next())?),
+                "--skybox-rotation" => opts.skybox_rotation = parse_value(&arg, it.next())?,
+                "--time" => opts.time = Some(parse_value(&arg, it.next())?),
+                "--turbidity" => opts.turbidity = parse_value(&arg, it.next())?,
+                "--export" => {
+                    let path: String = parse_value(&arg, it.next())?;
+                    let ext = std::path::Path::new(&path).extension().map(|e| e.to_string_lossy().to_ascii_lowercase());
+                    if !matches!(ext.as_deref(), Some("obj" | "gltf" | "glb")) {
+                        return Err(format!("Formato de exportación desconocido: {} (.obj | .gltf | .glb)", path));
+                    }
+                    opts.export = Some(path);
+                }
+                "--shadow-samples" => opts.shadow_samples = parse_value(&arg, it.next())?,
+                "--mip" => {
+                    let name: String = parse_value(&arg, it.next())?;
+                    opts.mip_selection = match name.as_str() {
+                        "footprint" => MipSelection::Footprint,
+                        "distance" => MipSelection::Distance,
+                        _ => return Err(format!("Selección de mip desconocida: {} (footprint | distance)", name)),
+                    };
+                }
+                "--aa-filter" => {
+                    let name: String = parse_value(&arg, it.next())?;
+                    opts.sampler.filter = match name.as_str() {
+                        "box" => PixelFilter::Box,
+                        "tent" => PixelFilter::Tent,
+                        "gaussian" => PixelFilter::Gaussian,
+                        _ => return Err(format!("Filtro desconocido: {} (box | tent | gaussian)", name)),
+                    };
+                }
+                other => return Err(format!("Argumento desconocido: {}\n\n{}", other, USAGE)),
+            }
+        }
+
+        if opts.spp == 0 || opts.sampler.samples == 0 || opts.shadow_samples == 0 {
+            return Err("--spp, --aa y --shadow-samples deben ser mayores que 0".to_string());
+        }
+        if opts.exposure <= 0.0 || opts.exposure.is_nan() {
+            return Err("--exposure debe ser positiva".to_string());
+        }
+        if opts.time.is_some_and(|t| !t.is_finite()) || !(1.0..=20.0).contains(&opts.turbidity) {
+            return Err("--time debe ser un número y --turbidity estar entre 1 y 20".to_string());
+        }
+        if opts.width == 0 || opts.height == 0 {
+            return Err("El ancho y alto deben ser mayores que 0".to_string());
+        }
+        if opts.radius.is_some_and(|r| r <= 0.0) {
+            return Err("El radio de la cámara debe ser positivo".to_string());
+        }
+        Ok(opts)
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("Falta el valor para {}", flag))?;
+    value.parse().map_err(|_| format!("Valor inválido para {}: {}", flag, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    fn ok(args: &[&str]) -> Options {
+        parse(args).unwrap_or_else(|e| panic!("{:?}: {}", args, e))
+    }
+
+    fn error(args: &[&str]) -> String {
+        match parse(args) {
+            Ok(_) => panic!("se esperaba un error con {:?}", args),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        let o = ok(&[]);
+        assert!(!o.headless);
+        assert_eq!((o.width, o.height), (1300, 900));
+        assert_eq!((o.yaw, o.pitch, o.radius), (None, None, None));
+        assert_eq!(o.output, "render.png");
+        assert_eq!((o.scene, o.skybox, o.export, o.time), (None, None, None, None));
+        assert_eq!((o.integrator, o.spp), (Integrator::Whitted, 64));
+        assert_eq!((o.tone_mapper, o.exposure), (ToneMapper::Exposure, 1.0));
+        assert_eq!(o.sampler, PixelSampler::default());
+        assert_eq!(o.mip_selection, MipSelection::Footprint);
+        assert_eq!((o.shadow_samples, o.env_samples), (16, 0));
+        assert_eq!(o.ao, AmbientOcclusion::default());
+        assert_eq!((o.skybox_rotation, o.turbidity), (0.0, 2.5));
+    }
+
+    #[test]
+    fn value_flags() {
+        let o = ok(&[
+            "--scene", "scenes/diorama.json", "--width", "640", "--height", "360",
+            "--yaw", "-0.5", "--pitch", "0.25", "--radius", "12",
+            "--integrator", "path", "--spp", "8", "--tonemap", "aces", "--exposure", "1.5",
+            "--aa", "4", "--aa-pattern", "halton", "--aa-filter", "gaussian", "--mip", "distance",
+            "--shadow-samples", "3", "--ao-samples", "6", "--ao-radius", "2.5", "--no-voxel-ao",
+            "--env-samples", "5", "--skybox", "sky.hdr", "--skybox-rotation", "90",
+            "--time", "18.5", "--turbidity", "7",
+        ]);
+        assert_eq!(o.scene.as_deref(), Some("scenes/diorama.json"));
+        assert_eq!((o.width, o.height), (640, 360));
+        assert_eq!((o.yaw, o.pitch, o.radius), (Some(-0.5), Some(0.25), Some(12.0)));
+        assert_eq!((o.integrator, o.spp), (Integrator::PathTracer, 8));
+        assert_eq!((o.tone_mapper, o.exposure), (ToneMapper::Aces, 1.5));
+        assert_eq!(o.sampler, PixelSampler::new(SamplePattern::Halton, 4, PixelFilter::Gaussian));
+        assert_eq!(o.mip_selection, MipSelection::Distance);
+        assert_eq!(o.shadow_samples, 3);
+        assert_eq!((o.ao.samples, o.ao.radius, o.ao.voxel), (6, 2.5, false));
+        assert_eq!(o.env_samples, 5);
+        assert_eq!((o.skybox.as_deref(), o.skybox_rotation), (Some("sky.hdr"), 90.0));
+        assert_eq!((o.time, o.turbidity), (Some(18.5), 7.0));
+
+        // Cada nombre de las listas de la ayuda
+        for (name, want) in [("grid", SamplePattern::Grid), ("jittered", SamplePattern::Jittered),
+                             ("halton", SamplePattern::Halton), ("bluenoise", SamplePattern::BlueNoise)] {
+            assert_eq!(ok(&["--aa-pattern", name]).sampler.pattern, want);
+        }
+        for (name, want) in [("box", PixelFilter::Box), ("tent", PixelFilter::Tent), ("gaussian", PixelFilter::Gaussian)] {
+            assert_eq!(ok(&["--aa-filter", name]).sampler.filter, want);
+        }
+        for (name, want) in [("exposure", ToneMapper::Exposure), ("reinhard", ToneMapper::Reinhard), ("aces", ToneMapper::Aces)] {
+            assert_eq!(ok(&["--tonemap", name]).tone_mapper, want);
+        }
+        assert_eq!(ok(&["--integrator", "whitted"]).integrator, Integrator::Whitted);
+        assert_eq!(ok(&["--mip", "footprint"]).mip_selection, MipSelection::Footprint);
+        // La última aparición gana
+        assert_eq!(ok(&["--spp", "2", "--spp", "9"]).spp, 9);
+    }
+
+    #[test]
+    fn output_and_headless() {
+        let o = ok(&["--headless"]);
+        assert!(o.headless);
+        assert_eq!(o.output, "render.png");
+
+        // La salida no activa el modo headless por sí sola
+        let o = ok(&["-o", "out.exr"]);
+        assert!(!o.headless);
+        assert_eq!(o.output, "out.exr");
+        let o = ok(&["--output", "frames/a.png", "--headless"]);
+        assert!(o.headless);
+        assert_eq!(o.output, "frames/a.png");
+
+        for path in ["mundo.obj", "mundo.gltf", "MUNDO.GLB"] {
+            assert_eq!(ok(&["--export", path]).export.as_deref(), Some(path));
+        }
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(error(&["--samples", "4"]).starts_with("Argumento desconocido: --samples\n\nUso:"));
+        assert!(error(&["render.png"]).starts_with("Argumento desconocido: render.png"));
+        assert_eq!(error(&["--width"]), "Falta el valor para --width");
+        assert_eq!(error(&["--headless", "-o"]), "Falta el valor para -o");
+        assert_eq!(error(&["--width", "-3"]), "Valor inválido para --width: -3");
+        assert_eq!(error(&["--exposure", "mucha"]), "Valor inválido para --exposure: mucha");
+        assert_eq!(error(&["--integrator", "raster"]), "Integrador desconocido: raster (whitted | path)");
+        assert_eq!(error(&["--tonemap", "filmic"]), "Tone mapper desconocido: filmic (exposure | reinhard | aces)");
+        assert_eq!(error(&["--aa-pattern", "sobol"]), "Patrón de muestreo desconocido: sobol (grid | jittered | halton | bluenoise)");
+        assert_eq!(error(&["--aa-filter", "lanczos"]), "Filtro desconocido: lanczos (box | tent | gaussian)");
+        assert_eq!(error(&["--mip", "none"]), "Selección de mip desconocida: none (footprint | distance)");
+        assert_eq!(error(&["--export", "mundo.stl"]), "Formato de exportación desconocido: mundo.stl (.obj | .gltf | .glb)");
+
+        let counts = "--spp, --aa y --shadow-samples deben ser mayores que 0";
+        for flag in ["--spp", "--aa", "--shadow-samples"] {
+            assert_eq!(error(&[flag, "0"]), counts);
+        }
+        assert_eq!(error(&["--exposure", "0"]), "--exposure debe ser positiva");
+        assert_eq!(error(&["--exposure", "NaN"]), "--exposure debe ser positiva");
+        let sky = "--time debe ser un número y --turbidity estar entre 1 y 20";
+        assert_eq!(error(&["--time", "inf"]), sky);
+        assert_eq!(error(&["--turbidity", "0.5"]), sky);
+        assert_eq!(error(&["--turbidity", "21"]), sky);
+        assert_eq!(error(&["--height", "0"]), "El ancho y alto deben ser mayores que 0");
+        assert_eq!(error(&["--radius", "-1"]), "El radio de la cámara debe ser positivo");
+    }
+}
@@ -1 +1,3 @@
-pub mod window;
\ No newline at end of file
+pub mod cli;
+#[cfg(feature = "window")]
+pub mod window;
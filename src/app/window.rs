@@ -36,6 +36,7 @@ impl Window {
     }
     pub fn mouse_wheel(&self) -> f32 { self.rl.get_mouse_wheel_move() }
     pub fn is_mouse_down(&self, btn: MouseButton) -> bool { self.rl.is_mouse_button_down(btn) }
+    pub fn is_key_pressed(&self, key: KeyboardKey) -> bool { self.rl.is_key_pressed(key) }
 
     pub fn present(&mut self, pixels_rgbx: &[u32]) {
         // Convertimos de 0x00RRGGBB (nuestro framebuffer) a RGBA8 que espera raylib
@@ -0,0 +1,71 @@
+use nalgebra_glm as glm;
+
+/// Caja alineada a los ejes
+#[derive(Clone, Copy, Debug)]
+pub struct Aabb {
+    pub min: glm::Vec3,
+    pub max: glm::Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: glm::Vec3, max: glm::Vec3) -> Self { Self { min, max } }
+
+    /// Caja "invertida": neutra para `union`
+    pub fn empty() -> Self {
+        Self {
+            min: glm::vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: glm::vec3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    /// Caja de todo el espacio (primitivas sin límites)
+    pub fn infinite() -> Self {
+        Self {
+            min: glm::vec3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            max: glm::vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        (0..3).all(|a| self.min[a].is_finite() && self.max[a].is_finite())
+    }
+
+    pub fn union(&self, o: &Aabb) -> Aabb {
+        Aabb { min: glm::min2(&self.min, &o.min), max: glm::max2(&self.max, &o.max) }
+    }
+
+    pub fn grow(&self, p: &glm::Vec3) -> Aabb {
+        Aabb { min: glm::min2(&self.min, p), max: glm::max2(&self.max, p) }
+    }
+
+    /// ¿El punto está dentro de la caja (agrandada en `eps`)?
+    pub fn contains(&self, p: &glm::Vec3, eps: f32) -> bool {
+        (0..3).all(|a| p[a] >= self.min[a] - eps && p[a] <= self.max[a] + eps)
+    }
+
+    pub fn centroid(&self) -> glm::Vec3 { (self.min + self.max) * 0.5 }
+
+    pub fn surface_area(&self) -> f32 {
+        let d = self.max - self.min;
+        if d.x < 0.0 || d.y < 0.0 || d.z < 0.0 { return 0.0; }
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+
+    /// Test de slabs; devuelve la distancia de entrada si el rayo cruza la caja
+    /// en [0, tmax]. `inv` es 1/dir precalculado.
+    #[inline]
+    pub fn hit(&self, ro: &glm::Vec3, inv: &glm::Vec3, tmax: f32) -> Option<f32> {
+        let mut t0 = 0.0_f32;
+        let mut t1 = tmax;
+        for a in 0..3 {
+            let mut tn = (self.min[a] - ro[a]) * inv[a];
+            let mut tf = (self.max[a] - ro[a]) * inv[a];
+            if tn > tf { std::mem::swap(&mut tn, &mut tf); }
+            if tn.is_nan() || tf.is_nan() { continue; }
+            t0 = t0.max(tn);
+            t1 = t1.min(tf);
+            if t0 > t1 { return None; }
+        }
+        Some(t0)
+    }
+}
@@ -0,0 +1,328 @@
+use nalgebra_glm as glm;
+use crate::core::geometry::RayIntersect;
+use crate::core::intersect::Intersect;
+use super::aabb::Aabb;
+
+// Cantidad de cubetas para evaluar la SAH
+const SAH_BUCKETS: usize = 12;
+// Primitivas por hoja a partir de las cuales ya no conviene dividir
+const MAX_LEAF_PRIMS: usize = 4;
+// Profundidad a partir de la cual se fuerza partición por mediana (acota la pila)
+const MAX_SAH_DEPTH: usize = 64;
+// Costos relativos de la SAH (recorrer nodo vs intersectar primitiva)
+const TRAVERSAL_COST: f32 = 0.125;
+const INTERSECT_COST: f32 = 1.0;
+
+/// Nodo aplanado en orden depth-first: el hijo izquierdo de un nodo interior
+/// está en `i + 1` y el derecho en `offset`. En hojas `offset` es el inicio
+/// en `indices` y `count` la cantidad de primitivas.
+#[derive(Clone, Copy)]
+struct BvhNode {
+    bounds: Aabb,
+    offset: u32,
+    count: u32,
+    axis: u8,
+}
+
+/// BVH construida con SAH sobre cualquier conjunto de cajas. No guarda las
+/// primitivas: la intersección la hace el closure que recibe el índice. Las
+/// cajas no finitas quedan fuera del árbol y se prueban con todos los rayos.
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    indices: Vec<u32>,
+    unbounded: Vec<u32>,
+}
+
+struct BuildItem {
+    bounds: Aabb,
+    centroid: glm::Vec3,
+    index: u32,
+}
+
+impl Bvh {
+    pub fn empty() -> Self { Self { nodes: Vec::new(), indices: Vec::new(), unbounded: Vec::new() } }
+
+    pub fn from_primitives<T: RayIntersect>(prims: &[T]) -> Self {
+        let bounds: Vec<Aabb> = prims.iter().map(|p| p.bounds()).collect();
+        Self::build(&bounds)
+    }
+
+    /// Construye la jerarquía; los índices de las hojas refieren a `bounds`.
+    pub fn build(bounds: &[Aabb]) -> Self {
+        let mut bvh = Self::empty();
+        if bounds.is_empty() { return bvh; }
+
+        let mut items = Vec::with_capacity(bounds.len());
+        for (i, b) in bounds.iter().enumerate() {
+            if b.is_finite() {
+                items.push(BuildItem { bounds: *b, centroid: b.centroid(), index: i as u32 });
+            } else {
+                bvh.unbounded.push(i as u32);
+            }
+        }
+        if items.is_empty() { return bvh; }
+
+        bvh.nodes.reserve(2 * bounds.len());
+        bvh.indices.reserve(bounds.len());
+        bvh.build_recursive(&mut items, 0);
+        bvh
+    }
+
+    fn build_recursive(&mut self, items: &mut [BuildItem], depth: usize) -> usize {
+        let node_idx = self.nodes.len();
+        let bounds = items.iter().fold(Aabb::empty(), |acc, it| acc.union(&it.bounds));
+        self.nodes.push(BvhNode { bounds, offset: 0, count: 0, axis: 0 });
+
+        let centroid_bounds = items.iter().fold(Aabb::empty(), |acc, it| acc.grow(&it.centroid));
+        let extent = centroid_bounds.max - centroid_bounds.min;
+        let axis = if extent.x > extent.y && extent.x > extent.z { 0 } else if extent.y > extent.z { 1 } else { 2 };
+
+        // Todas las primitivas en el mismo centroide: no hay partición posible
+        if items.len() <= MAX_LEAF_PRIMS || extent[axis] <= 0.0 {
+            self.make_leaf(node_idx, items);
+            return node_idx;
+        }
+
+        // SAH por cubetas sobre el eje más largo
+        let mut counts = [0usize; SAH_BUCKETS];
+        let mut boxes = [Aabb::empty(); SAH_BUCKETS];
+        let bucket_of = |c: &glm::Vec3| {
+            let rel = (c[axis] - centroid_bounds.min[axis]) / extent[axis];
+            ((rel * SAH_BUCKETS as f32) as usize).min(SAH_BUCKETS - 1)
+        };
+        for it in items.iter() {
+            let b = bucket_of(&it.centroid);
+            counts[b] += 1;
+            boxes[b] = boxes[b].union(&it.bounds);
+        }
+
+        let parent_area = bounds.surface_area().max(1e-12);
+        let mut best_cost = f32::INFINITY;
+        let mut best_split = 0;
+        for split in 0..SAH_BUCKETS - 1 {
+            let (mut b0, mut c0) = (Aabb::empty(), 0);
+            let (mut b1, mut c1) = (Aabb::empty(), 0);
+            for (i, (bx, &n)) in boxes.iter().zip(counts.iter()).enumerate() {
+                if i <= split { b0 = b0.union(bx); c0 += n; } else { b1 = b1.union(bx); c1 += n; }
+            }
+            if c0 == 0 || c1 == 0 { continue; }
+            let cost = TRAVERSAL_COST
+                + INTERSECT_COST * (c0 as f32 * b0.surface_area() + c1 as f32 * b1.surface_area()) / parent_area;
+            if cost < best_cost {
+                best_cost = cost;
+                best_split = split;
+            }
+        }
+
+        // Si dividir no mejora sobre una hoja (y cabe en una), dejamos hoja
+        let leaf_cost = INTERSECT_COST * items.len() as f32;
+        if best_cost >= leaf_cost {
+            self.make_leaf(node_idx, items);
+            return node_idx;
+        }
+
+        let mut mid = partition(items, |it| bucket_of(&it.centroid) <= best_split);
+        if mid == 0 || mid == items.len() || depth >= MAX_SAH_DEPTH {
+            // Caso degenerado o árbol muy profundo: partimos a la mitad por centroide
+            mid = items.len() / 2;
+            items.select_nth_unstable_by(mid, |a, b| a.centroid[axis].total_cmp(&b.centroid[axis]));
+        }
+
+        let (left, right) = items.split_at_mut(mid);
+        self.build_recursive(left, depth + 1);
+        let right_idx = self.build_recursive(right, depth + 1);
+        let node = &mut self.nodes[node_idx];
+        node.offset = right_idx as u32;
+        node.axis = axis as u8;
+        node_idx
+    }
+
+    fn make_leaf(&mut self, node_idx: usize, items: &[BuildItem]) {
+        let node = &mut self.nodes[node_idx];
+        node.offset = self.indices.len() as u32;
+        node.count = items.len() as u32;
+        self.indices.extend(items.iter().map(|it| it.index));
+    }
+
+    /// Hit más cercano con distancia < tmax. `intersect(i)` prueba la primitiva i.
+    pub fn closest_hit<F>(&self, ro: &glm::Vec3, rd: &glm::Vec3, tmax: f32, mut intersect: F) -> Intersect
+    where
+        F: FnMut(usize) -> Intersect,
+    {
+        let mut best = Intersect::miss();
+        let mut tbest = tmax;
+        self.traverse(ro, rd, tmax, |i| {
+            let hit = intersect(i);
+            if hit.is_intersecting && hit.distance < tbest {
+                tbest = hit.distance;
+                best = hit;
+            }
+            Some(tbest)
+        });
+        best
+    }
+
+    /// Cualquier hit (any-hit) con distancia < tmax; `f` devuelve false para cortar.
+    /// Devuelve false si el recorrido se cortó.
+    pub fn for_each_hit<I, F>(&self, ro: &glm::Vec3, rd: &glm::Vec3, tmax: f32, mut intersect: I, mut f: F) -> bool
+    where
+        I: FnMut(usize) -> Intersect,
+        F: FnMut(&Intersect) -> bool,
+    {
+        let mut keep_going = true;
+        self.traverse(ro, rd, tmax, |i| {
+            let hit = intersect(i);
+            if hit.is_intersecting && hit.distance < tmax && !f(&hit) {
+                keep_going = false;
+                return None;
+            }
+            Some(tmax)
+        });
+        keep_going
+    }
+
+    /// Recorrido con pila, visitando primero el hijo más cercano según el signo
+    /// del rayo en el eje de partición. `visit` devuelve el nuevo tmax (para
+    /// podar) o None para terminar.
+    fn traverse<V>(&self, ro: &glm::Vec3, rd: &glm::Vec3, tmax: f32, mut visit: V)
+    where
+        V: FnMut(usize) -> Option<f32>,
+    {
+        let mut tmax = tmax;
+        for &i in &self.unbounded {
+            match visit(i as usize) {
+                Some(t) => tmax = t,
+                None => return,
+            }
+        }
+        if self.nodes.is_empty() { return; }
+
+        let inv = glm::vec3(1.0 / rd.x, 1.0 / rd.y, 1.0 / rd.z);
+        let neg = [inv.x < 0.0, inv.y < 0.0, inv.z < 0.0];
+        let mut stack = [0u32; 2 * MAX_SAH_DEPTH];
+        let mut sp = 0usize;
+        let mut current = 0usize;
+
+        loop {
+            let node = &self.nodes[current];
+            if node.bounds.hit(ro, &inv, tmax).is_some() {
+                if node.count > 0 {
+                    let start = node.offset as usize;
+                    for &i in &self.indices[start..start + node.count as usize] {
+                        match visit(i as usize) {
+                            Some(t) => tmax = t,
+                            None => return,
+                        }
+                    }
+                } else {
+                    let (near, far) = if neg[node.axis as usize] {
+                        (node.offset as usize, current + 1)
+                    } else {
+                        (current + 1, node.offset as usize)
+                    };
+                    stack[sp] = far as u32;
+                    sp += 1;
+                    current = near;
+                    continue;
+                }
+            }
+            if sp == 0 { return; }
+            sp -= 1;
+            current = stack[sp] as usize;
+        }
+    }
+}
+
+/// Partición in-place estilo `std::partition`; devuelve el primer índice que no cumple.
+fn partition<T, P: Fn(&T) -> bool>(items: &mut [T], pred: P) -> usize {
+    let mut first = 0;
+    for i in 0..items.len() {
+        if pred(&items[i]) {
+            items.swap(first, i);
+            first += 1;
+        }
+    }
+    first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::geometry::{cube::Cube, sphere::Sphere};
+    use crate::core::material::Material;
+
+    /// Plano y = -20: sin límites, queda fuera del árbol
+    struct Floor;
+
+    impl RayIntersect for Floor {
+        fn ray_intersect(&self, ro: &glm::Vec3, rd: &glm::Vec3) -> Intersect {
+            let t = (-20.0 - ro.y) / rd.y;
+            if !(t > 0.001 && t.is_finite()) { return Intersect::miss(); }
+            Intersect::hit(t, ro + rd * t, glm::vec3(0.0, 1.0, 0.0), (0.0, 0.0), None, Material::default_black())
+        }
+
+        fn bounds(&self) -> Aabb { Aabb::infinite() }
+    }
+
+    fn scene() -> Vec<Box<dyn RayIntersect>> {
+        let mut seed = 7u32;
+        let mut rnd = move || { seed = seed.wrapping_mul(1664525).wrapping_add(1013904223); (seed >> 8) as f32 / (1 << 24) as f32 };
+        let mut prims: Vec<Box<dyn RayIntersect>> = vec![Box::new(Floor)];
+        for i in 0..300 {
+            let c = glm::vec3(rnd() * 40.0 - 20.0, rnd() * 40.0 - 20.0, rnd() * 40.0 - 20.0);
+            let s = 0.2 + rnd() * 2.0;
+            if i % 2 == 0 {
+                prims.push(Box::new(Sphere::new(c, s, Material::default_black())));
+            } else {
+                let e = glm::vec3(s, 0.3 + rnd() * 2.0, 0.3 + rnd());
+                prims.push(Box::new(Cube::new(c - e, c + e, Material::default_black())));
+            }
+        }
+        prims
+    }
+
+    fn rays() -> Vec<(glm::Vec3, glm::Vec3)> {
+        let mut seed = 99u32;
+        let mut rnd = move || { seed = seed.wrapping_mul(1664525).wrapping_add(1013904223); (seed >> 8) as f32 / (1 << 24) as f32 };
+        let mut out = vec![
+            (glm::vec3(0.0, 50.0, 0.0), glm::vec3(0.0, -1.0, 0.0)),
+            (glm::vec3(-50.0, 0.0, 0.0), glm::vec3(1.0, 0.0, 0.0)),
+            (glm::vec3(0.0, 0.0, 50.0), glm::vec3(0.0, 0.0, -1.0)),
+        ];
+        for _ in 0..500 {
+            let ro = glm::vec3(rnd() * 60.0 - 30.0, rnd() * 60.0 - 30.0, rnd() * 60.0 - 30.0);
+            let rd = glm::vec3(rnd() * 2.0 - 1.0, rnd() * 2.0 - 1.0, rnd() * 2.0 - 1.0);
+            if rd.norm() > 1e-3 { out.push((ro, rd.normalize())); }
+        }
+        out
+    }
+
+    #[test]
+    fn closest_hit_matches_brute_force() {
+        let prims = scene();
+        let bounds: Vec<Aabb> = prims.iter().map(|p| p.bounds()).collect();
+        let bvh = Bvh::build(&bounds);
+        assert_eq!(bvh.unbounded, vec![0]);
+        for (ro, rd) in rays() {
+            let brute = prims.iter().map(|p| p.ray_intersect(&ro, &rd)).filter(|h| h.is_intersecting)
+                .map(|h| h.distance).fold(f32::INFINITY, f32::min);
+            let hit = bvh.closest_hit(&ro, &rd, f32::INFINITY, |i| prims[i].ray_intersect(&ro, &rd));
+            assert_eq!(hit.is_intersecting, brute.is_finite(), "rayo {:?} {:?}", ro, rd);
+            if hit.is_intersecting { assert!((hit.distance - brute).abs() < 1e-4, "{} vs {}", hit.distance, brute); }
+        }
+    }
+
+    #[test]
+    fn for_each_hit_matches_brute_force() {
+        let prims = scene();
+        let bounds: Vec<Aabb> = prims.iter().map(|p| p.bounds()).collect();
+        let bvh = Bvh::build(&bounds);
+        for (ro, rd) in rays() {
+            let tmax = 35.0;
+            let brute = prims.iter().map(|p| p.ray_intersect(&ro, &rd)).filter(|h| h.is_intersecting && h.distance < tmax).count();
+            let mut got = 0;
+            assert!(bvh.for_each_hit(&ro, &rd, tmax, |i| prims[i].ray_intersect(&ro, &rd), |_| { got += 1; true }));
+            assert_eq!(got, brute, "rayo {:?} {:?}", ro, rd);
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod aabb;
+pub mod bvh;
+pub mod voxel_grid;
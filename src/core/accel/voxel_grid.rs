@@ -0,0 +1,293 @@
+use nalgebra_glm as glm;
+use crate::core::geometry::cube::Cube;
+use crate::core::geometry::RayIntersect;
+use crate::core::intersect::Intersect;
+
+// Marca de celda vacía
+const EMPTY: u32 = u32::MAX;
+// Límite de celdas de la grilla densa (por encima, todo va al camino lineal)
+const MAX_CELLS: usize = 1 << 24;
+// Tolerancia para aceptar el hit dentro de la celda actual
+const CELL_EPS: f32 = 1e-4;
+
+/// Grilla de vóxeles sobre los cubos unitarios alineados a enteros (los que
+/// produce `World::bake`). Se recorre con DDA 3D (Amanatides–Woo), así el costo
+/// por rayo depende de las celdas atravesadas y no de la cantidad de bloques.
+pub struct VoxelGrid {
+    min: (i32, i32, i32), // celda mínima
+    dims: (i32, i32, i32),
+    cells: Vec<u32>, // índice en `cubes` o EMPTY
+}
+
+impl VoxelGrid {
+    pub fn empty() -> Self {
+        Self { min: (0, 0, 0), dims: (0, 0, 0), cells: Vec::new() }
+    }
+
+    /// Construye la grilla. Devuelve también los índices de los cubos que no
+    /// encajan (tamaño distinto de 1, no alineados o celda repetida).
+    pub fn build(cubes: &[Cube]) -> (Self, Vec<usize>) {
+        let mut placed: Vec<((i32, i32, i32), usize)> = Vec::with_capacity(cubes.len());
+        let mut loose = Vec::new();
+
+        for (i, c) in cubes.iter().enumerate() {
+            match unit_cell(c) {
+                Some(cell) => placed.push((cell, i)),
+                None => loose.push(i),
+            }
+        }
+
+        if placed.is_empty() {
+            return (Self::empty(), loose);
+        }
+
+        let mut lo = placed[0].0;
+        let mut hi = placed[0].0;
+        for &((x, y, z), _) in &placed {
+            lo = (lo.0.min(x), lo.1.min(y), lo.2.min(z));
+            hi = (hi.0.max(x), hi.1.max(y), hi.2.max(z));
+        }
+        let dims = (hi.0 - lo.0 + 1, hi.1 - lo.1 + 1, hi.2 - lo.2 + 1);
+        let count = dims.0 as usize * dims.1 as usize * dims.2 as usize;
+
+        // Mundo demasiado disperso: mejor no reservar la grilla
+        if count > MAX_CELLS {
+            loose.extend(placed.iter().map(|&(_, i)| i));
+            loose.sort_unstable();
+            return (Self::empty(), loose);
+        }
+
+        let mut grid = Self { min: lo, dims, cells: vec![EMPTY; count] };
+        for ((x, y, z), i) in placed {
+            let idx = grid.index(x - lo.0, y - lo.1, z - lo.2);
+            if grid.cells[idx] == EMPTY {
+                grid.cells[idx] = i as u32;
+            } else {
+                loose.push(i);
+            }
+        }
+        (grid, loose)
+    }
+
+    /// Hit más cercano contra los cubos de la grilla con distancia < tmax.
+    pub fn closest_hit(&self, cubes: &[Cube], ro: &glm::Vec3, rd: &glm::Vec3, tmax: f32) -> Intersect {
+        let mut best = Intersect::miss();
+        self.walk(ro, rd, tmax, |i, t_exit| {
+            let hit = cubes[i].ray_intersect(ro, rd);
+            // Sólo cuenta si el hit cae dentro de esta celda; si no, seguimos
+            if hit.is_intersecting && hit.distance <= t_exit + CELL_EPS && hit.distance < tmax {
+                best = hit;
+                return false;
+            }
+            true
+        });
+        best
+    }
+
+    /// Visita en orden cada hit con distancia < tmax. `f` devuelve false para cortar.
+    pub fn for_each_hit<F>(&self, cubes: &[Cube], ro: &glm::Vec3, rd: &glm::Vec3, tmax: f32, mut f: F) -> bool
+    where
+        F: FnMut(&Intersect) -> bool,
+    {
+        let mut keep_going = true;
+        self.walk(ro, rd, tmax, |i, _| {
+            let hit = cubes[i].ray_intersect(ro, rd);
+            if hit.is_intersecting && hit.distance < tmax && !f(&hit) {
+                keep_going = false;
+                return false;
+            }
+            true
+        });
+        keep_going
+    }
+
+    /// DDA: llama a `visit(indice_cubo, t_salida_celda)` por cada celda ocupada
+    /// que cruza el rayo, en orden de distancia. `visit` devuelve false para cortar.
+    fn walk<F>(&self, ro: &glm::Vec3, rd: &glm::Vec3, tmax: f32, mut visit: F)
+    where
+        F: FnMut(usize, f32) -> bool,
+    {
+        if self.cells.is_empty() { return; }
+
+        let lo = glm::vec3(self.min.0 as f32, self.min.1 as f32, self.min.2 as f32);
+        let hi = lo + glm::vec3(self.dims.0 as f32, self.dims.1 as f32, self.dims.2 as f32);
+        let inv = glm::vec3(1.0 / rd.x, 1.0 / rd.y, 1.0 / rd.z);
+
+        // Slabs contra la caja de la grilla
+        let mut t0 = 0.0_f32;
+        let mut t1 = tmax;
+        for a in 0..3 {
+            let mut tn = (lo[a] - ro[a]) * inv[a];
+            let mut tf = (hi[a] - ro[a]) * inv[a];
+            if tn > tf { std::mem::swap(&mut tn, &mut tf); }
+            // NaN (rayo paralelo justo sobre el borde) no debe cerrar el intervalo
+            if tn.is_nan() || tf.is_nan() { continue; }
+            t0 = t0.max(tn);
+            t1 = t1.min(tf);
+            if t0 > t1 { return; }
+        }
+
+        // Celda de entrada (en coordenadas locales de la grilla)
+        let p = ro + rd * t0 - lo;
+        let mut cell = [
+            (p.x.floor() as i32).clamp(0, self.dims.0 - 1),
+            (p.y.floor() as i32).clamp(0, self.dims.1 - 1),
+            (p.z.floor() as i32).clamp(0, self.dims.2 - 1),
+        ];
+        let dims = [self.dims.0, self.dims.1, self.dims.2];
+
+        let mut step = [0i32; 3];
+        let mut t_next = [f32::INFINITY; 3];
+        let mut t_delta = [f32::INFINITY; 3];
+        for a in 0..3 {
+            if rd[a] > 0.0 {
+                step[a] = 1;
+                t_next[a] = (lo[a] + (cell[a] + 1) as f32 - ro[a]) * inv[a];
+                t_delta[a] = inv[a];
+            } else if rd[a] < 0.0 {
+                step[a] = -1;
+                t_next[a] = (lo[a] + cell[a] as f32 - ro[a]) * inv[a];
+                t_delta[a] = -inv[a];
+            }
+        }
+
+        loop {
+            // Eje con el próximo cruce de plano
+            let axis = if t_next[0] < t_next[1] {
+                if t_next[0] < t_next[2] { 0 } else { 2 }
+            } else if t_next[1] < t_next[2] { 1 } else { 2 };
+            let t_exit = t_next[axis];
+
+            let c = self.cells[self.index(cell[0], cell[1], cell[2])];
+            if c != EMPTY && !visit(c as usize, t_exit) {
+                return;
+            }
+
+            if t_exit > t1 { return; }
+            cell[axis] += step[axis];
+            if cell[axis] < 0 || cell[axis] >= dims[axis] { return; }
+            t_next[axis] += t_delta[axis];
+        }
+    }
+
+    #[inline]
+    fn index(&self, x: i32, y: i32, z: i32) -> usize {
+        ((z * self.dims.1 + y) * self.dims.0 + x) as usize
+    }
+}
+
+/// Celda entera de un cubo unitario alineado a la grilla, si lo es.
+pub(crate) fn unit_cell(c: &Cube) -> Option<(i32, i32, i32)> {
+    let size = c.max - c.min;
+    let eps = 1e-4;
+    if (size.x - 1.0).abs() > eps || (size.y - 1.0).abs() > eps || (size.z - 1.0).abs() > eps {
+        return None;
+    }
+    let r = glm::vec3(c.min.x.round(), c.min.y.round(), c.min.z.round());
+    if (c.min - r).abs().max() > eps {
+        return None;
+    }
+    Some((r.x as i32, r.y as i32, r.z as i32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::block::BlockKind;
+    use crate::core::material::Material;
+    use crate::core::material_registry::MaterialRegistry;
+    use crate::core::world::World;
+
+    fn cubes() -> Vec<Cube> {
+        let mut reg = MaterialRegistry::new();
+        reg.set(BlockKind::Stone, Material::default_black());
+        reg.set(BlockKind::Dirt, Material::default_black());
+        let mut w = World::new();
+        for x in -3..=3 {
+            for z in -3..=3 {
+                w.set(x, -1, z, BlockKind::Stone);
+            }
+        }
+        for y in 0..4 { w.set(0, y, 0, BlockKind::Dirt); }
+        w.set(2, 0, -2, BlockKind::Dirt);
+        w.set(-2, 2, 1, BlockKind::Dirt); // flotando, con hueco debajo
+        w.bake(&reg)
+    }
+
+    /// Hits de la búsqueda lineal empatados con el más cercano
+    fn brute(cubes: &[Cube], ro: &glm::Vec3, rd: &glm::Vec3) -> Vec<Intersect> {
+        let hits: Vec<Intersect> = cubes.iter().map(|c| c.ray_intersect(ro, rd)).filter(|h| h.is_intersecting).collect();
+        let best = hits.iter().map(|h| h.distance).fold(f32::INFINITY, f32::min);
+        hits.into_iter().filter(|h| h.distance <= best + 1e-4).collect()
+    }
+
+    fn same(a: &Intersect, b: &Intersect) -> bool {
+        (a.distance - b.distance).abs() < 1e-4
+            && a.face == b.face
+            && (a.normal - b.normal).norm() < 1e-5
+            && (a.point - b.point).norm() < 1e-3
+            && (a.uv.0 - b.uv.0).abs() < 1e-3
+            && (a.uv.1 - b.uv.1).abs() < 1e-3
+    }
+
+    fn rays() -> Vec<(glm::Vec3, glm::Vec3)> {
+        let mut out = vec![
+            // Alineados a los ejes, en ambos sentidos
+            (glm::vec3(0.5, 10.0, 0.5), glm::vec3(0.0, -1.0, 0.0)),
+            (glm::vec3(2.5, -10.0, -1.5), glm::vec3(0.0, 1.0, 0.0)),
+            (glm::vec3(-10.0, 0.5, 0.5), glm::vec3(1.0, 0.0, 0.0)),
+            (glm::vec3(10.0, 0.5, -1.5), glm::vec3(-1.0, 0.0, 0.0)),
+            (glm::vec3(0.5, 1.5, 10.0), glm::vec3(0.0, 0.0, -1.0)),
+            (glm::vec3(-1.5, 2.5, -10.0), glm::vec3(0.0, 0.0, 1.0)),
+            // Rasantes sobre el piso y a lo largo de una pared
+            (glm::vec3(-8.0, 0.02, 0.3), glm::vec3(1.0, -0.003, 0.05)),
+            (glm::vec3(0.98, 5.0, -6.0), glm::vec3(0.0005, -0.01, 1.0)),
+            // Desde dentro de la grilla: en una celda vacía y dentro de un bloque
+            (glm::vec3(-1.5, 0.5, -1.5), glm::vec3(-0.3, -1.0, 0.2)),
+            (glm::vec3(-1.5, 2.5, 1.5), glm::vec3(0.0, 1.0, 0.0)),
+            (glm::vec3(0.5, 2.5, 0.5), glm::vec3(0.7, 0.1, -0.2)),
+            // Sin hit
+            (glm::vec3(0.5, 10.0, 0.5), glm::vec3(0.0, 1.0, 0.0)),
+            (glm::vec3(20.0, 0.5, 20.0), glm::vec3(1.0, 0.2, 0.0)),
+        ];
+        // Pseudoaleatorios desde afuera y desde dentro de la caja del mundo
+        let mut seed = 12345u32;
+        let mut rnd = || { seed = seed.wrapping_mul(1664525).wrapping_add(1013904223); (seed >> 8) as f32 / (1 << 24) as f32 };
+        for _ in 0..400 {
+            let ro = glm::vec3(rnd() * 16.0 - 8.0, rnd() * 12.0 - 4.0, rnd() * 16.0 - 8.0);
+            let rd = glm::vec3(rnd() * 2.0 - 1.0, rnd() * 2.0 - 1.0, rnd() * 2.0 - 1.0);
+            if rd.norm() > 1e-3 { out.push((ro, rd.normalize())); }
+        }
+        out
+    }
+
+    #[test]
+    fn closest_hit_matches_linear_scan() {
+        let cubes = cubes();
+        let (grid, loose) = VoxelGrid::build(&cubes);
+        assert!(loose.is_empty());
+        for (ro, rd) in rays() {
+            let expected = brute(&cubes, &ro, &rd);
+            let hit = grid.closest_hit(&cubes, &ro, &rd, f32::INFINITY);
+            assert_eq!(hit.is_intersecting, !expected.is_empty(), "rayo {:?} {:?}", ro, rd);
+            if hit.is_intersecting {
+                assert!(expected.iter().any(|e| same(&hit, e)), "rayo {:?} {:?}: {} vs {}", ro, rd, hit.distance, expected[0].distance);
+            }
+        }
+    }
+
+    #[test]
+    fn for_each_hit_visits_every_cube() {
+        let cubes = cubes();
+        let (grid, _) = VoxelGrid::build(&cubes);
+        for (ro, rd) in rays() {
+            let mut expected: Vec<f32> = cubes.iter().map(|c| c.ray_intersect(&ro, &rd)).filter(|h| h.is_intersecting).map(|h| h.distance).collect();
+            let mut got = Vec::new();
+            grid.for_each_hit(&cubes, &ro, &rd, f32::INFINITY, |h| { got.push(h.distance); true });
+            expected.sort_by(f32::total_cmp);
+            got.sort_by(f32::total_cmp);
+            assert_eq!(got.len(), expected.len(), "rayo {:?} {:?}", ro, rd);
+            assert!(got.iter().zip(&expected).all(|(a, b)| (a - b).abs() < 1e-4));
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Oclusión ambiental para el término ambiente del Whitted: rayos cortos sobre
+//! el hemisferio de la normal y, opcionalmente, la oclusión por vértice estilo
+//! Minecraft que `World::bake` guarda en cada `Cube`.
+//!
+//! El path tracer no la usa: ahí la oclusión sale sola de los rebotes.
+
+use nalgebra_glm as glm;
+use super::renderer::shadow_visibility;
+use super::sampling;
+use super::scene::Scene;
+
+/// Configuración de la oclusión ambiental del `Renderer`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AmbientOcclusion {
+    pub samples: u32, // rayos por punto (0 = sin AO trazada)
+    pub radius: f32,  // distancia máxima a la que algo ocluye
+    pub voxel: bool,  // multiplica por la AO por vértice de los bloques
+}
+
+impl Default for AmbientOcclusion {
+    fn default() -> Self {
+        // La AO trazada cuesta rayos por punto: se pide aparte; la por vértice sale del bake
+        Self { samples: 0, radius: 1.0, voxel: true }
+    }
+}
+
+impl AmbientOcclusion {
+    /// Fracción del hemisferio de `n` visible desde `p` (1 = nada cerca)
+    pub(crate) fn ray_traced(&self, scene: &Scene, p: glm::Vec3, n: glm::Vec3) -> f32 {
+        if self.samples == 0 || self.radius <= 0.0 { return 1.0; }
+        let side = (self.samples as f32).sqrt().ceil() as u32;
+        let count = side * side;
+        let seed = sampling::point_seed(&p);
+
+        // Base ortonormal alrededor de la normal
+        let helper = if n.x.abs() > 0.9 { glm::vec3(0.0, 1.0, 0.0) } else { glm::vec3(1.0, 0.0, 0.0) };
+        let t = glm::normalize(&glm::cross(&helper, &n));
+        let b = glm::cross(&n, &t);
+
+        let mut open = 0.0;
+        for k in 0..count {
+            // Muestreo coseno estratificado: la AO pondera como un difuso
+            let [u1, u2] = sampling::stratified(k, side, seed);
+            let r = u1.sqrt();
+            let phi = 2.0 * std::f32::consts::PI * u2;
+            let dir = t * (r * phi.cos()) + b * (r * phi.sin()) + n * (1.0 - u1).max(0.0).sqrt();
+            open += shadow_visibility(scene, p, n, glm::normalize(&dir), self.radius);
+        }
+        open / count as f32
+    }
+}
+
+/// Brillo para cada nivel de AO por vértice (0 = esquina encerrada, 3 = libre)
+const VOXEL_AO_CURVE: [f32; 4] = [0.45, 0.65, 0.85, 1.0];
+
+/// Nivel de AO por vértice (ya interpolado) a factor de brillo
+pub(crate) fn voxel_factor(level: f32) -> f32 {
+    let level = level.clamp(0.0, 3.0);
+    let i = (level.floor() as usize).min(2);
+    let f = level - i as f32;
+    VOXEL_AO_CURVE[i] + (VOXEL_AO_CURVE[i + 1] - VOXEL_AO_CURVE[i]) * f
+}
@@ -10,8 +10,46 @@ pub enum BlockKind {
     Wood,
     Leaves,
     Iron,
+    /// Tipo definido por datos (archivos de escena / importadores)
+    Custom(u16),
+}
+
+impl BlockKind {
+    /// Tipos predefinidos con el nombre que se usa en archivos de escena
+    pub const BUILTIN: [(&'static str, BlockKind); 10] = [
+        ("grass", BlockKind::Grass),
+        ("stone", BlockKind::Stone),
+        ("dirt", BlockKind::Dirt),
+        ("lava", BlockKind::Lava),
+        ("diamond", BlockKind::Diamond),
+        ("air", BlockKind::Air),
+        ("water", BlockKind::Water),
+        ("wood", BlockKind::Wood),
+        ("leaves", BlockKind::Leaves),
+        ("iron", BlockKind::Iron),
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::BUILTIN.iter().find(|(n, _)| *n == name).map(|&(_, k)| k)
+    }
+
+    /// Nombre para archivos exportados (`custom_3` para los definidos por datos)
+    pub fn name(self) -> String {
+        match Self::BUILTIN.iter().find(|&&(_, k)| k == self) {
+            Some((n, _)) => n.to_string(),
+            None => match self {
+                BlockKind::Custom(n) => format!("custom_{}", n),
+                _ => unreachable!("todos los tipos predefinidos están en BUILTIN"),
+            },
+        }
+    }
+
+    /// Inversa de `name` (acepta `custom_N`)
+    pub fn from_export_name(name: &str) -> Option<Self> {
+        Self::from_name(name).or_else(|| name.strip_prefix("custom_")?.parse().ok().map(BlockKind::Custom))
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
 pub struct Block { pub kind: BlockKind }
-impl Block { pub fn new(kind: BlockKind) -> Self { Self { kind } } }
\ No newline at end of file
+impl Block { pub fn new(kind: BlockKind) -> Self { Self { kind } } }
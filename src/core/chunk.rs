@@ -0,0 +1,210 @@
+//! Chunk de 16³ celdas con paleta: cada celda guarda un índice a la paleta
+//! empaquetado en 1, 2, 4, 8 o 16 bits (sin cruzar de un u64 al siguiente).
+//! El índice 0 es siempre "sin bloque", así que un chunk recién creado no
+//! ocupa más que su paleta.
+
+use crate::core::block::BlockKind;
+
+pub const CHUNK_SIZE: i32 = 16;
+pub const CHUNK_SHIFT: u32 = 4;
+pub const CHUNK_VOLUME: usize = 16 * 16 * 16;
+
+#[derive(Clone, Debug)]
+pub struct Chunk {
+    palette: Vec<Option<BlockKind>>,
+    bits: u32, // 0 mientras la paleta sólo tiene el vacío
+    data: Vec<u64>,
+    len: usize, // celdas ocupadas
+}
+
+impl Default for Chunk {
+    fn default() -> Self { Self::new() }
+}
+
+impl Chunk {
+    pub fn new() -> Self {
+        Self { palette: vec![None], bits: 0, data: Vec::new(), len: 0 }
+    }
+
+    /// Índice de la celda local (x, y, z) ∈ [0, 16)³, en orden Y, Z, X
+    #[inline]
+    pub fn index(x: i32, y: i32, z: i32) -> usize {
+        ((y as usize) << 8) | ((z as usize) << 4) | x as usize
+    }
+
+    /// Celda local para un índice (inversa de `index`)
+    #[inline]
+    pub fn local(i: usize) -> (i32, i32, i32) {
+        ((i & 15) as i32, (i >> 8) as i32, (i >> 4 & 15) as i32)
+    }
+
+    /// Celdas con bloque
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Entradas de la paleta (la 0 es el vacío); pueden quedar entradas sin uso
+    pub fn palette(&self) -> &[Option<BlockKind>] { &self.palette }
+
+    /// Bits por celda del empaquetado actual
+    pub fn bits(&self) -> u32 { self.bits }
+
+    #[inline]
+    pub fn get(&self, i: usize) -> Option<BlockKind> {
+        self.palette[self.slot(i)]
+    }
+
+    /// Índice de paleta de la celda `i`
+    #[inline]
+    pub fn slot(&self, i: usize) -> usize {
+        if self.bits == 0 { return 0; }
+        let per_word = 64 / self.bits as usize;
+        let mask = (1u64 << self.bits) - 1;
+        ((self.data[i / per_word] >> ((i % per_word) as u32 * self.bits)) & mask) as usize
+    }
+
+    /// Cambia la celda y devuelve lo que había
+    pub fn set(&mut self, i: usize, kind: Option<BlockKind>) -> Option<BlockKind> {
+        let old = self.get(i);
+        if old == kind { return old; }
+        let slot = match self.palette.iter().position(|&p| p == kind) {
+            Some(s) => s,
+            None => self.add_to_palette(kind),
+        };
+        self.write(i, slot);
+        match (old, kind) {
+            (None, Some(_)) => self.len += 1,
+            (Some(_), None) => self.len -= 1,
+            _ => {}
+        }
+        old
+    }
+
+    /// Celdas ocupadas en orden Y, Z, X
+    pub fn iter(&self) -> impl Iterator<Item = (usize, BlockKind)> + '_ {
+        (0..CHUNK_VOLUME).filter_map(move |i| self.get(i).map(|k| (i, k)))
+    }
+
+    /// Bytes en el heap (paleta y celdas)
+    pub fn heap_bytes(&self) -> usize {
+        self.palette.capacity() * std::mem::size_of::<Option<BlockKind>>() + self.data.capacity() * 8
+    }
+
+    fn write(&mut self, i: usize, slot: usize) {
+        let per_word = 64 / self.bits as usize;
+        let shift = (i % per_word) as u32 * self.bits;
+        let mask = ((1u64 << self.bits) - 1) << shift;
+        let w = &mut self.data[i / per_word];
+        *w = (*w & !mask) | ((slot as u64) << shift);
+    }
+
+    fn add_to_palette(&mut self, kind: Option<BlockKind>) -> usize {
+        if self.palette.len() >= 1 << self.bits {
+            // Antes de ensanchar, se descartan las entradas que ya nadie usa
+            self.compact();
+            if self.palette.len() >= 1 << self.bits {
+                let bits = match self.bits { 0 => 1, 1 => 2, 2 => 4, 4 => 8, _ => 16 };
+                self.repack(bits, |s| s);
+            }
+        }
+        self.palette.push(kind);
+        self.palette.len() - 1
+    }
+
+    fn compact(&mut self) {
+        if self.bits == 0 { return; }
+        let mut used = vec![false; self.palette.len()];
+        used[0] = true;
+        for i in 0..CHUNK_VOLUME { used[self.slot(i)] = true; }
+        if used.iter().all(|&u| u) { return; }
+
+        let mut remap = vec![0; self.palette.len()];
+        let mut palette = Vec::with_capacity(self.palette.len());
+        for (s, &u) in used.iter().enumerate() {
+            if u {
+                remap[s] = palette.len();
+                palette.push(self.palette[s]);
+            }
+        }
+        self.palette = palette;
+        self.repack(self.bits, |s| remap[s]);
+    }
+
+    /// Reescribe las celdas con `bits` por entrada, pasando cada índice por `map`
+    fn repack(&mut self, bits: u32, map: impl Fn(usize) -> usize) {
+        let slots: Vec<usize> = (0..CHUNK_VOLUME).map(|i| map(self.slot(i))).collect();
+        self.bits = bits;
+        self.data = vec![0; CHUNK_VOLUME * bits as usize / 64];
+        for (i, s) in slots.into_iter().enumerate() {
+            if s != 0 { self.write(i, s); }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits_for(kinds: usize) -> u32 {
+        match kinds { 0 => 0, 1 => 1, 2..=3 => 2, 4..=15 => 4, 16..=255 => 8, _ => 16 }
+    }
+
+    #[test]
+    fn palette_grows_through_every_width() {
+        let mut c = Chunk::new();
+        assert_eq!(c.bits(), 0);
+        let mut seen = Vec::new();
+        for n in 0..300u16 {
+            // Las celdas salteadas obligan a que el reempaquetado conserve todo
+            let i = n as usize * 13 % CHUNK_VOLUME;
+            assert_eq!(c.set(i, Some(BlockKind::Custom(n))), None);
+            seen.push(c.bits());
+            assert_eq!(c.bits(), bits_for(n as usize + 1), "con {} tipos", n + 1);
+            assert_eq!(c.len(), n as usize + 1);
+        }
+        for w in [1, 2, 4, 8, 16] { assert!(seen.contains(&w)); }
+        for n in 0..300u16 {
+            assert_eq!(c.get(n as usize * 13 % CHUNK_VOLUME), Some(BlockKind::Custom(n)));
+        }
+        assert_eq!(c.iter().count(), 300);
+        assert_eq!(c.get(1), None);
+    }
+
+    #[test]
+    fn compact_reuses_freed_entries() {
+        let mut c = Chunk::new();
+        c.set(0, Some(BlockKind::Stone));
+        c.set(1, Some(BlockKind::Dirt));
+        c.set(2, Some(BlockKind::Grass));
+        assert_eq!((c.bits(), c.palette().len()), (2, 4));
+
+        // Sin bloques de Dirt ni Grass sus entradas quedan sin uso...
+        c.set(1, None);
+        c.set(2, Some(BlockKind::Stone));
+        assert_eq!(c.palette().len(), 4);
+        // ...y al llenarse la paleta se compacta en vez de ensanchar
+        c.set(3, Some(BlockKind::Water));
+        c.set(4, Some(BlockKind::Lava));
+        assert_eq!(c.bits(), 2);
+        assert_eq!(c.palette(), &[None, Some(BlockKind::Stone), Some(BlockKind::Water), Some(BlockKind::Lava)]);
+        assert_eq!([0, 1, 2, 3, 4].map(|i| c.get(i)),
+            [Some(BlockKind::Stone), None, Some(BlockKind::Stone), Some(BlockKind::Water), Some(BlockKind::Lava)]);
+        assert_eq!(c.len(), 4);
+
+        // Con todas las entradas en uso sí se ensancha
+        c.set(5, Some(BlockKind::Dirt));
+        assert_eq!((c.bits(), c.palette().len()), (4, 5));
+        assert_eq!(c.get(4), Some(BlockKind::Lava));
+    }
+
+    #[test]
+    fn index_and_local_are_inverse() {
+        for i in 0..CHUNK_VOLUME {
+            let (x, y, z) = Chunk::local(i);
+            assert_eq!(Chunk::index(x, y, z), i);
+        }
+        assert_eq!(Chunk::index(1, 0, 0), 1);
+        assert_eq!(Chunk::index(0, 0, 1), 16);
+        assert_eq!(Chunk::index(0, 1, 0), 256);
+    }
+}
@@ -1,3 +1,4 @@
+use std::sync::OnceLock;
 use nalgebra_glm as glm;
 
 #[derive(Clone, Copy, Debug)]
@@ -16,4 +17,40 @@ impl Color {
         let b = (v.z.clamp(0.0, 1.0) * 255.0).round() as u8;
         Self { r, g, b }
     }
-}
\ No newline at end of file
+
+    /// Interpreta el color como sRGB y lo decodifica a RGB lineal
+    pub fn to_linear(self) -> glm::Vec3 {
+        let lut = srgb_lut();
+        glm::vec3(lut[self.r as usize], lut[self.g as usize], lut[self.b as usize])
+    }
+
+    /// Codifica RGB lineal (ya en [0,1]) a sRGB de 8 bits
+    pub fn from_linear(v: &glm::Vec3) -> Self {
+        Self::from_vec3(&glm::vec3(srgb_encode(v.x), srgb_encode(v.y), srgb_encode(v.z)))
+    }
+}
+
+/// Curva sRGB: lineal -> codificado
+#[inline]
+pub fn srgb_encode(x: f32) -> f32 {
+    let x = x.clamp(0.0, 1.0);
+    if x <= 0.003_130_8 { 12.92 * x } else { 1.055 * x.powf(1.0 / 2.4) - 0.055 }
+}
+
+/// Curva sRGB: codificado -> lineal
+#[inline]
+pub fn srgb_decode(x: f32) -> f32 {
+    if x <= 0.040_45 { x / 12.92 } else { ((x + 0.055) / 1.055).powf(2.4) }
+}
+
+// Tabla de decodificación para los 256 valores de 8 bits
+fn srgb_lut() -> &'static [f32; 256] {
+    static LUT: OnceLock<[f32; 256]> = OnceLock::new();
+    LUT.get_or_init(|| {
+        let mut t = [0.0; 256];
+        for (i, v) in t.iter_mut().enumerate() {
+            *v = srgb_decode(i as f32 / 255.0);
+        }
+        t
+    })
+}
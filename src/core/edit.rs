@@ -0,0 +1,348 @@
+//! Operaciones de edición sobre `World` al estilo de WorldEdit: cajas llenas
+//! o huecas, esferas, cilindros, líneas, reemplazo dentro de una caja y
+//! copiar/pegar regiones (con giros y espejos).
+//!
+//! Las cajas son inclusivas y aceptan las esquinas en cualquier orden. Todas
+//! las operaciones pasan por `World::set`/`World::remove`, devuelven cuántas
+//! celdas cambiaron y son un solo paso del historial (ver `history`).
+
+use crate::core::block::BlockKind;
+use crate::core::world::World;
+
+type Pos = (i32, i32, i32);
+
+// Tope de celdas de una `Region` (1 GiB de celdas); también mantiene los
+// índices dentro de i32
+const MAX_REGION_CELLS: usize = 1 << 28;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis { X, Y, Z }
+
+/// Bloques copiados de una caja, relativos a su esquina mínima (`World::copy`)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Region {
+    size: Pos,
+    cells: Vec<Option<BlockKind>>, // orden Y, Z, X; None = celda vacía
+}
+
+impl Region {
+    /// Región vacía de `size` celdas. Entra en pánico con más de 2²⁸ celdas.
+    pub fn new(size: Pos) -> Self {
+        let size = (size.0.max(0), size.1.max(0), size.2.max(0));
+        let volume = volume(size).unwrap_or_else(|| panic!("región de {:?} celdas demasiado grande", size));
+        Self { size, cells: vec![None; volume] }
+    }
+
+    pub fn size(&self) -> Pos { self.size }
+
+    pub fn get(&self, x: i32, y: i32, z: i32) -> Option<BlockKind> {
+        self.index(x, y, z).and_then(|i| self.cells[i])
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, z: i32, kind: Option<BlockKind>) {
+        if let Some(i) = self.index(x, y, z) { self.cells[i] = kind; }
+    }
+
+    /// Celdas con bloque, relativas a la esquina mínima
+    pub fn blocks(&self) -> impl Iterator<Item = (Pos, BlockKind)> + '_ {
+        let (sx, _, sz) = self.size;
+        self.cells.iter().enumerate().filter_map(move |(i, c)| {
+            let i = i as i32;
+            c.map(|k| ((i % sx, i / (sx * sz), i / sx % sz), k))
+        })
+    }
+
+    /// Gira `quarter_turns` cuartos de vuelta alrededor de +Y; cada uno lleva
+    /// +X a +Z. La región sigue empezando en (0, 0, 0).
+    pub fn rotated_y(&self, quarter_turns: i32) -> Self {
+        let mut out = self.clone();
+        for _ in 0..quarter_turns.rem_euclid(4) {
+            let (sx, sy, sz) = out.size;
+            let mut r = Self::new((sz, sy, sx));
+            for ((x, y, z), k) in out.blocks() {
+                r.set(sz - 1 - z, y, x, Some(k));
+            }
+            out = r;
+        }
+        out
+    }
+
+    /// Espejo a lo largo de `axis`
+    pub fn mirrored(&self, axis: Axis) -> Self {
+        let (sx, sy, sz) = self.size;
+        let mut r = Self::new(self.size);
+        for ((x, y, z), k) in self.blocks() {
+            let p = match axis {
+                Axis::X => (sx - 1 - x, y, z),
+                Axis::Y => (x, sy - 1 - y, z),
+                Axis::Z => (x, y, sz - 1 - z),
+            };
+            r.set(p.0, p.1, p.2, Some(k));
+        }
+        r
+    }
+
+    fn index(&self, x: i32, y: i32, z: i32) -> Option<usize> {
+        let (sx, sy, sz) = self.size;
+        if x < 0 || y < 0 || z < 0 || x >= sx || y >= sy || z >= sz { return None; }
+        Some(((y * sz + z) * sx + x) as usize)
+    }
+}
+
+impl World {
+    /// Llena la caja `a`..=`b` con `kind`
+    pub fn fill(&mut self, a: Pos, b: Pos, kind: BlockKind) -> usize {
+        self.batch(|w| {
+            let mut n = 0;
+            for_box(a, b, |p| n += w.put(p, kind) as usize);
+            n
+        })
+    }
+
+    /// Caja con paredes, piso y techo de `kind` y el interior vacío
+    pub fn hollow(&mut self, a: Pos, b: Pos, kind: BlockKind) -> usize {
+        let (lo, hi) = sorted(a, b);
+        self.batch(|w| {
+            let mut n = 0;
+            for_box(lo, hi, |p| {
+                let shell = p.0 == lo.0 || p.0 == hi.0 || p.1 == lo.1 || p.1 == hi.1 || p.2 == lo.2 || p.2 == hi.2;
+                let changed = if shell { w.put(p, kind) } else { w.remove(p.0, p.1, p.2).is_some() };
+                n += changed as usize;
+            });
+            n
+        })
+    }
+
+    /// Vacía la caja `a`..=`b`
+    pub fn clear_box(&mut self, a: Pos, b: Pos) -> usize {
+        let cells: Vec<Pos> = self.blocks_in(a, b).map(|(p, _)| p).collect();
+        self.batch(|w| for &(x, y, z) in &cells { w.remove(x, y, z); });
+        cells.len()
+    }
+
+    /// Esfera de centro `center` (centro del bloque) y radio `radius`; con
+    /// `hollow` sólo la cáscara de un bloque de espesor
+    pub fn sphere(&mut self, center: Pos, radius: f32, kind: BlockKind, hollow: bool) -> usize {
+        let r = radius.max(0.0);
+        let inside = |d: Pos| within(d, r);
+        let e = r.floor() as i32;
+        self.batch(|w| {
+            let mut n = 0;
+            for_box((-e, -e, -e), (e, e, e), |d| {
+                if !inside(d) { return; }
+                if hollow && NEIGHBOURS.iter().all(|o| inside((d.0 + o.0, d.1 + o.1, d.2 + o.2))) { return; }
+                n += w.put((center.0 + d.0, center.1 + d.1, center.2 + d.2), kind) as usize;
+            });
+            n
+        })
+    }
+
+    /// Cilindro vertical de `height` bloques hacia arriba desde `base` (centro
+    /// de la base); con `hollow` sólo el anillo exterior, sin tapas
+    pub fn cylinder(&mut self, base: Pos, radius: f32, height: i32, kind: BlockKind, hollow: bool) -> usize {
+        let r = radius.max(0.0);
+        let inside = |dx: i32, dz: i32| within((dx, 0, dz), r);
+        let e = r.floor() as i32;
+        self.batch(|w| {
+            let mut n = 0;
+            for dy in 0..height.max(0) {
+                for dz in -e..=e {
+                    for dx in -e..=e {
+                        if !inside(dx, dz) { continue; }
+                        if hollow && inside(dx + 1, dz) && inside(dx - 1, dz) && inside(dx, dz + 1) && inside(dx, dz - 1) { continue; }
+                        n += w.put((base.0 + dx, base.1 + dy, base.2 + dz), kind) as usize;
+                    }
+                }
+            }
+            n
+        })
+    }
+
+    /// Línea de bloques de `a` a `b`, ambos incluidos (sin huecos en el eje más largo)
+    pub fn line(&mut self, a: Pos, b: Pos, kind: BlockKind) -> usize {
+        // En i64: entre extremos lejanos la diferencia no entra en i32
+        let d = (b.0 as i64 - a.0 as i64, b.1 as i64 - a.1 as i64, b.2 as i64 - a.2 as i64);
+        let steps = d.0.abs().max(d.1.abs()).max(d.2.abs());
+        self.batch(|w| {
+            let mut n = 0;
+            for i in 0..=steps {
+                let t = if steps == 0 { 0.0 } else { i as f64 / steps as f64 };
+                let at = |s: i32, d: i64| (s as i64 + (d as f64 * t).round() as i64) as i32;
+                n += w.put((at(a.0, d.0), at(a.1, d.1), at(a.2, d.2)), kind) as usize;
+            }
+            n
+        })
+    }
+
+    /// Cambia los bloques `from` por `to` dentro de la caja
+    pub fn replace(&mut self, a: Pos, b: Pos, from: BlockKind, to: BlockKind) -> usize {
+        if from == to { return 0; }
+        let cells: Vec<Pos> = self.blocks_in(a, b).filter(|&(_, k)| k == from).map(|(p, _)| p).collect();
+        self.batch(|w| for &(x, y, z) in &cells { w.set(x, y, z, to); });
+        cells.len()
+    }
+
+    /// Copia la caja `a`..=`b` (incluidas las celdas vacías). Error si la caja
+    /// tiene más de 2²⁸ celdas.
+    pub fn copy(&self, a: Pos, b: Pos) -> Result<Region, String> {
+        let (lo, hi) = sorted(a, b);
+        // En i64: entre i32::MIN e i32::MAX hay más de i32::MAX celdas
+        let extent = |l: i32, h: i32| h as i64 - l as i64 + 1;
+        let size = (extent(lo.0, hi.0), extent(lo.1, hi.1), extent(lo.2, hi.2));
+        let fits = |e: i64| i32::try_from(e).ok();
+        let size = match (fits(size.0), fits(size.1), fits(size.2)) {
+            (Some(x), Some(y), Some(z)) if volume((x, y, z)).is_some() => (x, y, z),
+            _ => return Err(format!("la caja de {}x{}x{} bloques es demasiado grande para copiarla", size.0, size.1, size.2)),
+        };
+        let mut r = Region::new(size);
+        for ((x, y, z), k) in self.blocks_in(lo, hi) {
+            r.set(x - lo.0, y - lo.1, z - lo.2, Some(k));
+        }
+        Ok(r)
+    }
+
+    /// Pega `region` con su esquina mínima en `at`; las celdas vacías de la
+    /// región no borran lo que ya hay
+    pub fn paste(&mut self, region: &Region, at: Pos) -> usize {
+        self.batch(|w| region.blocks().map(|((x, y, z), k)| w.put((at.0 + x, at.1 + y, at.2 + z), k) as usize).sum())
+    }
+
+    // `set` que avisa si la celda cambió
+    fn put(&mut self, p: Pos, kind: BlockKind) -> bool {
+        if self.get(p.0, p.1, p.2) == Some(kind) { return false; }
+        self.set(p.0, p.1, p.2, kind);
+        true
+    }
+}
+
+const NEIGHBOURS: [Pos; 6] = [(-1, 0, 0), (1, 0, 0), (0, -1, 0), (0, 1, 0), (0, 0, -1), (0, 0, 1)];
+
+// ¿El offset `d` está a distancia `r` o menos? En i64: con radios de decenas
+// de miles x² + y² + z² no entra en i32
+fn within(d: Pos, r: f32) -> bool {
+    let (x, y, z) = (d.0 as i64, d.1 as i64, d.2 as i64);
+    (x * x + y * y + z * z) as f64 <= r as f64 * r as f64
+}
+
+// Celdas de una región de `size` (no negativo), si no pasa de `MAX_REGION_CELLS`
+fn volume(size: Pos) -> Option<usize> {
+    (size.0 as usize).checked_mul(size.1 as usize)
+        .and_then(|v| v.checked_mul(size.2 as usize))
+        .filter(|&v| v <= MAX_REGION_CELLS)
+}
+
+fn sorted(a: Pos, b: Pos) -> (Pos, Pos) {
+    ((a.0.min(b.0), a.1.min(b.1), a.2.min(b.2)), (a.0.max(b.0), a.1.max(b.1), a.2.max(b.2)))
+}
+
+fn for_box(a: Pos, b: Pos, mut f: impl FnMut(Pos)) {
+    let (lo, hi) = sorted(a, b);
+    for y in lo.1..=hi.1 {
+        for z in lo.2..=hi.2 {
+            for x in lo.0..=hi.0 {
+                f((x, y, z));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Región de 3×2×4 sin simetrías: cada celda ocupada tiene su propio tipo
+    fn asymmetric() -> Region {
+        let mut r = Region::new((3, 2, 4));
+        for (i, p) in [(0, 0, 0), (2, 0, 0), (1, 1, 3), (0, 1, 2), (2, 0, 3)].into_iter().enumerate() {
+            r.set(p.0, p.1, p.2, Some(BlockKind::Custom(i as u16)));
+        }
+        r
+    }
+
+    #[test]
+    fn rotation_and_mirror_identities() {
+        let r = asymmetric();
+        assert_eq!(r.rotated_y(4), r);
+        assert_eq!(r.rotated_y(0), r);
+        assert_eq!(r.rotated_y(1).rotated_y(3), r);
+        assert_eq!(r.rotated_y(-1), r.rotated_y(3));
+        assert_ne!(r.rotated_y(2), r);
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            assert_eq!(r.mirrored(axis).mirrored(axis), r);
+            assert_ne!(r.mirrored(axis), r);
+        }
+        // Dos espejos horizontales son media vuelta
+        assert_eq!(r.mirrored(Axis::X).mirrored(Axis::Z), r.rotated_y(2));
+    }
+
+    #[test]
+    fn quarter_turn_carries_x_to_z() {
+        let mut r = Region::new((3, 1, 1));
+        r.set(2, 0, 0, Some(BlockKind::Stone));
+        let q = r.rotated_y(1);
+        assert_eq!(q.size(), (1, 1, 3));
+        assert_eq!(q.blocks().collect::<Vec<_>>(), [((0, 0, 2), BlockKind::Stone)]);
+        assert_eq!(asymmetric().rotated_y(1).size(), (4, 2, 3));
+    }
+
+    #[test]
+    fn paste_of_copy_reproduces_box() {
+        let mut world = World::new();
+        world.fill((-3, 0, -3), (3, 2, 3), BlockKind::Stone);
+        world.sphere((0, 4, 0), 2.5, BlockKind::Leaves, false);
+        world.line((-5, -1, 7), (6, 5, -4), BlockKind::Wood);
+        world.clear_box((-1, 1, -1), (1, 1, 1));
+
+        let (a, b) = ((-4, -1, -2), (5, 6, 4));
+        let region = world.copy(b, a).unwrap();
+        assert_eq!(region.size(), (10, 8, 7));
+        assert_eq!(region.blocks().count(), world.blocks_in(a, b).count());
+
+        // En un mundo vacío (lejos y con coordenadas negativas) queda la misma caja
+        let at = (-40, -20, 33);
+        let mut other = World::new();
+        assert_eq!(other.paste(&region, at), region.blocks().count());
+        let shifted: Vec<_> = world.blocks_in(a, b).map(|(p, k)| ((p.0 - a.0 + at.0, p.1 - a.1 + at.1, p.2 - a.2 + at.2), k)).collect();
+        let mut pasted: Vec<_> = other.blocks().collect();
+        let mut want = shifted.clone();
+        pasted.sort_by_key(|&(p, _)| p);
+        want.sort_by_key(|&(p, _)| p);
+        assert_eq!(pasted, want);
+        assert_eq!(other.copy(at, (at.0 + 9, at.1 + 7, at.2 + 6)), Ok(region.clone()));
+
+        // Sobre el original no cambia nada, y las celdas vacías no borran
+        assert_eq!(world.paste(&region, a), 0);
+        world.set(0, 1, 0, BlockKind::Diamond);
+        world.paste(&region, a);
+        assert_eq!(world.get(0, 1, 0), Some(BlockKind::Diamond));
+    }
+
+    #[test]
+    #[should_panic(expected = "demasiado grande")]
+    fn huge_region_panics() {
+        Region::new((1 << 16, 1 << 16, 2));
+    }
+
+    #[test]
+    fn huge_copy_is_an_error() {
+        let world = World::new();
+        assert_eq!(world.copy((i32::MIN, 0, 0), (i32::MAX, 0, 0)),
+                   Err("la caja de 4294967296x1x1 bloques es demasiado grande para copiarla".to_string()));
+        assert_eq!(world.copy((0, 0, 0), (1 << 14, 1 << 14, 0)),
+                   Err("la caja de 16385x16385x1 bloques es demasiado grande para copiarla".to_string()));
+    }
+
+    #[test]
+    fn large_coordinates_do_not_overflow() {
+        // Radio 50 000: x² + y² + z² pasa de i32::MAX
+        assert!(within((30_000, 30_000, 30_000), 52_000.0));
+        assert!(!within((30_000, 30_000, 30_000), 51_000.0));
+        assert!(within((-46_341, 0, 0), 46_341.0));
+
+        let mut world = World::new();
+        assert_eq!(world.line((i32::MIN, 0, 0), (i32::MIN + 3, 0, 0), BlockKind::Stone), 4);
+        assert_eq!(world.line((i32::MAX, 5, 0), (i32::MAX - 2, 5, 2), BlockKind::Wood), 3);
+        assert_eq!(world.get(i32::MAX - 1, 5, 1), Some(BlockKind::Wood));
+        assert_eq!(world.get(i32::MIN + 3, 0, 0), Some(BlockKind::Stone));
+    }
+}
@@ -0,0 +1,181 @@
+//! Luz de entorno: el fondo de la escena (skybox o cielo procedural) discretizado
+//! en un mapa lat-long con una distribución 2D proporcional a su luminancia,
+//! para muestrear con importancia las zonas brillantes (sol poniente, nubes
+//! iluminadas) en vez de disparar rayos uniformes al hemisferio.
+//!
+//! El mapa sólo decide *hacia dónde* muestrear; la radiancia se vuelve a leer
+//! del fondo real para no perder detalle.
+
+use std::f32::consts::PI;
+use nalgebra_glm as glm;
+use super::tonemap::luminance;
+
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    // CDF de filas (height + 1) y, por fila, CDF de columnas (width + 1)
+    marginal: Vec<f32>,
+    conditional: Vec<f32>,
+    // Peso de cada texel normalizado para que la densidad en UV tenga integral 1
+    density: Vec<f32>,
+}
+
+impl EnvironmentMap {
+    /// Discretiza `radiance(dir)` en `width`×`height` texels lat-long. None si el
+    /// entorno es negro (no hay nada que muestrear).
+    pub fn build<F>(width: usize, height: usize, radiance: F) -> Option<Self>
+    where
+        F: Fn(glm::Vec3) -> glm::Vec3,
+    {
+        let (width, height) = (width.max(1), height.max(1));
+        let mut weights = Vec::with_capacity(width * height);
+        for y in 0..height {
+            // sin θ: las filas cerca de los polos cubren menos ángulo sólido
+            let theta = (y as f32 + 0.5) / height as f32 * PI;
+            for x in 0..width {
+                let dir = direction((x as f32 + 0.5) / width as f32, (y as f32 + 0.5) / height as f32);
+                weights.push(luminance(&radiance(dir)) * theta.sin());
+            }
+        }
+        let total: f32 = weights.iter().sum();
+        if total <= 0.0 || !total.is_finite() { return None; }
+        // Piso pequeño: ningún texel con luz real queda con probabilidad 0
+        let floor = total / weights.len() as f32 * 0.01;
+        for w in weights.iter_mut() { *w = w.max(floor); }
+
+        let mut conditional = Vec::with_capacity(height * (width + 1));
+        let mut marginal = Vec::with_capacity(height + 1);
+        marginal.push(0.0);
+        for row in weights.chunks_exact(width) {
+            let start = conditional.len();
+            conditional.push(0.0);
+            let mut acc = 0.0;
+            for &w in row {
+                acc += w;
+                conditional.push(acc);
+            }
+            for c in &mut conditional[start..] { *c /= acc; }
+            marginal.push(marginal.last().unwrap() + acc);
+        }
+        let sum = *marginal.last().unwrap();
+        for m in marginal.iter_mut() { *m /= sum; }
+
+        let texels = (width * height) as f32;
+        let density = weights.iter().map(|w| w / sum * texels).collect();
+        Some(Self { width, height, marginal, conditional, density })
+    }
+
+    /// Dirección elegida en proporción a la luminancia, con su densidad en ángulo sólido
+    pub fn sample(&self, u: [f32; 2]) -> Option<(glm::Vec3, f32)> {
+        let y = find_interval(&self.marginal, u[1]);
+        let row = &self.conditional[y * (self.width + 1)..(y + 1) * (self.width + 1)];
+        let x = find_interval(row, u[0]);
+
+        // Posición continua dentro del texel elegido
+        let fy = (u[1] - self.marginal[y]) / (self.marginal[y + 1] - self.marginal[y]).max(1e-12);
+        let fx = (u[0] - row[x]) / (row[x + 1] - row[x]).max(1e-12);
+        let uu = (x as f32 + fx.clamp(0.0, 1.0)) / self.width as f32;
+        let vv = (y as f32 + fy.clamp(0.0, 1.0)) / self.height as f32;
+
+        let pdf = self.pdf_uv(x, y, vv);
+        if pdf <= 0.0 { return None; }
+        Some((direction(uu, vv), pdf))
+    }
+
+    /// Densidad (ángulo sólido) con la que `sample` elige `dir`
+    pub fn pdf(&self, dir: glm::Vec3) -> f32 {
+        let (u, v) = lat_long(&glm::normalize(&dir));
+        let x = ((u * self.width as f32) as usize).min(self.width - 1);
+        let y = ((v * self.height as f32) as usize).min(self.height - 1);
+        self.pdf_uv(x, y, v)
+    }
+
+    // Densidad en UV del texel llevada a ángulo sólido: dω = 2π² sin θ du dv
+    fn pdf_uv(&self, x: usize, y: usize, v: f32) -> f32 {
+        let sin_t = (v * PI).sin();
+        if sin_t <= 0.0 { return 0.0; }
+        self.density[y * self.width + x] / (2.0 * PI * PI * sin_t)
+    }
+}
+
+/// Dirección para (u, v) lat-long: v = 0 en +Y, u recorre el azimut desde +X
+fn direction(u: f32, v: f32) -> glm::Vec3 {
+    let theta = v * PI;
+    let phi = u * 2.0 * PI;
+    glm::vec3(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin())
+}
+
+fn lat_long(d: &glm::Vec3) -> (f32, f32) {
+    let u = d.z.atan2(d.x) / (2.0 * PI);
+    (u.rem_euclid(1.0), d.y.clamp(-1.0, 1.0).acos() / PI)
+}
+
+/// Último índice i con cdf[i] <= u (búsqueda binaria)
+fn find_interval(cdf: &[f32], u: f32) -> usize {
+    let i = cdf.partition_point(|&c| c <= u);
+    i.saturating_sub(1).min(cdf.len() - 2)
+}
+
+/// Peso de MIS (heurística de potencia, β = 2) para una estrategia con densidad `a` frente a `b`
+#[inline]
+pub(crate) fn power_heuristic(a: f32, b: f32) -> f32 {
+    let (a2, b2) = (a * a, b * b);
+    if a2 + b2 <= 0.0 { 0.0 } else { a2 / (a2 + b2) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::SmallRng;
+
+    // Un "sol" de radiancia 200 sobre un cielo gris de 1
+    fn sun() -> glm::Vec3 { glm::normalize(&glm::vec3(1.0, 0.4, -0.7)) }
+
+    fn in_sun(d: &glm::Vec3) -> bool { glm::dot(d, &sun()) > 0.95 }
+
+    fn map() -> EnvironmentMap {
+        EnvironmentMap::build(64, 32, |d| if in_sun(&d) { glm::vec3(200.0, 190.0, 150.0) } else { glm::vec3(1.0, 1.0, 1.0) }).unwrap()
+    }
+
+    #[test]
+    fn sampled_pdf_matches_pdf() {
+        let env = map();
+        let mut rng = SmallRng::seed_from_u64(7);
+        let n = 20_000;
+        let mut hits = 0;
+        for _ in 0..n {
+            let (dir, pdf) = env.sample([rng.random(), rng.random()]).unwrap();
+            assert!((glm::length(&dir) - 1.0).abs() < 1e-4);
+            let again = env.pdf(dir);
+            assert!((pdf - again).abs() <= 1e-3 * pdf, "{:?}: sample {} vs pdf {}", dir, pdf, again);
+            if in_sun(&dir) { hits += 1; }
+        }
+        // El casquete del sol es 2.5 % de la esfera pero se lleva la mayoría de las muestras
+        let frac = hits as f32 / n as f32;
+        assert!(frac > 0.7, "{}", frac);
+        assert!(env.pdf(sun()) > 50.0 * env.pdf(-sun()));
+    }
+
+    #[test]
+    fn pdf_integrates_to_one() {
+        let env = map();
+        let mut rng = SmallRng::seed_from_u64(11);
+        let n = 200_000;
+        // Direcciones uniformes en la esfera: E[pdf · 4π] = ∫ pdf dω
+        let mut sum = 0.0f64;
+        for _ in 0..n {
+            let z: f32 = rng.random_range(-1.0..1.0);
+            let phi: f32 = rng.random_range(0.0..2.0 * PI);
+            let r = (1.0 - z * z).sqrt();
+            sum += env.pdf(glm::vec3(r * phi.cos(), z, r * phi.sin())) as f64;
+        }
+        let integral = sum / n as f64 * 4.0 * std::f64::consts::PI;
+        assert!((integral - 1.0).abs() < 0.03, "{}", integral);
+    }
+
+    #[test]
+    fn black_environment_has_no_map() {
+        assert!(EnvironmentMap::build(8, 4, |_| glm::Vec3::zeros()).is_none());
+    }
+}
@@ -0,0 +1,260 @@
+//! Lector de mundos guardados de Minecraft Java (formato Anvil, archivos
+//! `region/r.X.Z.mca`), para recortar una caja de bloques y volcarla en un
+//! `World`.
+//!
+//! Cada región guarda 32×32 chunks comprimidos; cada chunk, secciones de 16³
+//! con una paleta de estados y los índices empaquetados en longs. Se leen los
+//! chunks de 1.13 en adelante (paletas con nombre): desde 1.18 con
+//! `sections`/`block_states`, antes con `Level.Sections`/`BlockStates`.
+
+use std::path::{Path, PathBuf};
+use super::minecraft::{BlockMapping, BlockState, ImportReport};
+use super::nbt::{self, Tag};
+use crate::core::world::World;
+
+const SECTOR: usize = 4096;
+
+/// Un archivo de región ya leído a memoria
+pub struct RegionFile {
+    data: Vec<u8>,
+}
+
+impl RegionFile {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read(path).map_err(|e| format!("No pude leer {}: {}", path.display(), e))?;
+        if !data.is_empty() && data.len() < 2 * SECTOR {
+            return Err(format!("{}: cabecera de región truncada", path.display()));
+        }
+        Ok(Self { data })
+    }
+
+    /// NBT del chunk (`cx`, `cz` locales, 0..32); None si nunca se generó
+    pub fn chunk(&self, cx: usize, cz: usize) -> Result<Option<Tag>, String> {
+        if self.data.is_empty() { return Ok(None); }
+        let entry = &self.data[4 * (cx + cz * 32)..][..4];
+        let offset = u32::from_be_bytes([0, entry[0], entry[1], entry[2]]) as usize * SECTOR;
+        if offset == 0 { return Ok(None); }
+
+        let header = self.data.get(offset..offset + 5).ok_or("chunk fuera del archivo")?;
+        let len = u32::from_be_bytes(header[..4].try_into().unwrap()) as usize;
+        let body = self.data.get(offset + 5..offset + 4 + len).ok_or("chunk truncado")?;
+        let raw = match header[4] {
+            1 | 2 => nbt::decompress(body)?,
+            3 => body.to_vec(),
+            c if c & 0x80 != 0 => return Err("chunks en archivos .mcc externos no soportados".to_string()),
+            c => return Err(format!("compresión de chunk desconocida ({})", c)),
+        };
+        Ok(Some(nbt::parse(&raw)?.1))
+    }
+}
+
+/// Un mundo guardado: la carpeta del mundo o directamente su `region/`
+pub struct AnvilWorld {
+    region_dir: PathBuf,
+}
+
+impl AnvilWorld {
+    pub fn open(path: &str) -> Result<Self, String> {
+        let path = Path::new(path);
+        let region_dir = if path.join("region").is_dir() { path.join("region") } else { path.to_path_buf() };
+        if !region_dir.is_dir() {
+            return Err(format!("{} no es un mundo de Minecraft (falta region/)", path.display()));
+        }
+        Ok(Self { region_dir })
+    }
+
+    /// Copia los bloques de la caja `from`..=`to` (coordenadas del mundo guardado)
+    /// a `world`, con la esquina mínima en `origin`
+    pub fn import(&self, from: (i32, i32, i32), to: (i32, i32, i32), origin: (i32, i32, i32), mapping: &BlockMapping, world: &mut World) -> Result<ImportReport, String> {
+        let min = (from.0.min(to.0), from.1.min(to.1), from.2.min(to.2));
+        let max = (from.0.max(to.0), from.1.max(to.1), from.2.max(to.2));
+        let mut report = ImportReport::default();
+
+        for rz in (min.2 >> 9)..=(max.2 >> 9) {
+            for rx in (min.0 >> 9)..=(max.0 >> 9) {
+                let path = self.region_dir.join(format!("r.{}.{}.mca", rx, rz));
+                // Regiones que no existen son zonas sin generar
+                if !path.exists() { continue; }
+                let region = RegionFile::open(&path)?;
+                let (cx0, cx1) = ((min.0 >> 4).max(rx * 32), (max.0 >> 4).min(rx * 32 + 31));
+                let (cz0, cz1) = ((min.2 >> 4).max(rz * 32), (max.2 >> 4).min(rz * 32 + 31));
+                for cz in cz0..=cz1 {
+                    for cx in cx0..=cx1 {
+                        let ctx = |e: String| format!("{}: chunk ({}, {}): {}", path.display(), cx, cz, e);
+                        let Some(chunk) = region.chunk((cx - rx * 32) as usize, (cz - rz * 32) as usize).map_err(ctx)? else { continue };
+                        import_chunk(&chunk, (cx, cz), min, max, origin, mapping, world, &mut report).map_err(ctx)?;
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn import_chunk(chunk: &Tag, (cx, cz): (i32, i32), min: (i32, i32, i32), max: (i32, i32, i32), origin: (i32, i32, i32),
+                mapping: &BlockMapping, world: &mut World, report: &mut ImportReport) -> Result<(), String> {
+    let version = chunk.get("DataVersion").and_then(Tag::as_i32).unwrap_or(0);
+    // 1.18+ deja las secciones en la raíz; antes van dentro de `Level`
+    let level = chunk.get("Level").unwrap_or(chunk);
+    let sections = match level.get("sections").or_else(|| level.get("Sections")) {
+        Some(s) => s.as_list().ok_or("'sections' debe ser una lista")?,
+        None => return Ok(()),
+    };
+
+    for section in sections {
+        let Some(sy) = section.get("Y").and_then(Tag::as_i32) else { continue };
+        // `Y` es un byte en los archivos de Minecraft
+        if !(-128..128).contains(&sy) {
+            return Err(format!("sección con Y = {} fuera de rango", sy));
+        }
+        if sy * 16 + 15 < min.1 || sy * 16 > max.1 { continue; }
+        let (palette, data) = match section.get("block_states") {
+            Some(bs) => (bs.get("palette"), bs.get("data")),
+            None => (section.get("Palette"), section.get("BlockStates")),
+        };
+        let Some(palette) = palette else {
+            if section.get("Blocks").is_some() {
+                return Err("chunk anterior a 1.13 (IDs numéricos) no soportado".to_string());
+            }
+            continue; // sección vacía (sólo luz)
+        };
+        let palette = palette.as_list().ok_or("'palette' debe ser una lista")?
+            .iter().map(BlockState::from_nbt).collect::<Result<Vec<_>, _>>()?;
+        if palette.is_empty() { continue; }
+        let kinds: Vec<_> = palette.iter().map(|s| mapping.lookup(s)).collect();
+        let indices = unpack(data.and_then(Tag::as_long_array), palette.len(), version)?;
+
+        for (i, &index) in indices.iter().enumerate() {
+            let (x, z, y) = (cx * 16 + (i & 15) as i32, cz * 16 + (i >> 4 & 15) as i32, sy * 16 + (i >> 8) as i32);
+            if x < min.0 || x > max.0 || y < min.1 || y > max.1 || z < min.2 || z > max.2 { continue; }
+            let index = index as usize;
+            let state = palette.get(index).ok_or_else(|| format!("estado {} fuera de la paleta", index))?;
+            let pos = (origin.0 + x - min.0, origin.1 + y - min.1, origin.2 + z - min.2);
+            report.place(world, pos, state, kinds[index], mapping.fallback);
+        }
+    }
+    Ok(())
+}
+
+// Desde 20w17a (1.16) los índices ya no cruzan de un long al siguiente
+const NO_SPANNING_VERSION: i32 = 2527;
+
+/// Índices de paleta de los 4096 bloques de una sección, en orden Y, Z, X
+fn unpack(data: Option<&[i64]>, palette_len: usize, version: i32) -> Result<Vec<u16>, String> {
+    // Paleta de un solo estado: el arreglo se omite
+    let Some(data) = data.filter(|_| palette_len > 1) else { return Ok(vec![0; 4096]) };
+    let bits = (usize::BITS - (palette_len - 1).leading_zeros()).max(4) as usize;
+    if bits > 12 { return Err(format!("paleta de {} estados en una sección de 4096 bloques", palette_len)); }
+    let mask = (1u64 << bits) - 1;
+    let spanning = version < NO_SPANNING_VERSION;
+    let needed = if spanning { (4096 * bits).div_ceil(64) } else { 4096usize.div_ceil(64 / bits) };
+    if data.len() < needed {
+        return Err(format!("'data' con {} longs, se esperaban {}", data.len(), needed));
+    }
+
+    let mut out = Vec::with_capacity(4096);
+    for i in 0..4096 {
+        let v = if spanning {
+            let bit = i * bits;
+            let (word, shift) = (bit / 64, bit % 64);
+            let mut v = (data[word] as u64) >> shift;
+            if shift + bits > 64 { v |= (data[word + 1] as u64) << (64 - shift); }
+            v & mask
+        } else {
+            let per_long = 64 / bits;
+            ((data[i / per_long] as u64) >> (i % per_long * bits)) & mask
+        };
+        out.push(v as u16);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Empaqueta como Minecraft: antes de `NO_SPANNING_VERSION` los índices
+    /// cruzan de un long al siguiente, después cada long lleva 64 / bits enteros
+    fn pack(indices: &[u16], bits: usize, spanning: bool) -> Vec<i64> {
+        let mut out = Vec::new();
+        if spanning {
+            out.resize((indices.len() * bits).div_ceil(64), 0u64);
+            for (i, &v) in indices.iter().enumerate() {
+                let bit = i * bits;
+                out[bit / 64] |= (v as u64) << (bit % 64);
+                if bit % 64 + bits > 64 { out[bit / 64 + 1] |= (v as u64) >> (64 - bit % 64); }
+            }
+        } else {
+            let per_long = 64 / bits;
+            out.resize(indices.len().div_ceil(per_long), 0u64);
+            for (i, &v) in indices.iter().enumerate() {
+                out[i / per_long] |= (v as u64) << (i % per_long * bits);
+            }
+        }
+        out.into_iter().map(|v| v as i64).collect()
+    }
+
+    #[test]
+    fn known_words() {
+        let ramp: Vec<u16> = (0..4096).map(|i| (i % 16) as u16).collect();
+        let data = pack(&ramp, 4, false);
+        assert_eq!(data[0] as u64, 0xfedc_ba98_7654_3210);
+        assert_eq!(unpack(Some(&data), 16, NO_SPANNING_VERSION).unwrap(), ramp);
+
+        // 5 bits sin cruzar: 12 índices por long y 4 bits de relleno arriba
+        let ones = vec![31u16; 4096];
+        let data = pack(&ones, 5, false);
+        assert_eq!(data.len(), 342);
+        assert_eq!(data[0] as u64, (1 << 60) - 1);
+        // El mismo contenido cruzando longs ocupa 4096 * 5 / 64 = 320
+        assert_eq!(pack(&ones, 5, true).len(), 320);
+        assert!(pack(&ones, 5, true).iter().all(|&w| w == -1));
+    }
+
+    #[test]
+    fn round_trip_both_layouts() {
+        for (bits, palette_len) in [(4, 16), (5, 17), (5, 32), (12, 4096)] {
+            let mut seed = 12345u32;
+            let indices: Vec<u16> = (0..4096).map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                ((seed >> 8) as usize % palette_len) as u16
+            }).collect();
+            for (spanning, version) in [(true, NO_SPANNING_VERSION - 1), (false, NO_SPANNING_VERSION)] {
+                let data = pack(&indices, bits, spanning);
+                assert_eq!(unpack(Some(&data), palette_len, version).unwrap(), indices, "{} bits, cruzando: {}", bits, spanning);
+                assert!(unpack(Some(&data[1..]), palette_len, version).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn degenerate_sections() {
+        // Un solo estado: sin arreglo
+        assert_eq!(unpack(None, 1, NO_SPANNING_VERSION).unwrap(), vec![0; 4096]);
+        assert!(unpack(Some(&[0; 1024]), 4097, NO_SPANNING_VERSION).is_err());
+
+        let section = |y: Tag| Tag::Compound(vec![
+            ("Y".to_string(), y),
+            ("block_states".to_string(), Tag::Compound(vec![("palette".to_string(), Tag::List(vec![
+                Tag::Compound(vec![("Name".to_string(), Tag::String("minecraft:stone".to_string()))]),
+            ]))])),
+        ]);
+        let chunk = |y: Tag| Tag::Compound(vec![
+            ("DataVersion".to_string(), Tag::Int(3465)),
+            ("sections".to_string(), Tag::List(vec![section(y)])),
+        ]);
+        let import = |chunk: &Tag, world: &mut World| {
+            let mut report = ImportReport::default();
+            import_chunk(chunk, (0, 0), (0, -64, 0), (1, 0, 1), (0, 0, 0), &BlockMapping::default(), world, &mut report).map(|_| report)
+        };
+
+        let mut world = World::new();
+        let report = import(&chunk(Tag::Byte(-1)), &mut world).unwrap();
+        assert_eq!(report.blocks, 2 * 16 * 2);
+        assert_eq!(world.get(1, 63, 1), Some(crate::core::block::BlockKind::Stone));
+        for y in [i32::MAX, i32::MIN, 128] {
+            assert!(import(&chunk(Tag::Int(y)), &mut World::new()).is_err());
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Materiales y texturas de una `Mesh` en la forma que necesitan los
+//! exportadores (OBJ/MTL y glTF): un material por grupo y las texturas
+//! deduplicadas por contenido, ya codificadas como PNG.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use crate::core::material::{AlphaMode, Material};
+use crate::core::material_registry::MaterialRegistry;
+use crate::core::mesh::Mesh;
+use crate::core::texture::Texture;
+
+pub(crate) struct ExportTexture {
+    pub name: String, // sin extensión, único dentro del export
+    pub png: Vec<u8>,
+}
+
+pub(crate) struct ExportMaterial {
+    pub name: String,
+    pub color: [f32; 3], // RGB lineal (multiplica a la textura)
+    pub texture: Option<usize>,
+    pub opacity: f32,
+    pub alpha: AlphaMode,
+    pub emission: [f32; 3], // RGB lineal ya multiplicado por la intensidad
+    pub emission_texture: Option<usize>,
+    pub specular: f32,
+    pub shininess: f32,
+    pub reflectivity: f32,
+    pub ior: f32,
+}
+
+/// Un material por grupo de `mesh`, en el mismo orden
+pub(crate) fn materials(mesh: &Mesh, reg: &MaterialRegistry) -> Result<(Vec<ExportMaterial>, Vec<ExportTexture>), String> {
+    let mut textures = Vec::new();
+    let mut seen: HashMap<u64, usize> = HashMap::new();
+    let mut add = |tex: &Texture, name: String| -> Result<usize, String> {
+        let mut h = std::collections::hash_map::DefaultHasher::new();
+        (tex.width, tex.height, &tex.data).hash(&mut h);
+        let key = h.finish();
+        if let Some(&i) = seen.get(&key) { return Ok(i); }
+        textures.push(ExportTexture { name, png: tex.to_png()? });
+        seen.insert(key, textures.len() - 1);
+        Ok(textures.len() - 1)
+    };
+
+    let mut out = Vec::with_capacity(mesh.groups.len());
+    for g in &mesh.groups {
+        let name = g.material_name();
+        let m: &Material = reg.get(g.kind).ok_or_else(|| format!("el bloque {} no tiene material", g.kind.name()))?;
+        let texture = m.albedo_tex.for_face(g.face).map(|t| add(t, name.clone())).transpose()?;
+        let emission_texture = match m.is_emissive() {
+            true => m.emission_tex.for_face(g.face).map(|t| add(t, format!("{}_emission", name))).transpose()?,
+            false => None,
+        };
+        let lin = |c: crate::core::color::Color| { let v = c.to_linear(); [v.x, v.y, v.z] };
+        let emission = if m.is_emissive() { lin(m.emission).map(|c| c * m.emission_strength) } else { [0.0; 3] };
+        out.push(ExportMaterial {
+            name,
+            color: lin(m.albedo),
+            texture,
+            opacity: 1.0 - m.transparency.clamp(0.0, 1.0),
+            alpha: m.alpha_mode,
+            emission,
+            emission_texture,
+            specular: m.specular,
+            shininess: m.shininess,
+            reflectivity: m.reflectivity,
+            ior: m.ior,
+        });
+    }
+    Ok((out, textures))
+}
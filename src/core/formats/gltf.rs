@@ -0,0 +1,250 @@
+//! Exportador glTF 2.0.
+//!
+//! `.glb` guarda todo en un solo binario (geometría y PNGs); cualquier otra
+//! extensión escribe `modelo.gltf` con `modelo.bin` y las PNG al lado. Cada
+//! grupo de la malla es una primitiva con su material PBR: la reflectividad
+//! pasa a `metallicFactor`, el brillo Phong a rugosidad, la emisión por encima
+//! de 1 usa `KHR_materials_emissive_strength` y los transparentes `KHR_materials_ior`.
+//! Las texturas se muestrean sin filtrar, como en el render.
+
+use std::path::Path;
+use super::export::{self, ExportMaterial};
+use super::json::Value;
+use crate::core::material::AlphaMode;
+use crate::core::material_registry::MaterialRegistry;
+use crate::core::mesh::Mesh;
+
+// Constantes de glTF
+const FLOAT: usize = 5126;
+const UNSIGNED_INT: usize = 5125;
+const ARRAY_BUFFER: usize = 34962;
+const ELEMENT_ARRAY_BUFFER: usize = 34963;
+const NEAREST: usize = 9728;
+const NEAREST_MIPMAP_LINEAR: usize = 9986;
+
+pub fn write_gltf(path: &str, mesh: &Mesh, reg: &MaterialRegistry) -> Result<(), String> {
+    let path = Path::new(path);
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| "world".to_string());
+    let binary = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("glb"));
+    // glTF no admite una malla sin primitivas
+    if mesh.triangles() == 0 {
+        return Err("la malla está vacía: no hay nada que exportar".to_string());
+    }
+    let (materials, textures) = export::materials(mesh, reg)?;
+
+    let mut b = Builder { bin: Vec::new(), views: Vec::new(), accessors: Vec::new() };
+    let mut primitives = Vec::new();
+    for (i, g) in mesh.groups.iter().enumerate() {
+        let (lo, hi) = g.positions.iter().fold(([f32::MAX; 3], [f32::MIN; 3]), |(lo, hi), p| {
+            ([0, 1, 2].map(|k| lo[k].min(p[k])), [0, 1, 2].map(|k| hi[k].max(p[k])))
+        });
+        let count = g.positions.len();
+        let pos = b.view(g.positions.iter().flatten().flat_map(|v| v.to_le_bytes()), Some(ARRAY_BUFFER));
+        let pos = b.accessor(pos, FLOAT, count, "VEC3", Some((&lo, &hi)));
+        let nrm = b.view(g.normals.iter().flatten().flat_map(|v| v.to_le_bytes()), Some(ARRAY_BUFFER));
+        let nrm = b.accessor(nrm, FLOAT, count, "VEC3", None);
+        // glTF pone v = 0 arriba de la imagen
+        let uv = b.view(g.uvs.iter().flat_map(|t| [t[0], 1.0 - t[1]]).flat_map(|v| v.to_le_bytes()), Some(ARRAY_BUFFER));
+        let uv = b.accessor(uv, FLOAT, count, "VEC2", None);
+        let idx = b.view(g.indices.iter().flat_map(|v| v.to_le_bytes()), Some(ELEMENT_ARRAY_BUFFER));
+        let idx = b.accessor(idx, UNSIGNED_INT, g.indices.len(), "SCALAR", None);
+        primitives.push(object(vec![
+            ("attributes", object(vec![("POSITION", num(pos)), ("NORMAL", num(nrm)), ("TEXCOORD_0", num(uv))])),
+            ("indices", num(idx)),
+            ("material", num(i)),
+        ]));
+    }
+
+    let bin_name = format!("{}.bin", stem);
+    let images: Vec<Value> = textures.iter().map(|t| {
+        if binary {
+            let view = b.view(t.png.iter().copied(), None);
+            object(vec![("bufferView", num(view)), ("mimeType", Value::String("image/png".into()))])
+        } else {
+            object(vec![("uri", Value::String(format!("{}_{}.png", stem, t.name)))])
+        }
+    }).collect();
+
+    let mut extensions: Vec<&str> = Vec::new();
+    let gltf_materials: Vec<Value> = materials.iter().map(|m| material(m, &mut extensions)).collect();
+
+    let mut buffer = vec![("byteLength", num(b.bin.len()))];
+    if !binary { buffer.push(("uri", Value::String(bin_name.clone()))); }
+    let mut root = vec![
+        ("asset", object(vec![("version", Value::String("2.0".into())), ("generator", Value::String("minecraft_diorama".into()))])),
+        ("scene", num(0)),
+        ("scenes", Value::Array(vec![object(vec![("nodes", Value::Array(vec![num(0)]))])])),
+        ("nodes", Value::Array(vec![object(vec![("mesh", num(0)), ("name", Value::String(stem.clone()))])])),
+        ("meshes", Value::Array(vec![object(vec![("primitives", Value::Array(primitives))])])),
+        ("materials", Value::Array(gltf_materials)),
+        ("buffers", Value::Array(vec![object(buffer)])),
+        ("bufferViews", Value::Array(b.views)),
+        ("accessors", Value::Array(b.accessors)),
+    ];
+    if !textures.is_empty() {
+        root.push(("samplers", Value::Array(vec![object(vec![("magFilter", num(NEAREST)), ("minFilter", num(NEAREST_MIPMAP_LINEAR))])])));
+        root.push(("textures", Value::Array((0..textures.len()).map(|i| object(vec![("sampler", num(0)), ("source", num(i))])).collect())));
+        root.push(("images", Value::Array(images)));
+    }
+    if !extensions.is_empty() {
+        root.push(("extensionsUsed", Value::Array(extensions.iter().map(|e| Value::String(e.to_string())).collect())));
+    }
+    let json = object(root).to_string();
+
+    let write = |name: &Path, bytes: &[u8]| std::fs::write(name, bytes).map_err(|e| format!("No pude escribir {}: {}", name.display(), e));
+    if binary {
+        write(path, &glb(json.into_bytes(), b.bin))
+    } else {
+        write(path, json.as_bytes())?;
+        write(&dir.join(&bin_name), &b.bin)?;
+        for t in &textures {
+            write(&dir.join(format!("{}_{}.png", stem, t.name)), &t.png)?;
+        }
+        Ok(())
+    }
+}
+
+fn material(m: &ExportMaterial, extensions: &mut Vec<&'static str>) -> Value {
+    let mut pbr = vec![
+        ("baseColorFactor", floats(&[m.color[0], m.color[1], m.color[2], m.opacity])),
+        ("metallicFactor", float(m.reflectivity.clamp(0.0, 1.0))),
+        // Rugosidad equivalente al exponente de Phong (Beckmann: α² = 2 / (n + 2))
+        ("roughnessFactor", float((2.0 / (m.shininess.max(0.0) + 2.0)).sqrt().sqrt())),
+    ];
+    if let Some(t) = m.texture { pbr.push(("baseColorTexture", object(vec![("index", num(t))]))); }
+
+    let mut out = vec![("name", Value::String(m.name.clone())), ("pbrMetallicRoughness", object(pbr))];
+    let peak = m.emission.iter().fold(0.0f32, |a, &c| a.max(c));
+    if peak > 0.0 {
+        let scale = peak.max(1.0);
+        out.push(("emissiveFactor", floats(&m.emission.map(|c| c / scale))));
+        if let Some(t) = m.emission_texture { out.push(("emissiveTexture", object(vec![("index", num(t))]))); }
+        if scale > 1.0 {
+            use_extension(extensions, "KHR_materials_emissive_strength");
+            out.push(("extensions", object(vec![("KHR_materials_emissive_strength", object(vec![("emissiveStrength", float(scale))]))])));
+        }
+    }
+    match m.alpha {
+        AlphaMode::Cutout { threshold } => {
+            out.push(("alphaMode", Value::String("MASK".into())));
+            out.push(("alphaCutoff", float(threshold)));
+            // Las hojas se ven por dentro a través de los huecos
+            out.push(("doubleSided", Value::Bool(true)));
+        }
+        AlphaMode::Blend => out.push(("alphaMode", Value::String("BLEND".into()))),
+        AlphaMode::Opaque if m.opacity < 1.0 => out.push(("alphaMode", Value::String("BLEND".into()))),
+        AlphaMode::Opaque => {}
+    }
+    if m.opacity < 1.0 && m.ior > 1.0 {
+        use_extension(extensions, "KHR_materials_ior");
+        let ior = ("KHR_materials_ior", object(vec![("ior", float(m.ior))]));
+        match out.iter_mut().find(|(k, _)| *k == "extensions") {
+            Some((_, Value::Object(fields))) => fields.push((ior.0.to_string(), ior.1)),
+            _ => out.push(("extensions", object(vec![ior]))),
+        }
+    }
+    object(out)
+}
+
+fn use_extension(list: &mut Vec<&'static str>, name: &'static str) {
+    if !list.contains(&name) { list.push(name); }
+}
+
+/// Contenedor GLB: cabecera, chunk JSON (relleno con espacios) y chunk BIN
+fn glb(mut json: Vec<u8>, mut bin: Vec<u8>) -> Vec<u8> {
+    while !json.len().is_multiple_of(4) { json.push(b' '); }
+    while !bin.len().is_multiple_of(4) { bin.push(0); }
+    let total = 12 + 8 + json.len() + 8 + bin.len();
+    let mut out = Vec::with_capacity(total);
+    out.extend_from_slice(b"glTF");
+    out.extend_from_slice(&2u32.to_le_bytes());
+    out.extend_from_slice(&(total as u32).to_le_bytes());
+    out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"JSON");
+    out.extend_from_slice(&json);
+    out.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"BIN\0");
+    out.extend_from_slice(&bin);
+    out
+}
+
+struct Builder {
+    bin: Vec<u8>,
+    views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl Builder {
+    /// Agrega los bytes al buffer (alineados a 4) y devuelve el índice del bufferView
+    fn view(&mut self, bytes: impl Iterator<Item = u8>, target: Option<usize>) -> usize {
+        while !self.bin.len().is_multiple_of(4) { self.bin.push(0); }
+        let offset = self.bin.len();
+        self.bin.extend(bytes);
+        let mut v = vec![("buffer", num(0)), ("byteOffset", num(offset)), ("byteLength", num(self.bin.len() - offset))];
+        if let Some(t) = target { v.push(("target", num(t))); }
+        self.views.push(object(v));
+        self.views.len() - 1
+    }
+
+    fn accessor(&mut self, view: usize, component: usize, count: usize, kind: &str, bounds: Option<(&[f32; 3], &[f32; 3])>) -> usize {
+        let mut a = vec![
+            ("bufferView", num(view)),
+            ("componentType", num(component)),
+            ("count", num(count)),
+            ("type", Value::String(kind.to_string())),
+        ];
+        if let Some((lo, hi)) = bounds {
+            a.push(("min", floats(lo)));
+            a.push(("max", floats(hi)));
+        }
+        self.accessors.push(object(a));
+        self.accessors.len() - 1
+    }
+}
+
+fn object(fields: Vec<(&str, Value)>) -> Value {
+    Value::Object(fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+}
+
+fn num(n: usize) -> Value {
+    Value::Number(n as f64)
+}
+
+/// f32 con su representación corta (0.6 y no 0.6000000238418579)
+fn float(x: f32) -> Value {
+    Value::Number(x.to_string().parse().unwrap_or(0.0))
+}
+
+fn floats(v: &[f32]) -> Value {
+    Value::Array(v.iter().map(|&x| float(x)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::block::BlockKind;
+    use crate::core::color::Color;
+    use crate::core::material::Material;
+    use crate::core::world::World;
+
+    #[test]
+    fn empty_mesh_is_an_error() {
+        let dir = std::env::temp_dir().join(format!("diorama_gltf_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("vacio.glb");
+        let path = path.to_str().unwrap();
+        let mut reg = MaterialRegistry::new();
+        assert!(write_gltf(path, &Mesh { groups: Vec::new() }, &reg).is_err());
+        assert!(!Path::new(path).exists());
+
+        reg.set(BlockKind::Stone, Material::with_color(Color::new(120, 120, 120), 0.0, 1.0, 0.0, 0.0, 1.0));
+        let mut world = World::new();
+        world.set(0, 0, 0, BlockKind::Stone);
+        write_gltf(path, &world.mesh(&reg), &reg).unwrap();
+        let bytes = std::fs::read(path).unwrap();
+        assert_eq!(&bytes[..4], b"glTF");
+        assert_eq!(u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize, bytes.len());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,358 @@
+//! Lector JSON mínimo (sin dependencias) para los archivos de escena; `Display`
+//! escribe un `Value` como JSON compacto (para los exportadores).
+
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>), // conserva el orden del archivo
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub col: usize,
+    pub msg: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JSON inválido en {}:{}: {}", self.line, self.col, self.msg)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        if let Value::Number(n) = self { Some(*n) } else { None }
+    }
+    pub fn as_f32(&self) -> Option<f32> { self.as_f64().map(|n| n as f32) }
+    pub fn as_i32(&self) -> Option<i32> {
+        self.as_f64().filter(|&n| n.fract() == 0.0 && (i32::MIN as f64..=i32::MAX as f64).contains(&n)).map(|n| n as i32)
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        if let Value::Bool(b) = self { Some(*b) } else { None }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        if let Value::String(s) = self { Some(s) } else { None }
+    }
+    pub fn as_array(&self) -> Option<&[Value]> {
+        if let Value::Array(a) = self { Some(a) } else { None }
+    }
+    pub fn as_object(&self) -> Option<&[(String, Value)]> {
+        if let Value::Object(o) = self { Some(o) } else { None }
+    }
+    pub fn is_null(&self) -> bool { matches!(self, Value::Null) }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            // JSON no tiene NaN ni infinitos
+            Value::Number(n) if !n.is_finite() => f.write_str("null"),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write_string(f, s),
+            Value::Array(items) => {
+                f.write_str("[")?;
+                for (i, v) in items.iter().enumerate() {
+                    if i > 0 { f.write_str(",")?; }
+                    write!(f, "{}", v)?;
+                }
+                f.write_str("]")
+            }
+            Value::Object(fields) => {
+                f.write_str("{")?;
+                for (i, (k, v)) in fields.iter().enumerate() {
+                    if i > 0 { f.write_str(",")?; }
+                    write_string(f, k)?;
+                    write!(f, ":{}", v)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+pub fn parse(src: &str) -> Result<Value, ParseError> {
+    let mut p = Parser { src: src.as_bytes(), pos: 0 };
+    p.skip_ws();
+    let v = p.value()?;
+    p.skip_ws();
+    if p.pos < p.src.len() {
+        return Err(p.error("contenido extra después del valor"));
+    }
+    Ok(v)
+}
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, msg: &str) -> ParseError {
+        let before = &self.src[..self.pos.min(self.src.len())];
+        let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+        let col = before.iter().rev().take_while(|&&b| b != b'\n').count() + 1;
+        ParseError { line, col, msg: msg.to_string() }
+    }
+
+    fn peek(&self) -> Option<u8> { self.src.get(self.pos).copied() }
+
+    fn skip_ws(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, b: u8) -> Result<(), ParseError> {
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("se esperaba '{}'", b as char)))
+        }
+    }
+
+    fn literal(&mut self, word: &str, v: Value) -> Result<Value, ParseError> {
+        if self.src[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(v)
+        } else {
+            Err(self.error("valor desconocido"))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        match self.peek() {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            Some(b'"') => Ok(Value::String(self.string()?)),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("carácter inesperado")),
+            None => Err(self.error("fin de archivo inesperado")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, ParseError> {
+        self.expect(b'{')?;
+        let mut fields = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(fields));
+        }
+        loop {
+            self.skip_ws();
+            let key = self.string()?;
+            self.skip_ws();
+            self.expect(b':')?;
+            self.skip_ws();
+            let v = self.value()?;
+            fields.push((key, v));
+            self.skip_ws();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => { self.pos += 1; return Ok(Value::Object(fields)); }
+                _ => return Err(self.error("se esperaba ',' o '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, ParseError> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Value::Array(items));
+        }
+        loop {
+            self.skip_ws();
+            items.push(self.value()?);
+            self.skip_ws();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => { self.pos += 1; return Ok(Value::Array(items)); }
+                _ => return Err(self.error("se esperaba ',' o ']'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.expect(b'"')?;
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            let Some(b) = self.peek() else { return Err(self.error("string sin cerrar")); };
+            self.pos += 1;
+            match b {
+                b'"' => break,
+                b'\\' => {
+                    let Some(e) = self.peek() else { return Err(self.error("escape incompleto")); };
+                    self.pos += 1;
+                    match e {
+                        b'"' => out.push(b'"'),
+                        b'\\' => out.push(b'\\'),
+                        b'/' => out.push(b'/'),
+                        b'b' => out.push(0x08),
+                        b'f' => out.push(0x0c),
+                        b'n' => out.push(b'\n'),
+                        b'r' => out.push(b'\r'),
+                        b't' => out.push(b'\t'),
+                        b'u' => {
+                            let c = self.unicode_escape()?;
+                            let mut buf = [0u8; 4];
+                            out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                        }
+                        _ => return Err(self.error("escape inválido")),
+                    }
+                }
+                _ => out.push(b),
+            }
+        }
+        String::from_utf8(out).map_err(|_| self.error("UTF-8 inválido"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, ParseError> {
+        let digits = self.src.get(self.pos..self.pos + 4).ok_or_else(|| self.error("escape \\u incompleto"))?;
+        let s = std::str::from_utf8(digits).map_err(|_| self.error("escape \\u inválido"))?;
+        let v = u32::from_str_radix(s, 16).map_err(|_| self.error("escape \\u inválido"))?;
+        self.pos += 4;
+        Ok(v)
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, ParseError> {
+        let hi = self.hex4()?;
+        // Par sustituto UTF-16
+        if (0xD800..0xDC00).contains(&hi) && self.src[self.pos..].starts_with(b"\\u") {
+            self.pos += 2;
+            let lo = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&lo) {
+                return Err(self.error("par sustituto inválido"));
+            }
+            let c = 0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00);
+            return char::from_u32(c).ok_or_else(|| self.error("escape \\u inválido"));
+        }
+        char::from_u32(hi).ok_or_else(|| self.error("escape \\u inválido"))
+    }
+
+    fn number(&mut self) -> Result<Value, ParseError> {
+        let start = self.pos;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        let s = std::str::from_utf8(&self.src[start..self.pos]).unwrap_or("");
+        s.parse::<f64>().map(Value::Number).map_err(|_| {
+            self.pos = start;
+            self.error("número inválido")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn err_at(src: &str) -> (usize, usize) {
+        let e = parse(src).expect_err(src);
+        (e.line, e.col)
+    }
+
+    #[test]
+    fn escapes() {
+        let v = parse(r#""a\"b\\c\/d\b\f\n\r\t\u00e9\u0001""#).unwrap();
+        assert_eq!(v.as_str(), Some("a\"b\\c/d\u{8}\u{c}\n\r\té\u{1}"));
+        // Par sustituto: U+1F600
+        assert_eq!(parse(r#""\ud83d\ude00""#).unwrap().as_str(), Some("😀"));
+        assert_eq!(parse("\"ñandú\"").unwrap().as_str(), Some("ñandú"));
+        for bad in [r#""\ud83d""#, r#""\ud83d\u0041""#, r#""\u12""#, r#""\x""#, r#""\uzzzz""#] {
+            assert!(parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn numbers() {
+        for (src, n) in [("0", 0.0), ("-7", -7.0), ("3.25", 3.25), ("-1.5e3", -1500.0), ("2E-2", 0.02), ("1e+2", 100.0)] {
+            assert_eq!(parse(src).unwrap().as_f64(), Some(n), "{}", src);
+        }
+        assert_eq!(parse("-12").unwrap().as_i32(), Some(-12));
+        assert_eq!(parse("1.5").unwrap().as_i32(), None);
+        for bad in ["-", "1e", "1.2.3", "--1"] {
+            assert!(parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn nesting() {
+        let v = parse(r#" { "a": [1, [2, {"b": null}], {}], "c": {"d": [true, false]}, "e": [] } "#).unwrap();
+        let a = v.get("a").and_then(Value::as_array).unwrap();
+        assert_eq!(a.len(), 3);
+        assert_eq!(a[1].as_array().unwrap()[1].get("b"), Some(&Value::Null));
+        assert_eq!(a[2], Value::Object(vec![]));
+        assert_eq!(v.get("c").and_then(|c| c.get("d")).and_then(Value::as_array).unwrap()[1].as_bool(), Some(false));
+        assert_eq!(v.get("e"), Some(&Value::Array(vec![])));
+        // Las claves conservan el orden del archivo
+        let keys: Vec<_> = v.as_object().unwrap().iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, ["a", "c", "e"]);
+    }
+
+    #[test]
+    fn error_positions() {
+        assert_eq!(err_at("{\n  \"a\": tru }"), (2, 8));
+        assert_eq!(err_at("[1, 2"), (1, 6));
+        assert_eq!(err_at("[1 2]"), (1, 4));
+        assert_eq!(err_at("{\"a\" 1}"), (1, 6));
+        assert_eq!(err_at("\n\n  \"abc"), (3, 7));
+        assert_eq!(err_at("{} x"), (1, 4));
+        assert_eq!(err_at("[1, -x]"), (1, 5));
+        assert_eq!(err_at(""), (1, 1));
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let v = Value::Object(vec![
+            ("s".to_string(), Value::String("comillas \" barra \\ salto \n tab \t ctrl \u{1} 😀".to_string())),
+            ("n".to_string(), Value::Array(vec![Value::Number(-1.5e3), Value::Number(0.1), Value::Number(1e-7), Value::Number(42.0)])),
+            ("b".to_string(), Value::Array(vec![Value::Bool(true), Value::Bool(false), Value::Null])),
+            ("o".to_string(), Value::Object(vec![("vacío".to_string(), Value::Array(vec![]))])),
+        ]);
+        let text = v.to_string();
+        assert!(!text.contains('\n'));
+        assert_eq!(parse(&text).unwrap(), v);
+        // NaN e infinitos se escriben como null
+        assert_eq!(Value::Array(vec![Value::Number(f64::NAN), Value::Number(f64::INFINITY)]).to_string(), "[null,null]");
+    }
+}
@@ -0,0 +1,245 @@
+//! Traducción de bloques de Minecraft (`minecraft:oak_log[axis=y]`) a
+//! `BlockKind`, compartida por los importadores de estructuras, schematics y
+//! regiones.
+//!
+//! Las reglas se prueban de la última a la primera, así que las que agrega el
+//! usuario tapan a las de `BlockMapping::default()`. Un patrón es un ID con o
+//! sin espacio de nombres (`stone`, `minecraft:stone`), puede llevar `*` como
+//! comodín (`*_leaves`) y opcionalmente propiedades que deben coincidir
+//! (`oak_log[axis=y]`).
+
+use std::collections::BTreeMap;
+use std::fmt;
+use super::nbt::Tag;
+use crate::core::block::BlockKind;
+use crate::core::world::World;
+
+/// Estado de bloque: ID con espacio de nombres y propiedades
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockState {
+    pub name: String,
+    pub properties: Vec<(String, String)>,
+}
+
+impl BlockState {
+    /// Interpreta `minecraft:oak_log[axis=y,waterlogged=false]`; sin espacio de
+    /// nombres se asume `minecraft:`
+    pub fn parse(s: &str) -> Self {
+        let (name, props) = match s.split_once('[') {
+            Some((n, rest)) => (n, rest.trim_end_matches(']')),
+            None => (s, ""),
+        };
+        let properties = props.split(',')
+            .filter_map(|kv| kv.split_once('='))
+            .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+            .collect();
+        Self { name: qualified(name.trim()), properties }
+    }
+
+    /// Entrada de paleta en NBT: `{ Name, Properties }`
+    pub fn from_nbt(t: &Tag) -> Result<Self, String> {
+        let name = t.get("Name").and_then(Tag::as_str).ok_or("entrada de paleta sin 'Name'")?;
+        let mut state = Self::parse(name);
+        if let Some(props) = t.get("Properties").and_then(Tag::as_compound) {
+            state.properties = props.iter()
+                .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                .collect();
+        }
+        Ok(state)
+    }
+
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+}
+
+/// IDs de Minecraft -> `BlockKind`
+#[derive(Clone, Debug)]
+pub struct BlockMapping {
+    pub rules: Vec<(String, BlockKind)>, // patrón -> tipo; gana la última que coincide
+    pub fallback: Option<BlockKind>,     // tipo para bloques sin regla (None = no se colocan)
+}
+
+impl Default for BlockMapping {
+    /// Reglas para los bloques vanilla más comunes
+    fn default() -> Self {
+        use BlockKind::*;
+        let table: &[(&str, BlockKind)] = &[
+            ("stone", Stone), ("cobblestone", Stone), ("mossy_cobblestone", Stone), ("*stone_bricks", Stone),
+            ("smooth_stone", Stone), ("andesite", Stone), ("diorite", Stone), ("granite", Stone),
+            ("polished_andesite", Stone), ("polished_diorite", Stone), ("polished_granite", Stone),
+            ("deepslate", Stone), ("cobbled_deepslate", Stone), ("tuff", Stone), ("bedrock", Stone),
+            ("gravel", Stone), ("*_ore", Stone), ("*stone_slab", Stone), ("*stone_stairs", Stone),
+            ("*stone_wall", Stone), ("*stone_brick_slab", Stone), ("*stone_brick_stairs", Stone),
+            ("dirt", Dirt), ("coarse_dirt", Dirt), ("rooted_dirt", Dirt), ("podzol", Dirt), ("mud", Dirt),
+            ("dirt_path", Dirt), ("farmland", Dirt),
+            ("grass_block", Grass), ("mycelium", Grass), ("moss_block", Grass),
+            ("lava", Lava), ("magma_block", Lava),
+            ("water", Water), ("bubble_column", Water),
+            ("diamond_block", Diamond), ("diamond_ore", Diamond), ("deepslate_diamond_ore", Diamond),
+            ("iron_block", Iron), ("raw_iron_block", Iron), ("iron_ore", Iron), ("deepslate_iron_ore", Iron),
+            ("*_log", Wood), ("*_wood", Wood), ("*_planks", Wood), ("*_stem", Wood), ("*_hyphae", Wood),
+            ("*_slab", Wood), ("*_stairs", Wood), ("*_fence", Wood), ("bamboo_block", Wood),
+            ("*_leaves", Leaves), ("azalea_leaves", Leaves),
+            ("air", Air), ("cave_air", Air), ("void_air", Air), ("structure_void", Air),
+        ];
+        // Las escaleras y losas de madera van antes que las de piedra: con la
+        // prioridad de la última regla, la piedra gana cuando ambas coinciden
+        let mut rules: Vec<(String, BlockKind)> = table.iter().map(|&(p, k)| (p.to_string(), k)).collect();
+        rules.sort_by_key(|(p, _)| !matches!(p.as_str(), "*_slab" | "*_stairs" | "*_fence"));
+        Self { rules, fallback: None }
+    }
+}
+
+impl BlockMapping {
+    /// Sin reglas ni tipo por defecto
+    pub fn empty() -> Self {
+        Self { rules: Vec::new(), fallback: None }
+    }
+
+    /// Agrega una regla con prioridad sobre las anteriores
+    pub fn map(&mut self, pattern: &str, kind: BlockKind) -> &mut Self {
+        self.rules.push((pattern.to_string(), kind));
+        self
+    }
+
+    /// Tipo para `state` (sin aplicar `fallback`)
+    pub fn lookup(&self, state: &BlockState) -> Option<BlockKind> {
+        self.rules.iter().rev().find(|(p, _)| pattern_matches(p, state)).map(|&(_, k)| k)
+    }
+
+    /// Tipo para `state`, o `fallback`; None si el bloque no se coloca
+    pub fn resolve(&self, state: &BlockState) -> Option<BlockKind> {
+        self.lookup(state).or(self.fallback)
+    }
+}
+
+/// Resultado de volcar bloques de Minecraft en un `World`
+#[derive(Clone, Debug, Default)]
+pub struct ImportReport {
+    pub blocks: usize,                    // bloques colocados
+    pub air: usize,                       // aire (no se coloca)
+    pub unknown: BTreeMap<String, usize>, // IDs sin regla -> cantidad
+}
+
+impl ImportReport {
+    /// Coloca un bloque con el tipo que dio `BlockMapping::lookup` (los
+    /// importadores lo resuelven una vez por entrada de paleta), llevando la cuenta
+    pub(crate) fn place(&mut self, world: &mut World, pos: (i32, i32, i32), state: &BlockState, kind: Option<BlockKind>, fallback: Option<BlockKind>) {
+        if kind.is_none() {
+            match self.unknown.get_mut(&state.name) {
+                Some(n) => *n += 1,
+                None => { self.unknown.insert(state.name.clone(), 1); }
+            }
+        }
+        match kind.or(fallback) {
+            Some(BlockKind::Air) => self.air += 1,
+            Some(k) => {
+                world.set(pos.0, pos.1, pos.2, k);
+                self.blocks += 1;
+            }
+            None => {}
+        }
+    }
+
+    /// Total de bloques sin regla
+    pub fn unknown_count(&self) -> usize {
+        self.unknown.values().sum()
+    }
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bloques", self.blocks)?;
+        if !self.unknown.is_empty() {
+            // Los más frecuentes primero
+            let mut ids: Vec<_> = self.unknown.iter().collect();
+            ids.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            write!(f, "; {} sin mapear:", self.unknown_count())?;
+            for (id, n) in ids {
+                write!(f, " {} ×{}", id, n)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn qualified(name: &str) -> String {
+    if name.contains(':') { name.to_string() } else { format!("minecraft:{}", name) }
+}
+
+fn pattern_matches(pattern: &str, state: &BlockState) -> bool {
+    let want = BlockState::parse(pattern);
+    // El comodín no cruza el espacio de nombres: `*_log` es `minecraft:*_log`
+    glob(&want.name, &state.name)
+        && want.properties.iter().all(|(k, v)| state.property(k) == Some(v.as_str()))
+}
+
+/// `*` coincide con cualquier secuencia (incluida la vacía)
+fn glob(pattern: &str, s: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == s,
+        Some((head, rest)) => {
+            let Some(tail) = s.strip_prefix(head) else { return false };
+            (0..=tail.len()).filter(|&i| tail.is_char_boundary(i)).any(|i| glob(rest, &tail[i..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(mapping: &BlockMapping, s: &str) -> Option<BlockKind> {
+        mapping.lookup(&BlockState::parse(s))
+    }
+
+    #[test]
+    fn block_state_parse() {
+        let s = BlockState::parse("oak_log[axis=y, waterlogged=false]");
+        assert_eq!(s.name, "minecraft:oak_log");
+        assert_eq!(s.property("axis"), Some("y"));
+        assert_eq!(s.property("waterlogged"), Some("false"));
+        assert_eq!(BlockState::parse("mod:thing").name, "mod:thing");
+    }
+
+    #[test]
+    fn mapping_rules() {
+        let mut m = BlockMapping::default();
+        assert_eq!(kind(&m, "minecraft:stone"), Some(BlockKind::Stone));
+        assert_eq!(kind(&m, "birch_log[axis=x]"), Some(BlockKind::Wood));
+        assert_eq!(kind(&m, "oak_stairs"), Some(BlockKind::Wood));
+        assert_eq!(kind(&m, "cobblestone_stairs"), Some(BlockKind::Stone));
+        assert_eq!(kind(&m, "mod:oak_log"), None); // el comodín no cruza el espacio de nombres
+        assert_eq!(kind(&m, "beacon"), None);
+
+        // Las reglas nuevas tapan a las anteriores, con o sin propiedades
+        m.map("oak_log[axis=y]", BlockKind::Dirt).map("beacon", BlockKind::Diamond);
+        assert_eq!(kind(&m, "oak_log[axis=y]"), Some(BlockKind::Dirt));
+        assert_eq!(kind(&m, "oak_log[axis=z]"), Some(BlockKind::Wood));
+        assert_eq!(kind(&m, "beacon"), Some(BlockKind::Diamond));
+    }
+
+    #[test]
+    fn unknown_blocks_reach_report() {
+        let mapping = BlockMapping::default();
+        let mut world = World::new();
+        let mut report = ImportReport::default();
+        for (i, id) in ["stone", "beacon", "air", "beacon", "mod:gizmo"].into_iter().enumerate() {
+            let state = BlockState::parse(id);
+            report.place(&mut world, (i as i32, 0, 0), &state, mapping.lookup(&state), None);
+        }
+        assert_eq!((report.blocks, report.air, report.unknown_count()), (1, 1, 3));
+        assert_eq!(report.unknown.get("minecraft:beacon"), Some(&2));
+        assert_eq!(report.unknown.get("mod:gizmo"), Some(&1));
+        assert_eq!(world.get(1, 0, 0), None);
+        assert_eq!(report.to_string(), "1 bloques; 3 sin mapear: minecraft:beacon ×2 mod:gizmo ×1");
+
+        // Con `fallback` se colocan, pero siguen contando como sin regla
+        let mut report = ImportReport::default();
+        let state = BlockState::parse("beacon");
+        report.place(&mut world, (1, 0, 0), &state, None, Some(BlockKind::Iron));
+        assert_eq!(world.get(1, 0, 0), Some(BlockKind::Iron));
+        assert_eq!((report.blocks, report.unknown_count()), (1, 1));
+    }
+}
@@ -0,0 +1,11 @@
+pub mod anvil;
+mod export;
+pub mod gltf;
+pub mod json;
+pub mod minecraft;
+pub mod nbt;
+pub mod obj;
+pub mod scene_file;
+pub mod schematic;
+pub mod vox;
+pub mod world_file;
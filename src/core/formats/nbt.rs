@@ -0,0 +1,271 @@
+//! Lector de NBT (Named Binary Tag), el formato binario de Minecraft Java:
+//! estructuras, schematics y regiones de mundos guardados. Big-endian, con
+//! compresión gzip o zlib opcional.
+
+use std::io::Read;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(Vec<(String, Tag)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    /// Campo de un compound (None si no es compound o no existe)
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&[(String, Tag)]> {
+        match self { Tag::Compound(f) => Some(f), _ => None }
+    }
+
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self { Tag::List(l) => Some(l), _ => None }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self { Tag::String(s) => Some(s), _ => None }
+    }
+
+    /// Cualquier entero como i64
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Tag::Byte(v) => Some(v as i64),
+            Tag::Short(v) => Some(v as i64),
+            Tag::Int(v) => Some(v as i64),
+            Tag::Long(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        self.as_i64().and_then(|v| i32::try_from(v).ok())
+    }
+
+    pub fn as_byte_array(&self) -> Option<&[i8]> {
+        match self { Tag::ByteArray(a) => Some(a), _ => None }
+    }
+
+    pub fn as_long_array(&self) -> Option<&[i64]> {
+        match self { Tag::LongArray(a) => Some(a), _ => None }
+    }
+
+    /// Lista de 3 enteros (posiciones y tamaños) o IntArray de 3
+    pub fn as_ivec3(&self) -> Option<[i32; 3]> {
+        match self {
+            Tag::List(l) if l.len() == 3 => Some([l[0].as_i32()?, l[1].as_i32()?, l[2].as_i32()?]),
+            Tag::IntArray(a) if a.len() == 3 => Some([a[0], a[1], a[2]]),
+            _ => None,
+        }
+    }
+}
+
+/// Lee un archivo NBT (comprimido o no); devuelve el nombre y el tag raíz
+pub fn load(path: &str) -> Result<(String, Tag), String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("No pude leer {}: {}", path, e))?;
+    parse(&decompress(&bytes)?).map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Descomprime gzip o zlib según la cabecera; el resto se devuelve tal cual
+pub fn decompress(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let res = match bytes {
+        [0x1f, 0x8b, ..] => flate2::read::GzDecoder::new(bytes).read_to_end(&mut out),
+        [0x78, ..] => flate2::read::ZlibDecoder::new(bytes).read_to_end(&mut out),
+        _ => return Ok(bytes.to_vec()),
+    };
+    res.map_err(|e| format!("no pude descomprimir: {}", e))?;
+    Ok(out)
+}
+
+/// NBT sin comprimir: un tag con nombre en la raíz (normalmente un compound)
+pub fn parse(bytes: &[u8]) -> Result<(String, Tag), String> {
+    let mut r = Reader { data: bytes, pos: 0 };
+    let id = r.u8()?;
+    if id == 0 { return Err("NBT vacío".to_string()); }
+    let name = r.string()?;
+    let tag = r.payload(id, 0)?;
+    Ok((name, tag))
+}
+
+// Compounds y listas anidados más allá de esto son un archivo corrupto
+const MAX_DEPTH: u32 = 512;
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let end = self.pos + N;
+        let bytes = self.data.get(self.pos..end).ok_or("NBT truncado")?;
+        self.pos = end;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> { Ok(self.take::<1>()?[0]) }
+    fn i16(&mut self) -> Result<i16, String> { Ok(i16::from_be_bytes(self.take()?)) }
+    fn i32(&mut self) -> Result<i32, String> { Ok(i32::from_be_bytes(self.take()?)) }
+    fn i64(&mut self) -> Result<i64, String> { Ok(i64::from_be_bytes(self.take()?)) }
+
+    fn len(&mut self) -> Result<usize, String> {
+        let n = self.i32()?;
+        // Cada elemento ocupa al menos un byte: una longitud mayor es basura
+        if n < 0 || n as usize > self.data.len() - self.pos { return Err("longitud inválida en NBT".to_string()); }
+        Ok(n as usize)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let n = self.i16()? as u16 as usize;
+        let bytes = self.data.get(self.pos..self.pos + n).ok_or("NBT truncado")?;
+        self.pos += n;
+        // Java usa "UTF-8 modificado"; para IDs de bloque basta con lossy
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    fn payload(&mut self, id: u8, depth: u32) -> Result<Tag, String> {
+        if depth > MAX_DEPTH { return Err("NBT demasiado anidado".to_string()); }
+        Ok(match id {
+            1 => Tag::Byte(self.u8()? as i8),
+            2 => Tag::Short(self.i16()?),
+            3 => Tag::Int(self.i32()?),
+            4 => Tag::Long(self.i64()?),
+            5 => Tag::Float(f32::from_bits(self.i32()? as u32)),
+            6 => Tag::Double(f64::from_bits(self.i64()? as u64)),
+            7 => {
+                let n = self.len()?;
+                Tag::ByteArray((0..n).map(|_| self.u8().map(|b| b as i8)).collect::<Result<_, _>>()?)
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let elem = self.u8()?;
+                let n = self.len()?;
+                let mut items = Vec::with_capacity(n.min(4096));
+                for _ in 0..n { items.push(self.payload(elem, depth + 1)?); }
+                Tag::List(items)
+            }
+            10 => {
+                let mut fields = Vec::new();
+                loop {
+                    let id = self.u8()?;
+                    if id == 0 { break; }
+                    let name = self.string()?;
+                    fields.push((name, self.payload(id, depth + 1)?));
+                }
+                Tag::Compound(fields)
+            }
+            11 => {
+                let n = self.len()?;
+                Tag::IntArray((0..n).map(|_| self.i32()).collect::<Result<_, _>>()?)
+            }
+            12 => {
+                let n = self.len()?;
+                Tag::LongArray((0..n).map(|_| self.i64()).collect::<Result<_, _>>()?)
+            }
+            other => return Err(format!("tipo de tag NBT desconocido: {}", other)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    // Compound raíz sin nombre con un campo de cada tipo
+    const ALL_TAGS: &[u8] = &[
+        10, 0, 0,
+        1, 0, 1, b'b', 0xff,
+        2, 0, 1, b's', 0x01, 0x02,
+        3, 0, 1, b'i', 0xff, 0xff, 0xff, 0xfe,
+        4, 0, 1, b'l', 0, 0, 0, 0, 0, 0, 0x01, 0x00,
+        5, 0, 1, b'f', 0x3f, 0xc0, 0, 0,
+        6, 0, 1, b'd', 0xc0, 0x04, 0, 0, 0, 0, 0, 0,
+        7, 0, 2, b'b', b'a', 0, 0, 0, 2, 0x01, 0xff,
+        8, 0, 3, b's', b't', b'r', 0, 5, b'h', 0xc3, 0xa9, b'l', b'o',
+        9, 0, 1, b'L', 3, 0, 0, 0, 2, 0, 0, 0, 7, 0, 0, 0, 8,
+        9, 0, 1, b'E', 0, 0, 0, 0, 0,
+        10, 0, 1, b'c', 1, 0, 1, b'x', 5, 0,
+        11, 0, 2, b'i', b'a', 0, 0, 0, 1, 0, 0, 0, 9,
+        12, 0, 2, b'l', b'a', 0, 0, 0, 1, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0,
+    ];
+
+    fn field(name: &str, tag: Tag) -> (String, Tag) { (name.to_string(), tag) }
+
+    #[test]
+    fn every_tag_type() {
+        let (name, root) = parse(ALL_TAGS).unwrap();
+        assert_eq!(name, "");
+        assert_eq!(root, Tag::Compound(vec![
+            field("b", Tag::Byte(-1)),
+            field("s", Tag::Short(258)),
+            field("i", Tag::Int(-2)),
+            field("l", Tag::Long(256)),
+            field("f", Tag::Float(1.5)),
+            field("d", Tag::Double(-2.5)),
+            field("ba", Tag::ByteArray(vec![1, -1])),
+            field("str", Tag::String("hélo".to_string())),
+            field("L", Tag::List(vec![Tag::Int(7), Tag::Int(8)])),
+            field("E", Tag::List(vec![])),
+            field("c", Tag::Compound(vec![field("x", Tag::Byte(5))])),
+            field("ia", Tag::IntArray(vec![9])),
+            field("la", Tag::LongArray(vec![-1])),
+        ]));
+        assert_eq!(root.get("L").and_then(Tag::as_list).map(|l| l.len()), Some(2));
+        assert_eq!(root.get("s").and_then(Tag::as_i32), Some(258));
+        assert_eq!(root.get("str").and_then(Tag::as_str), Some("hélo"));
+    }
+
+    #[test]
+    fn compressed() {
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(ALL_TAGS).unwrap();
+        let mut zlib = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        zlib.write_all(ALL_TAGS).unwrap();
+        for bytes in [gz.finish().unwrap(), zlib.finish().unwrap()] {
+            assert_eq!(decompress(&bytes).unwrap(), ALL_TAGS);
+        }
+        assert!(decompress(&[0x1f, 0x8b, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn truncated_input() {
+        for n in 0..ALL_TAGS.len() {
+            assert!(parse(&ALL_TAGS[..n]).is_err(), "prefijo de {} bytes", n);
+        }
+    }
+
+    #[test]
+    fn bad_lengths_and_ids() {
+        let neg = [0xff, 0xff, 0xff, 0xff];
+        for head in [&[7u8, 0, 0][..], &[9, 0, 0, 1], &[11, 0, 0], &[12, 0, 0]] {
+            let bytes = [head, &neg].concat();
+            assert_eq!(parse(&bytes).unwrap_err(), "longitud inválida en NBT");
+        }
+        // Más elementos que bytes restantes
+        assert!(parse(&[11, 0, 0, 0x7f, 0xff, 0xff, 0xff, 0, 0]).is_err());
+        assert!(parse(&[13, 0, 0]).is_err());
+        assert!(parse(&[0]).is_err());
+
+        // Listas anidadas sin fin
+        let mut deep = vec![9, 0, 0];
+        for _ in 0..600 { deep.extend_from_slice(&[9, 0, 0, 0, 1]); }
+        assert_eq!(parse(&deep).unwrap_err(), "NBT demasiado anidado");
+    }
+}
@@ -0,0 +1,68 @@
+//! Exportador Wavefront OBJ + MTL.
+//!
+//! Escribe `modelo.obj`, `modelo.mtl` al lado y una PNG por textura
+//! (`modelo_<material>.png`). Un grupo `usemtl` por material; los colores del
+//! MTL van en RGB lineal y la emisión en `Ke` (ya multiplicada por su intensidad).
+
+use std::fmt::Write as _;
+use std::path::Path;
+use super::export::{self, ExportTexture};
+use crate::core::material::AlphaMode;
+use crate::core::material_registry::MaterialRegistry;
+use crate::core::mesh::Mesh;
+
+pub fn write_obj(path: &str, mesh: &Mesh, reg: &MaterialRegistry) -> Result<(), String> {
+    let path = Path::new(path);
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| "world".to_string());
+    let mtl_name = format!("{}.mtl", stem);
+    let (materials, textures) = export::materials(mesh, reg)?;
+    let tex_file = |t: &ExportTexture| format!("{}_{}.png", stem, t.name);
+
+    // Índices 1-based y comunes a todo el archivo: cada vértice tiene su v/vt/vn
+    let mut obj = String::new();
+    writeln!(obj, "# Minecraft Diorama: {} triángulos", mesh.triangles()).unwrap();
+    writeln!(obj, "mtllib {}", mtl_name).unwrap();
+    let mut base = 1;
+    for (g, m) in mesh.groups.iter().zip(&materials) {
+        writeln!(obj, "o {}", m.name).unwrap();
+        for p in &g.positions { writeln!(obj, "v {} {} {}", p[0], p[1], p[2]).unwrap(); }
+        for t in &g.uvs { writeln!(obj, "vt {} {}", t[0], t[1]).unwrap(); }
+        for n in &g.normals { writeln!(obj, "vn {} {} {}", n[0], n[1], n[2]).unwrap(); }
+        writeln!(obj, "usemtl {}", m.name).unwrap();
+        for tri in g.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| tri[k] as usize + base);
+            writeln!(obj, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}").unwrap();
+        }
+        base += g.positions.len();
+    }
+
+    let mut mtl = String::new();
+    for m in &materials {
+        writeln!(mtl, "newmtl {}", m.name).unwrap();
+        writeln!(mtl, "Kd {} {} {}", m.color[0], m.color[1], m.color[2]).unwrap();
+        writeln!(mtl, "Ks {0} {0} {0}", m.specular).unwrap();
+        writeln!(mtl, "Ns {}", m.shininess).unwrap();
+        writeln!(mtl, "Ke {} {} {}", m.emission[0], m.emission[1], m.emission[2]).unwrap();
+        writeln!(mtl, "Ni {}", m.ior).unwrap();
+        writeln!(mtl, "d {}", m.opacity).unwrap();
+        // illum 3 = con reflejos de trazado de rayos
+        writeln!(mtl, "illum {}", if m.reflectivity > 0.0 { 3 } else { 2 }).unwrap();
+        if let Some(t) = m.texture {
+            writeln!(mtl, "map_Kd {}", tex_file(&textures[t])).unwrap();
+            if m.alpha != AlphaMode::Opaque { writeln!(mtl, "map_d {}", tex_file(&textures[t])).unwrap(); }
+        }
+        if let Some(t) = m.emission_texture {
+            writeln!(mtl, "map_Ke {}", tex_file(&textures[t])).unwrap();
+        }
+        writeln!(mtl).unwrap();
+    }
+
+    let write = |name: &Path, bytes: &[u8]| std::fs::write(name, bytes).map_err(|e| format!("No pude escribir {}: {}", name.display(), e));
+    write(path, obj.as_bytes())?;
+    write(&dir.join(&mtl_name), mtl.as_bytes())?;
+    for t in &textures {
+        write(&dir.join(tex_file(t)), &t.png)?;
+    }
+    Ok(())
+}
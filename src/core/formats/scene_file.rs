@@ -0,0 +1,703 @@
+//! Formato declarativo de dioramas (JSON).
+//!
+//! La referencia completa, sección por sección, está en `docs/scene_format.md`;
+//! `scenes/diorama.json` es un ejemplo que reproduce el diorama por defecto.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use nalgebra_glm as glm;
+use crate::core::block::BlockKind;
+use crate::core::camera::OrbitCamera;
+use crate::core::color::Color;
+use crate::core::geometry::sphere::Sphere;
+use crate::core::light::{Attenuation, Light};
+use crate::core::material::{AlbedoTex, AlphaMode, Material};
+use crate::core::material_registry::MaterialRegistry;
+use crate::core::scene::Scene;
+use crate::core::sky::ProceduralSky;
+use crate::core::skybox::Skybox;
+use crate::core::texture::{FilterMode, Texture, WrapMode};
+use crate::core::world::World;
+use super::json::{self, Value};
+use super::anvil::AnvilWorld;
+use super::minecraft::BlockMapping;
+use super::schematic::Schematic;
+use super::vox::VoxFile;
+
+/// Resultado de cargar un archivo de escena
+pub struct SceneFile {
+    pub registry: MaterialRegistry,
+    pub world: World,
+    pub scene: Scene,
+    pub camera: OrbitCamera,
+    pub warnings: Vec<String>, // avisos no fatales (bloques sin mapear al importar)
+}
+
+type Pos = (i32, i32, i32);
+
+impl SceneFile {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let src = std::fs::read_to_string(path).map_err(|e| format!("No pude leer {}: {}", path, e))?;
+        let base = Path::new(path).parent().unwrap_or(Path::new("."));
+        Self::parse(&src, base).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Construye la escena desde el texto JSON; `base_dir` resuelve rutas relativas.
+    pub fn parse(src: &str, base_dir: &Path) -> Result<Self, String> {
+        let root = json::parse(src).map_err(|e| e.to_string())?;
+        if root.as_object().is_none() {
+            return Err("la raíz debe ser un objeto".to_string());
+        }
+        let loader = Loader { base_dir };
+
+        // Texturas
+        let mut textures: HashMap<String, Texture> = HashMap::new();
+        for (name, v) in entries(&root, "textures")? {
+            textures.insert(name.clone(), loader.texture(v).map_err(|e| format!("textures.{}: {}", name, e))?);
+        }
+
+        // Materiales
+        let mut materials: HashMap<String, Material> = HashMap::new();
+        for (name, v) in entries(&root, "materials")? {
+            let mat = material(v, &textures).map_err(|e| format!("materials.{}: {}", name, e))?;
+            materials.insert(name.clone(), mat);
+        }
+        let find_mat = |name: &str| {
+            materials.get(name).cloned().ok_or_else(|| format!("material desconocido '{}'", name))
+        };
+
+        // Tipos de bloque -> material
+        let mut registry = MaterialRegistry::new();
+        let mut kinds: HashMap<String, BlockKind> = BlockKind::BUILTIN.iter().map(|&(n, k)| (n.to_string(), k)).collect();
+        let mut next_custom = 0u16;
+        for (name, v) in entries(&root, "blocks")? {
+            let kind = match BlockKind::from_name(name) {
+                Some(k) => k,
+                None => {
+                    let k = BlockKind::Custom(next_custom);
+                    next_custom = next_custom.checked_add(1).ok_or("demasiados tipos de bloque")?;
+                    kinds.insert(name.clone(), k);
+                    k
+                }
+            };
+            let mat_name = v.as_str().ok_or_else(|| format!("blocks.{}: se esperaba el nombre de un material", name))?;
+            registry.set(kind, find_mat(mat_name).map_err(|e| format!("blocks.{}: {}", name, e))?);
+        }
+        let find_kind = |name: &str| kinds.get(name).copied().ok_or_else(|| format!("bloque desconocido '{}'", name));
+
+        // Mundo
+        let mut world = World::new();
+        let mut overrides: HashMap<Pos, Material> = HashMap::new();
+        let mut warnings = Vec::new();
+        for (i, e) in array(&root, "world")?.iter().enumerate() {
+            let mut placed: Vec<(Pos, BlockKind, Option<Material>)> = Vec::new();
+            let entry_mat = match e.get("material") {
+                Some(m) => Some(str_of(m).and_then(find_mat).map_err(|err| format!("world[{}]: {}", i, err))?),
+                None => None,
+            };
+
+            if let Some(path) = e.get("vox") {
+                // Modelo de MagicaVoxel: 'palette' asigna índices de color a bloques;
+                // el resto recibe un tipo nuevo con el color de la paleta
+                let ctx = |err: String| format!("world[{}]: {}", i, err);
+                let vox = VoxFile::load(&loader.resolve(str_of(path)?).to_string_lossy()).map_err(ctx)?;
+                let origin = e.get("at").map(ivec3).transpose()?.unwrap_or((0, 0, 0));
+                let mut mapping: HashMap<u8, BlockKind> = HashMap::new();
+                for (index, block) in e.get("palette").map(|p| p.as_object().ok_or("'palette' debe ser un objeto")).transpose()?.unwrap_or(&[]) {
+                    let index: u8 = index.parse().map_err(|_| ctx(format!("índice de paleta inválido '{}'", index)))?;
+                    mapping.insert(index, find_kind(str_of(block)?).map_err(ctx)?);
+                }
+                for ((x, y, z), color) in vox.voxels() {
+                    let kind = match mapping.get(&color) {
+                        Some(&k) => k,
+                        None => {
+                            let k = BlockKind::Custom(next_custom);
+                            next_custom = next_custom.checked_add(1).ok_or("demasiados tipos de bloque")?;
+                            registry.set(k, vox.material(color));
+                            mapping.insert(color, k);
+                            k
+                        }
+                    };
+                    placed.push(((origin.0 + x, origin.1 + y, origin.2 + z), kind, entry_mat.clone()));
+                }
+            } else if e.get("schematic").is_some() || e.get("region").is_some() {
+                // Estructura, schematic o mundo guardado de Minecraft: reglas del
+                // usuario sobre las vanilla
+                let ctx = |err: String| format!("world[{}]: {}", i, err);
+                let origin = e.get("at").map(ivec3).transpose()?.unwrap_or((0, 0, 0));
+                let mut mapping = BlockMapping::default();
+                for (pattern, block) in e.get("mapping").map(|m| m.as_object().ok_or("'mapping' debe ser un objeto")).transpose()?.unwrap_or(&[]) {
+                    mapping.map(pattern, find_kind(str_of(block)?).map_err(ctx)?);
+                }
+                mapping.fallback = e.get("unknown").map(|u| str_of(u).and_then(find_kind)).transpose().map_err(ctx)?;
+                // Importamos a un mundo aparte para aplicar 'material' y las sustituciones
+                let mut part = World::new();
+                let (path, report) = if let Some(path) = e.get("schematic") {
+                    let path = str_of(path)?;
+                    let schem = Schematic::load(&loader.resolve(path).to_string_lossy()).map_err(ctx)?;
+                    (path, schem.import(origin, &mapping, &mut part))
+                } else {
+                    let path = e.get("region").map(str_of).transpose()?.unwrap_or_default();
+                    let (from, to) = match (e.get("from"), e.get("to")) {
+                        (Some(f), Some(t)) => (ivec3(f)?, ivec3(t)?),
+                        _ => return Err(ctx("'region' necesita 'from' y 'to'".to_string())),
+                    };
+                    let save = AnvilWorld::open(&loader.resolve(path).to_string_lossy()).map_err(ctx)?;
+                    (path, save.import(from, to, origin, &mapping, &mut part).map_err(ctx)?)
+                };
+                if !report.unknown.is_empty() {
+                    warnings.push(format!("world[{}] ({}): {}", i, path, report));
+                }
+                for (pos, kind) in part.blocks() {
+                    placed.push((pos, kind, entry_mat.clone()));
+                }
+            } else if let Some(layer) = e.get("layer") {
+                let y = int_of(layer).map_err(|err| format!("world[{}].layer: {}", i, err))?;
+                let origin = e.get("origin").map(ivec2).transpose()?.unwrap_or((0, 0));
+                let rows = e.get("rows").and_then(Value::as_array).ok_or_else(|| format!("world[{}]: falta 'rows'", i))?;
+                let legend = e.get("legend").and_then(Value::as_object).ok_or_else(|| format!("world[{}]: falta 'legend'", i))?;
+
+                for (dz, row) in rows.iter().enumerate() {
+                    let row = row.as_str().ok_or_else(|| format!("world[{}].rows: se esperaban strings", i))?;
+                    for (dx, ch) in row.chars().enumerate() {
+                        // '.' y ' ' dejan la celda sin tocar
+                        if ch == '.' || ch == ' ' { continue; }
+                        let key = ch.to_string();
+                        let cell = legend.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+                            .ok_or_else(|| format!("world[{}]: '{}' no está en legend", i, ch))?;
+                        if cell.is_null() { continue; }
+                        let (kind, mat) = legend_cell(cell, &find_kind, &find_mat)
+                            .map_err(|err| format!("world[{}].legend.{}: {}", i, ch, err))?;
+                        let pos = (origin.0 + dx as i32, y, origin.1 + dz as i32);
+                        placed.push((pos, kind, mat.or_else(|| entry_mat.clone())));
+                    }
+                }
+            } else {
+                let kind = find_kind(e.get("block").map(str_of).transpose()?.ok_or_else(|| format!("world[{}]: falta 'block' o 'layer'", i))?)
+                    .map_err(|err| format!("world[{}]: {}", i, err))?;
+                let pos = |key: &str, v: &Value| ivec3(v).map_err(|err| format!("world[{}].{}: {}", i, key, err));
+                if let Some(at) = e.get("at") {
+                    placed.push((pos("at", at)?, kind, entry_mat));
+                } else {
+                    let (from, to) = match (e.get("from"), e.get("to")) {
+                        (Some(f), Some(t)) => (pos("from", f)?, pos("to", t)?),
+                        _ => return Err(format!("world[{}]: se esperaba 'at' o 'from'/'to'", i)),
+                    };
+                    for z in from.2.min(to.2)..=from.2.max(to.2) {
+                        for y in from.1.min(to.1)..=from.1.max(to.1) {
+                            for x in from.0.min(to.0)..=from.0.max(to.0) {
+                                placed.push(((x, y, z), kind, entry_mat.clone()));
+                            }
+                        }
+                    }
+                }
+            }
+
+            for ((x, y, z), kind, mat) in placed {
+                world.set(x, y, z, kind);
+                match mat {
+                    Some(m) => { overrides.insert((x, y, z), m); }
+                    None => { overrides.remove(&(x, y, z)); }
+                }
+            }
+        }
+
+        // Horneo + sustituciones por posición
+        let mut cubes = world.bake(&registry);
+        for c in &mut cubes {
+            let pos = (c.min.x.round() as i32, c.min.y.round() as i32, c.min.z.round() as i32);
+            if let Some(m) = overrides.get(&pos) {
+                c.material = m.clone();
+            }
+        }
+
+        let mut spheres = Vec::new();
+        for (i, s) in array(&root, "spheres")?.iter().enumerate() {
+            let ctx = |err: String| format!("spheres[{}]: {}", i, err);
+            let center = s.get("center").ok_or_else(|| "falta 'center'".to_string()).and_then(vec3).map_err(ctx)?;
+            let radius = num(s, "radius", 1.0).map_err(ctx)?;
+            let mat = s.get("material").ok_or_else(|| "falta 'material'".to_string()).and_then(str_of).and_then(find_mat).map_err(ctx)?;
+            spheres.push(Sphere::new(center, radius, mat));
+        }
+
+        let mut lights = Vec::new();
+        for (i, l) in array(&root, "lights")?.iter().enumerate() {
+            lights.push(light(l).map_err(|e| format!("lights[{}]: {}", i, e))?);
+        }
+
+        let skybox = match root.get("skybox") {
+            None | Some(Value::Null) => None,
+            Some(v) => Some(loader.skybox(v).map_err(|e| format!("skybox: {}", e))?),
+        };
+
+        let camera = match root.get("camera") {
+            None => default_camera(),
+            Some(c) => camera(c).map_err(|e| format!("camera: {}", e))?,
+        };
+
+        let sky = match root.get("sky") {
+            None | Some(Value::Null) => None,
+            Some(v) => Some(sky(v).map_err(|e| format!("sky: {}", e))?),
+        };
+
+        let mut scene = Scene::new(spheres, cubes, lights, skybox);
+        scene.set_sky(sky);
+        Ok(Self { registry, world, scene, camera, warnings })
+    }
+}
+
+/// Cámara por defecto (la misma del diorama de `main`)
+pub fn default_camera() -> OrbitCamera {
+    OrbitCamera::new(glm::vec3(0.0, 2.0, 0.0), 10.0, 1.0, 0.35)
+}
+
+struct Loader<'a> {
+    base_dir: &'a Path,
+}
+
+impl Loader<'_> {
+    fn resolve(&self, p: &str) -> PathBuf {
+        let path = Path::new(p);
+        if path.is_absolute() { path.to_path_buf() } else { self.base_dir.join(path) }
+    }
+
+    fn load_texture(&self, p: &str) -> Result<Texture, String> {
+        let full = self.resolve(p);
+        Texture::try_load(&full.to_string_lossy()).map_err(|e| format!("no pude abrir {}: {}", full.display(), e))
+    }
+
+    fn texture(&self, v: &Value) -> Result<Texture, String> {
+        match v {
+            Value::String(p) => self.load_texture(p),
+            Value::Object(_) => {
+                let p = v.get("path").map(str_of).transpose()?.ok_or("falta 'path'")?;
+                let mut tex = self.load_texture(p)?;
+                if flag(v, "rotate_180")? { tex = tex.rotated_180(); }
+                let wrap = match v.get("wrap").map(str_of).transpose()? {
+                    None | Some("repeat") => WrapMode::Repeat,
+                    Some("clamp") => WrapMode::Clamp,
+                    Some("mirror") => WrapMode::Mirror,
+                    Some(other) => return Err(format!("wrap desconocido '{}' (repeat | clamp | mirror)", other)),
+                };
+                let filter = match v.get("filter").map(str_of).transpose()? {
+                    None | Some("nearest") => FilterMode::Nearest,
+                    Some("bilinear") => FilterMode::Bilinear,
+                    Some("trilinear") => FilterMode::Trilinear,
+                    Some(other) => return Err(format!("filtro desconocido '{}' (nearest | bilinear | trilinear)", other)),
+                };
+                Ok(tex.with_wrap(wrap).with_filter(filter))
+            }
+            _ => Err("se esperaba una ruta o un objeto".to_string()),
+        }
+    }
+
+    fn skybox(&self, v: &Value) -> Result<Skybox, String> {
+        // Un solo archivo: según la proporción, equirectangular o cruz
+        if let Value::String(p) = v {
+            return Skybox::load(&self.resolve(p).to_string_lossy());
+        }
+        // Cada imagen acepta lo mismo que `textures` (ruta u objeto con filtro)
+        let sb = if let Some(t) = v.get("equirect") {
+            Skybox::equirect(self.texture(t)?)
+        } else if let Some(t) = v.get("cross") {
+            Skybox::cross(self.texture(t)?)?
+        } else {
+            let face = |k: &str| -> Result<Texture, String> {
+                self.texture(v.get(k).ok_or_else(|| format!("falta la cara '{}'", k))?)
+            };
+            Skybox::new(face("px")?, face("nx")?, face("py")?, face("ny")?, face("pz")?, face("nz")?)
+        };
+        Ok(sb.with_rotation(num(v, "rotation", 0.0)?.to_radians()))
+    }
+}
+
+fn material(v: &Value, textures: &HashMap<String, Texture>) -> Result<Material, String> {
+    let tex = |name: &Value| -> Result<Texture, String> {
+        let name = str_of(name)?;
+        textures.get(name).cloned().ok_or_else(|| format!("textura desconocida '{}'", name))
+    };
+    let specular = num(v, "specular", 0.0)?;
+    let shininess = num(v, "shininess", 1.0)?;
+    let reflectivity = num(v, "reflectivity", 0.0)?;
+    let transparency = num(v, "transparency", 0.0)?;
+    let ior = num(v, "ior", 1.0)?;
+    let cube_faces = |faces: &Value, key: &str| -> Result<AlbedoTex, String> {
+        let face = |k: &str| tex(faces.get(k).ok_or_else(|| format!("{}: falta '{}'", key, k))?);
+        Ok(AlbedoTex::Cube {
+            nx: face("nx")?, px: face("px")?,
+            ny: face("ny")?, py: face("py")?,
+            nz: face("nz")?, pz: face("pz")?,
+        })
+    };
+
+    let mut mat = if let Some(t) = v.get("texture") {
+        Material::with_texture(tex(t)?, specular, shininess, reflectivity, transparency, ior)
+    } else if let Some(faces) = v.get("faces") {
+        let mut m = Material::with_color(Color::new(255, 255, 255), specular, shininess, reflectivity, transparency, ior);
+        m.albedo_tex = cube_faces(faces, "faces")?;
+        m
+    } else {
+        let albedo = match v.get("color") {
+            Some(c) => color(c)?,
+            None => Color::new(255, 255, 255),
+        };
+        Material::with_color(albedo, specular, shininess, reflectivity, transparency, ior)
+    };
+
+    // Emisión: mapa (una textura o por cara) y/o color de tinte
+    let strength = num(v, "emission_strength", 1.0)?;
+    if let Some(t) = v.get("emission_texture") {
+        mat = mat.with_emission_map(AlbedoTex::Single(tex(t)?), strength);
+    } else if let Some(faces) = v.get("emission_faces") {
+        mat = mat.with_emission_map(cube_faces(faces, "emission_faces")?, strength);
+    }
+    if let Some(c) = v.get("emission") {
+        if mat.emission_strength > 0.0 {
+            mat.emission = color(c)?;
+        } else {
+            mat = mat.with_emission(color(c)?, strength);
+        }
+    }
+
+    mat.alpha_mode = match v.get("alpha").map(str_of).transpose()? {
+        None | Some("opaque") => AlphaMode::Opaque,
+        Some("cutout") => AlphaMode::Cutout { threshold: num(v, "alpha_threshold", 0.5)? },
+        Some("blend") => AlphaMode::Blend,
+        Some(other) => return Err(format!("alpha desconocido '{}' (opaque | cutout | blend)", other)),
+    };
+    Ok(mat)
+}
+
+fn legend_cell<K, M>(cell: &Value, find_kind: &K, find_mat: &M) -> Result<(BlockKind, Option<Material>), String>
+where
+    K: Fn(&str) -> Result<BlockKind, String>,
+    M: Fn(&str) -> Result<Material, String>,
+{
+    match cell {
+        Value::String(name) => Ok((find_kind(name)?, None)),
+        Value::Object(_) => {
+            let kind = find_kind(cell.get("block").map(str_of).transpose()?.ok_or("falta 'block'")?)?;
+            let mat = match cell.get("material") {
+                Some(m) => Some(find_mat(str_of(m)?)?),
+                None => None,
+            };
+            Ok((kind, mat))
+        }
+        _ => Err("se esperaba un nombre de bloque o un objeto".to_string()),
+    }
+}
+
+fn light(v: &Value) -> Result<Light, String> {
+    let kind = v.get("type").map(str_of).transpose()?.unwrap_or("point");
+    let col = match v.get("color") {
+        Some(c) => color(c)?,
+        None => Color::new(255, 255, 255),
+    };
+    let intensity = num(v, "intensity", 1.0)?;
+    let field = |k: &str| v.get(k).ok_or_else(|| format!("falta '{}'", k)).and_then(vec3);
+    let light = match kind {
+        "point" => Light::point(field("position")?, col, intensity),
+        "directional" => Light::directional(field("direction")?, col, intensity),
+        "rect" => Light::rect(field("center")?, field("edge_u")?, field("edge_v")?, col, intensity),
+        "disc" => Light::disc(field("center")?, field("normal")?, num(v, "radius", 0.5)?, col, intensity),
+        "sphere" => Light::sphere(field("center")?, num(v, "radius", 0.5)?, col, intensity),
+        "spot" => {
+            let outer = num(v, "outer_angle", 30.0)?;
+            let inner = num(v, "inner_angle", outer * 0.8)?;
+            Light::spot(field("position")?, field("direction")?, inner.to_radians(), outer.to_radians(), col, intensity)
+        }
+        other => return Err(format!("tipo de luz desconocido '{}'", other)),
+    };
+    Ok(match v.get("attenuation") {
+        Some(a) => {
+            let a = vec3(a)?;
+            light.with_attenuation(Attenuation { constant: a.x, linear: a.y, quadratic: a.z })
+        }
+        None => light,
+    })
+}
+
+fn sky(v: &Value) -> Result<ProceduralSky, String> {
+    let mut sky = ProceduralSky::new(num(v, "time", 12.0)?);
+    sky.turbidity = num(v, "turbidity", sky.turbidity)?.clamp(1.0, 20.0);
+    sky.latitude = num(v, "latitude", sky.latitude.to_degrees())?.to_radians();
+    if v.get("stars").is_some() { sky.stars = flag(v, "stars")?; }
+    Ok(sky)
+}
+
+fn camera(v: &Value) -> Result<OrbitCamera, String> {
+    let mut cam = default_camera();
+    if let Some(c) = v.get("center") { cam.center = vec3(c)?; }
+    cam.radius = num(v, "radius", cam.radius)?;
+    cam.yaw = num(v, "yaw", cam.yaw)?;
+    cam.pitch = num(v, "pitch", cam.pitch)?;
+    Ok(cam)
+}
+
+// --- Helpers de lectura ---
+
+fn entries<'a>(root: &'a Value, key: &str) -> Result<&'a [(String, Value)], String> {
+    match root.get(key) {
+        None => Ok(&[]),
+        Some(v) => v.as_object().ok_or_else(|| format!("'{}' debe ser un objeto", key)),
+    }
+}
+
+fn array<'a>(root: &'a Value, key: &str) -> Result<&'a [Value], String> {
+    match root.get(key) {
+        None => Ok(&[]),
+        Some(v) => v.as_array().ok_or_else(|| format!("'{}' debe ser una lista", key)),
+    }
+}
+
+fn str_of(v: &Value) -> Result<&str, String> {
+    v.as_str().ok_or_else(|| "se esperaba un string".to_string())
+}
+
+fn int_of(v: &Value) -> Result<i32, String> {
+    v.as_i32().ok_or_else(|| "se esperaba un entero".to_string())
+}
+
+fn num(v: &Value, key: &str, default: f32) -> Result<f32, String> {
+    match v.get(key) {
+        None => Ok(default),
+        Some(n) => n.as_f32().ok_or_else(|| format!("'{}' debe ser un número", key)),
+    }
+}
+
+fn flag(v: &Value, key: &str) -> Result<bool, String> {
+    match v.get(key) {
+        None => Ok(false),
+        Some(b) => b.as_bool().ok_or_else(|| format!("'{}' debe ser true/false", key)),
+    }
+}
+
+fn numbers<const N: usize>(v: &Value) -> Result<[f64; N], String> {
+    let arr = v.as_array().filter(|a| a.len() == N).ok_or_else(|| format!("se esperaba una lista de {} números", N))?;
+    let mut out = [0.0; N];
+    for (o, x) in out.iter_mut().zip(arr) {
+        *o = x.as_f64().ok_or_else(|| format!("se esperaba una lista de {} números", N))?;
+    }
+    Ok(out)
+}
+
+fn vec3(v: &Value) -> Result<glm::Vec3, String> {
+    let [x, y, z] = numbers::<3>(v)?;
+    Ok(glm::vec3(x as f32, y as f32, z as f32))
+}
+
+fn ivec3(v: &Value) -> Result<Pos, String> {
+    let [x, y, z] = numbers::<3>(v)?;
+    if [x, y, z].iter().any(|c| c.fract() != 0.0) {
+        return Err("las posiciones de bloque deben ser enteras".to_string());
+    }
+    Ok((x as i32, y as i32, z as i32))
+}
+
+fn ivec2(v: &Value) -> Result<(i32, i32), String> {
+    let [x, z] = numbers::<2>(v)?;
+    if x.fract() != 0.0 || z.fract() != 0.0 {
+        return Err("'origin' debe ser entero".to_string());
+    }
+    Ok((x as i32, z as i32))
+}
+
+fn color(v: &Value) -> Result<Color, String> {
+    let [r, g, b] = numbers::<3>(v)?;
+    let c = |x: f64| x.round().clamp(0.0, 255.0) as u8;
+    Ok(Color::new(c(r), c(g), c(b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::light::LightKind;
+
+    fn parse(src: &str) -> SceneFile {
+        SceneFile::parse(src, Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn error(src: &str) -> String {
+        match SceneFile::parse(src, Path::new(env!("CARGO_MANIFEST_DIR"))) {
+            Ok(_) => panic!("se esperaba un error en {}", src),
+            Err(e) => e,
+        }
+    }
+
+    fn cube_at(file: &SceneFile, p: Pos) -> &Material {
+        let min = glm::vec3(p.0 as f32, p.1 as f32, p.2 as f32);
+        &file.scene.cubes.iter().find(|c| c.min == min).unwrap_or_else(|| panic!("no hay cubo en {:?}", p)).material
+    }
+
+    fn rgb(c: Color) -> (u8, u8, u8) { (c.r, c.g, c.b) }
+
+    const BLOCKS: &str = r#"
+        "materials": { "gray": { "color": [128, 128, 128] }, "red": { "color": [200, 30, 30] },
+                       "blue": { "color": [20, 40, 220], "specular": 0.5, "shininess": 16 } },
+        "blocks": { "stone": "gray", "marble": "red", "lapis": "blue" }"#;
+
+    #[test]
+    fn blocks_at_and_boxes() {
+        let file = parse(&format!(r#"{{ {}, "world": [
+            {{ "block": "marble", "at": [0, 5, 0] }},
+            {{ "block": "stone", "from": [2, -1, 1], "to": [-1, 0, 3] }},
+            {{ "block": "lapis", "at": [0, 0, 2], "material": "red" }}
+        ] }}"#, BLOCKS));
+
+        // 'marble' y 'lapis' no son predefinidos: reciben tipos Custom en orden
+        assert_eq!(file.world.get(0, 5, 0), Some(BlockKind::Custom(0)));
+        assert_eq!(file.world.get(0, 0, 2), Some(BlockKind::Custom(1)));
+        // La caja acepta esquinas en cualquier orden: 4 × 2 × 3 menos la pisada
+        let boxed: Vec<_> = file.world.blocks_in((-1, -1, 1), (2, 0, 3)).collect();
+        assert_eq!(boxed.len(), 24);
+        assert_eq!(boxed.iter().filter(|&&(_, k)| k == BlockKind::Stone).count(), 23);
+        assert_eq!(file.world.len(), 25);
+
+        assert_eq!(rgb(cube_at(&file, (0, 5, 0)).albedo), (200, 30, 30));
+        assert_eq!(rgb(cube_at(&file, (2, 0, 3)).albedo), (128, 128, 128));
+        // 'material' reemplaza sólo en esa posición
+        let lapis = file.registry.get(BlockKind::Custom(1)).unwrap();
+        assert_eq!((rgb(lapis.albedo), lapis.specular, lapis.shininess), ((20, 40, 220), 0.5, 16.0));
+        assert_eq!(rgb(cube_at(&file, (0, 0, 2)).albedo), (200, 30, 30));
+    }
+
+    #[test]
+    fn layers_and_legend() {
+        let file = parse(&format!(r#"{{ {}, "world": [
+            {{ "layer": 2, "origin": [-1, 4], "rows": ["SM.", " L", "S"],
+               "legend": {{ "S": "stone", "M": "marble", "L": {{ "block": "lapis", "material": "red" }} }} }},
+            {{ "layer": 2, "origin": [-1, 4], "rows": ["S"], "legend": {{ "S": null }} }}
+        ] }}"#, BLOCKS));
+
+        // Las filas avanzan en z y los caracteres en x; '.', ' ' y null no tocan la celda
+        let mut blocks: Vec<_> = file.world.blocks().collect();
+        blocks.sort_by_key(|&(p, _)| p);
+        assert_eq!(blocks, [
+            ((-1, 2, 4), BlockKind::Stone),
+            ((-1, 2, 6), BlockKind::Stone),
+            ((0, 2, 4), BlockKind::Custom(0)),
+            ((0, 2, 5), BlockKind::Custom(1)),
+        ]);
+        assert_eq!(rgb(cube_at(&file, (0, 2, 5)).albedo), (200, 30, 30));
+        assert_eq!(rgb(cube_at(&file, (-1, 2, 6)).albedo), (128, 128, 128));
+    }
+
+    #[test]
+    fn materials_and_textures() {
+        let file = parse(r#"{
+            "textures": { "stone": "assets/stone.png",
+                          "lava": { "path": "assets/lava.png", "filter": "trilinear", "wrap": "clamp" } },
+            "materials": {
+                "stone": { "texture": "stone", "reflectivity": 0.25 },
+                "lava": { "texture": "lava", "emission_texture": "lava", "emission_strength": 2 },
+                "glass": { "color": [240, 250, 255], "transparency": 0.8, "ior": 1.5, "alpha": "cutout", "alpha_threshold": 0.3 },
+                "glow": { "emission": [255, 128, 0], "emission_strength": 3 }
+            },
+            "blocks": { "stone": "stone", "lava": "lava", "water": "glass", "glow": "glow" }
+        }"#);
+
+        let stone = file.registry.get(BlockKind::Stone).unwrap();
+        let tex = stone.albedo_tex.for_face(None).expect("textura de piedra");
+        let want = Texture::load(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/stone.png"));
+        assert_eq!((tex.width, tex.height, tex.filter(), tex.wrap), (want.width, want.height, FilterMode::Nearest, WrapMode::Repeat));
+        assert_eq!(stone.reflectivity, 0.25);
+
+        let lava = file.registry.get(BlockKind::Lava).unwrap();
+        let tex = lava.albedo_tex.for_face(None).unwrap();
+        assert_eq!((tex.filter(), tex.wrap), (FilterMode::Trilinear, WrapMode::Clamp));
+        assert_eq!(lava.emission_strength, 2.0);
+        assert!(lava.emission_tex.for_face(None).is_some());
+
+        let glass = file.registry.get(BlockKind::Water).unwrap();
+        assert_eq!((glass.transparency, glass.ior), (0.8, 1.5));
+        assert!(matches!(glass.alpha_mode, AlphaMode::Cutout { threshold } if threshold == 0.3));
+
+        let glow = file.registry.get(BlockKind::Custom(0)).unwrap();
+        assert_eq!((rgb(glow.emission), glow.emission_strength), ((255, 128, 0), 3.0));
+        assert!(glow.is_emissive());
+    }
+
+    #[test]
+    fn camera_lights_spheres_and_sky() {
+        let file = parse(&format!(r#"{{ {},
+            "spheres": [ {{ "center": [0, 3, 0], "radius": 0.5, "material": "red" }} ],
+            "lights": [
+                {{ "position": [4, 6, 4], "intensity": 1.5 }},
+                {{ "type": "directional", "direction": [0, -1, 0], "color": [255, 0, 0] }},
+                {{ "type": "spot", "position": [0, 5, 0], "direction": [0, -1, 0], "inner_angle": 20, "outer_angle": 30,
+                   "attenuation": [1, 0, 0.1] }},
+                {{ "type": "rect", "center": [0, 6, 0], "edge_u": [2, 0, 0], "edge_v": [0, 0, 2] }},
+                {{ "type": "disc", "center": [0, 6, 0], "normal": [0, -1, 0], "radius": 1 }},
+                {{ "type": "sphere", "center": [3, 5, 3] }}
+            ],
+            "sky": {{ "time": 17.5, "turbidity": 50, "latitude": 30, "stars": true }},
+            "camera": {{ "center": [1, 2, 3], "radius": 7 }}
+        }}"#, BLOCKS));
+
+        assert_eq!(file.scene.spheres.len(), 1);
+        assert_eq!((file.scene.spheres[0].center, file.scene.spheres[0].radius), (glm::vec3(0.0, 3.0, 0.0), 0.5));
+        assert_eq!(rgb(file.scene.spheres[0].material.albedo), (200, 30, 30));
+
+        let lights = &file.scene.lights;
+        assert_eq!(lights.len(), 6);
+        assert!(matches!(lights[0].kind, LightKind::Point { attenuation, .. } if attenuation == Attenuation::NONE));
+        assert_eq!(lights[0].intensity, 1.5);
+        assert!(matches!(lights[1].kind, LightKind::Directional { .. }));
+        assert_eq!(rgb(lights[1].color), (255, 0, 0));
+        match lights[2].kind {
+            LightKind::Spot { inner_angle, outer_angle, attenuation, .. } => {
+                assert!((inner_angle - 20f32.to_radians()).abs() < 1e-6);
+                assert!((outer_angle - 30f32.to_radians()).abs() < 1e-6);
+                assert_eq!(attenuation.quadratic, 0.1);
+            }
+            other => panic!("se esperaba un foco: {:?}", other),
+        }
+        assert!(matches!(lights[3].kind, LightKind::Rect { .. }));
+        assert!(matches!(lights[4].kind, LightKind::Disc { radius, .. } if radius == 1.0));
+        assert!(matches!(lights[5].kind, LightKind::Sphere { radius, .. } if radius == 0.5));
+
+        let sky = file.scene.sky().expect("cielo procedural");
+        assert_eq!((sky.time_of_day, sky.turbidity, sky.stars), (17.5, 20.0, true));
+        assert!((sky.latitude - 30f32.to_radians()).abs() < 1e-6);
+
+        // Lo que falta en 'camera' queda como en la cámara por defecto
+        let def = default_camera();
+        assert_eq!((file.camera.center, file.camera.radius), (glm::vec3(1.0, 2.0, 3.0), 7.0));
+        assert_eq!((file.camera.yaw, file.camera.pitch), (def.yaw, def.pitch));
+
+        // Sin secciones: escena vacía con la cámara por defecto
+        let empty = parse("{}");
+        assert!(empty.world.is_empty() && empty.scene.cubes.is_empty() && empty.scene.lights.is_empty());
+        assert!(empty.scene.sky().is_none() && empty.scene.skybox.is_none());
+        assert_eq!(empty.camera.center, def.center);
+    }
+
+    #[test]
+    fn error_messages() {
+        let with_world = |entry: &str| error(&format!(r#"{{ {}, "world": [ {} ] }}"#, BLOCKS, entry));
+
+        assert_eq!(with_world(r#"{ "block": "obsidian", "at": [0, 0, 0] }"#), "world[0]: bloque desconocido 'obsidian'");
+        assert_eq!(with_world(r#"{ "at": [0, 0, 0] }"#), "world[0]: falta 'block' o 'layer'");
+        assert_eq!(with_world(r#"{ "block": "stone" }"#), "world[0]: se esperaba 'at' o 'from'/'to'");
+        assert_eq!(with_world(r#"{ "block": "stone", "at": [0, 0] }"#), "world[0].at: se esperaba una lista de 3 números");
+        assert_eq!(with_world(r#"{ "block": "stone", "from": [0, "1", 0], "to": [1, 1, 1] }"#), "world[0].from: se esperaba una lista de 3 números");
+        assert_eq!(with_world(r#"{ "block": "stone", "at": [0, 0.5, 0] }"#), "world[0].at: las posiciones de bloque deben ser enteras");
+        assert_eq!(with_world(r#"{ "block": "stone", "at": [0, 0, 0], "material": "gold" }"#), "world[0]: material desconocido 'gold'");
+        assert_eq!(with_world(r#"{ "layer": 0, "rows": ["X"] }"#), "world[0]: falta 'legend'");
+        assert_eq!(with_world(r#"{ "layer": 0, "rows": ["X"], "legend": { "S": "stone" } }"#), "world[0]: 'X' no está en legend");
+        assert_eq!(with_world(r#"{ "layer": 0, "rows": ["S"], "legend": { "S": "dust" } }"#), "world[0].legend.S: bloque desconocido 'dust'");
+
+        assert_eq!(error(r#"{ "blocks": { "stone": "gold" } }"#), "blocks.stone: material desconocido 'gold'");
+        assert_eq!(error(r#"{ "materials": { "m": { "texture": "none" } } }"#), "materials.m: textura desconocida 'none'");
+        assert_eq!(error(r#"{ "materials": { "m": { "alpha": "half" } } }"#), "materials.m: alpha desconocido 'half' (opaque | cutout | blend)");
+        assert_eq!(error(r#"{ "lights": [ { "type": "laser" } ] }"#), "lights[0]: tipo de luz desconocido 'laser'");
+        assert_eq!(error(r#"{ "lights": [ { "type": "spot", "position": [0, 1, 0] } ] }"#), "lights[0]: falta 'direction'");
+        assert_eq!(error(r#"{ "camera": { "radius": "far" } }"#), "camera: 'radius' debe ser un número");
+        assert_eq!(error(r#"{ "spheres": [ { "center": [0, 0, 0] } ] }"#), "spheres[0]: falta 'material'");
+        assert_eq!(error(r#"{ "spheres": [ { "center": [0, 0], "material": "red" } ], "materials": { "red": {} } }"#),
+                   "spheres[0]: se esperaba una lista de 3 números");
+        assert_eq!(error(r#"{ "world": {} }"#), "'world' debe ser una lista");
+        assert_eq!(error("[]"), "la raíz debe ser un objeto");
+        assert!(error(r#"{ "textures": { "t": "assets/missing.png" } }"#).starts_with("textures.t: no pude abrir"));
+    }
+}
@@ -0,0 +1,440 @@
+//! Importador de modelos MagicaVoxel (.vox).
+//!
+//! Lee los chunks `SIZE`/`XYZI` de cada modelo, la paleta `RGBA`, los
+//! materiales `MATL` (vidrio, metal, emisión) y el grafo de escena
+//! (`nTRN`/`nGRP`/`nSHP`) para ubicar varios modelos; las rotaciones de los
+//! nodos se ignoran. MagicaVoxel usa Z hacia arriba: un voxel (x, y, z) queda
+//! en el bloque (x, z, -y), con la esquina mínima del conjunto en el origen.
+//!
+//! Cada índice de paleta se convierte en un `BlockKind` elegido por el usuario
+//! o en un `BlockKind::Custom` con un material de color sólido.
+
+use std::collections::HashMap;
+use crate::core::block::BlockKind;
+use crate::core::color::Color;
+use crate::core::material::Material;
+use crate::core::material_registry::MaterialRegistry;
+use crate::core::world::World;
+
+// Tamaños y traslaciones más allá de esto no son de un .vox real; acotarlos
+// evita desbordes al sumar las traslaciones del grafo
+const MAX_COORD: i32 = 1 << 20;
+
+pub struct VoxModel {
+    pub size: [i32; 3],
+    pub voxels: Vec<([u8; 3], u8)>, // posición dentro del modelo e índice de color (1..=255)
+    pub translation: [i32; 3],      // centro del modelo en la escena (coordenadas .vox)
+}
+
+/// Material de un índice de paleta (chunk `MATL`)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VoxMaterial {
+    pub glass: f32,    // transparencia (0 = opaco)
+    pub ior: f32,
+    pub metal: f32,    // reflectividad
+    pub emission: f32, // intensidad de emisión
+}
+
+pub struct VoxFile {
+    pub models: Vec<VoxModel>,
+    pub palette: [[u8; 4]; 256], // RGBA por índice de color (el 0 no se usa)
+    pub materials: HashMap<u8, VoxMaterial>,
+}
+
+/// Cómo volcar un .vox en un `World`
+#[derive(Clone, Debug, Default)]
+pub struct VoxImport {
+    pub origin: (i32, i32, i32),        // dónde cae la esquina mínima del modelo
+    pub palette: HashMap<u8, BlockKind>, // índices que usan un tipo ya registrado
+    // Primer `Custom(n)` para el resto de colores; None = el siguiente libre
+    // del registro. Nunca se pisa un `Custom` ya registrado.
+    pub first_custom: Option<u16>,
+}
+
+/// Resultado de `VoxFile::import`
+pub struct VoxSummary {
+    pub blocks: usize,
+    pub next_custom: u16, // siguiente `Custom(n)` libre
+}
+
+impl VoxFile {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("No pude leer {}: {}", path, e))?;
+        Self::parse(&bytes).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let mut r = Reader { data: bytes, pos: 0 };
+        if r.take(4)? != b"VOX " {
+            return Err("no es un archivo .vox".to_string());
+        }
+        r.i32()?; // versión
+
+        let mut sizes = Vec::new();
+        let mut models = Vec::new();
+        let mut palette = None;
+        let mut materials = HashMap::new();
+        let mut nodes = HashMap::new();
+
+        // MAIN envuelve al resto; recorremos los chunks en orden plano
+        while r.pos < bytes.len() {
+            let id: [u8; 4] = r.take(4)?.try_into().unwrap();
+            let content = r.i32()?.max(0) as usize;
+            r.i32()?; // tamaño de los hijos (vienen a continuación)
+            if &id == b"MAIN" { continue; }
+            let mut c = Reader { data: r.take(content)?, pos: 0 };
+            match &id {
+                b"SIZE" => {
+                    let size = [c.i32()?, c.i32()?, c.i32()?];
+                    if size.iter().any(|v| !(0..=MAX_COORD).contains(v)) {
+                        return Err(format!("tamaño de modelo fuera de rango: {:?}", size));
+                    }
+                    sizes.push(size);
+                }
+                b"XYZI" => {
+                    let n = c.i32()?.max(0) as usize;
+                    let mut voxels = Vec::with_capacity(n);
+                    for _ in 0..n {
+                        let v = c.take(4)?;
+                        voxels.push(([v[0], v[1], v[2]], v[3]));
+                    }
+                    let size = *sizes.get(models.len()).ok_or("XYZI sin SIZE")?;
+                    models.push(VoxModel { size, voxels, translation: [0; 3] });
+                }
+                b"RGBA" => {
+                    // La entrada i corresponde al índice de color i + 1
+                    let mut pal = [[0u8; 4]; 256];
+                    for i in 0..255 {
+                        pal[i + 1].copy_from_slice(c.take(4)?);
+                    }
+                    palette = Some(pal);
+                }
+                b"MATL" => {
+                    let id = c.i32()?;
+                    let props = c.dict()?;
+                    if let Ok(index) = u8::try_from(id) {
+                        materials.insert(index, vox_material(&props));
+                    }
+                }
+                b"nTRN" => {
+                    let id = c.i32()?;
+                    c.dict()?;
+                    let child = c.i32()?;
+                    c.i32()?; // reservado
+                    c.i32()?; // capa
+                    let frames = c.i32()?;
+                    let mut translation = [0; 3];
+                    if frames > 0 && let Some(t) = c.dict()?.get("_t") {
+                        let parts: Vec<i32> = t.split_whitespace().filter_map(|p| p.parse().ok()).collect();
+                        if let [x, y, z] = parts[..] { translation = [x, y, z]; }
+                        if translation.iter().any(|v| v.abs() > MAX_COORD) {
+                            return Err(format!("traslación fuera de rango: {:?}", translation));
+                        }
+                    }
+                    nodes.insert(id, Node::Transform { child, translation });
+                }
+                b"nGRP" => {
+                    let id = c.i32()?;
+                    c.dict()?;
+                    let n = c.i32()?.max(0);
+                    let children = (0..n).map(|_| c.i32()).collect::<Result<_, _>>()?;
+                    nodes.insert(id, Node::Group { children });
+                }
+                b"nSHP" => {
+                    let id = c.i32()?;
+                    c.dict()?;
+                    let n = c.i32()?.max(0);
+                    let mut shape = Vec::new();
+                    for _ in 0..n {
+                        shape.push(c.i32()?);
+                        c.dict()?;
+                    }
+                    nodes.insert(id, Node::Shape { models: shape });
+                }
+                _ => {} // chunks que no usamos (capas, cámaras, notas...)
+            }
+        }
+        if models.is_empty() {
+            return Err("el archivo no tiene modelos".to_string());
+        }
+
+        // Grafo de escena: acumulamos las traslaciones desde la raíz (nodo 0)
+        if nodes.contains_key(&0) {
+            let mut placed = vec![None; models.len()];
+            place(&nodes, 0, [0; 3], &mut placed, 0);
+            for (m, t) in models.iter_mut().zip(placed) {
+                if let Some(t) = t { m.translation = t; }
+            }
+        }
+
+        Ok(Self { models, palette: palette.unwrap_or_else(fallback_palette), materials })
+    }
+
+    /// Voxels en coordenadas del diorama (Y arriba), con la esquina mínima en (0, 0, 0)
+    pub fn voxels(&self) -> Vec<((i32, i32, i32), u8)> {
+        let mut out = Vec::new();
+        for m in &self.models {
+            // La traslación apunta al centro del modelo
+            let base = [0, 1, 2].map(|k| m.translation[k] - m.size[k] / 2);
+            for &(p, color) in &m.voxels {
+                let (x, y, z) = (base[0] + p[0] as i32, base[1] + p[1] as i32, base[2] + p[2] as i32);
+                out.push(((x, z, -y), color));
+            }
+        }
+        let min = out.iter().fold((i32::MAX, i32::MAX, i32::MAX), |m, &((x, y, z), _)| (m.0.min(x), m.1.min(y), m.2.min(z)));
+        for (p, _) in &mut out {
+            *p = (p.0 - min.0, p.1 - min.1, p.2 - min.2);
+        }
+        out
+    }
+
+    /// Material de color sólido para un índice de paleta (con vidrio, metal o
+    /// emisión si el archivo los define)
+    pub fn material(&self, index: u8) -> Material {
+        let [r, g, b, a] = self.palette[index as usize];
+        let color = Color::new(r, g, b);
+        let m = self.materials.get(&index).copied().unwrap_or_default();
+        let transparency = m.glass.max(1.0 - a as f32 / 255.0);
+        let ior = if transparency > 0.0 { m.ior.max(1.0) } else { 1.0 };
+        let mat = Material::with_color(color, 0.04 + 0.4 * m.metal, 8.0 + 56.0 * m.metal, m.metal, transparency, ior);
+        if m.emission > 0.0 { mat.with_emission(color, m.emission) } else { mat }
+    }
+
+    /// Pone los voxels en `world` y registra un material para cada color que
+    /// no esté en `opts.palette`
+    pub fn import(&self, opts: &VoxImport, world: &mut World, registry: &mut MaterialRegistry) -> Result<VoxSummary, String> {
+        let mut kinds = opts.palette.clone();
+        let mut next_custom = match opts.first_custom {
+            Some(n) => n,
+            None => registry.next_custom().ok_or("demasiados tipos de bloque")?,
+        };
+        let voxels = self.voxels();
+        for &((x, y, z), color) in &voxels {
+            let kind = match kinds.get(&color) {
+                Some(&k) => k,
+                None => {
+                    while registry.get(BlockKind::Custom(next_custom)).is_some() {
+                        next_custom = next_custom.checked_add(1).ok_or("demasiados tipos de bloque")?;
+                    }
+                    let k = BlockKind::Custom(next_custom);
+                    next_custom = next_custom.checked_add(1).ok_or("demasiados tipos de bloque")?;
+                    registry.set(k, self.material(color));
+                    kinds.insert(color, k);
+                    k
+                }
+            };
+            world.set(opts.origin.0 + x, opts.origin.1 + y, opts.origin.2 + z, kind);
+        }
+        Ok(VoxSummary { blocks: voxels.len(), next_custom })
+    }
+}
+
+enum Node {
+    Transform { child: i32, translation: [i32; 3] },
+    Group { children: Vec<i32> },
+    Shape { models: Vec<i32> },
+}
+
+fn place(nodes: &HashMap<i32, Node>, id: i32, t: [i32; 3], out: &mut [Option<[i32; 3]>], depth: u32) {
+    // Un grafo mal formado podría tener ciclos
+    if depth > 64 { return; }
+    match nodes.get(&id) {
+        Some(Node::Transform { child, translation }) => {
+            let t = [0, 1, 2].map(|k| t[k].saturating_add(translation[k]));
+            place(nodes, *child, t, out, depth + 1);
+        }
+        Some(Node::Group { children }) => {
+            for &c in children { place(nodes, c, t, out, depth + 1); }
+        }
+        Some(Node::Shape { models }) => {
+            for &m in models {
+                if let Some(slot) = usize::try_from(m).ok().and_then(|m| out.get_mut(m)) { *slot = Some(t); }
+            }
+        }
+        None => {}
+    }
+}
+
+fn vox_material(props: &HashMap<String, String>) -> VoxMaterial {
+    let get = |k: &str| props.get(k).and_then(|v| v.parse::<f32>().ok());
+    let mut m = VoxMaterial { ior: 1.0, ..Default::default() };
+    match props.get("_type").map(String::as_str) {
+        Some("_glass") => {
+            m.glass = get("_trans").or(get("_alpha")).unwrap_or(0.5).clamp(0.0, 1.0);
+            // Las versiones nuevas guardan ior - 1
+            m.ior = get("_ior").map_or(1.5, |v| if v < 1.0 { 1.0 + v } else { v });
+        }
+        Some("_metal") => m.metal = get("_metal").unwrap_or(0.5).clamp(0.0, 1.0) * (1.0 - get("_rough").unwrap_or(0.0).clamp(0.0, 1.0)),
+        Some("_emit") => m.emission = get("_emit").unwrap_or(1.0).max(0.0) * (1.0 + get("_flux").unwrap_or(0.0).max(0.0)),
+        _ => {}
+    }
+    m
+}
+
+/// Sin chunk `RGBA`: rampa de grises (la paleta por defecto de MagicaVoxel no
+/// viene en el archivo)
+fn fallback_palette() -> [[u8; 4]; 256] {
+    let mut pal = [[0u8; 4]; 256];
+    for (i, p) in pal.iter_mut().enumerate() {
+        let v = 255 - i as u8;
+        *p = [v, v, v, 255];
+    }
+    pal
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(n).filter(|&e| e <= self.data.len()).ok_or("archivo truncado")?;
+        let out = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let n = self.i32()?.max(0) as usize;
+        Ok(String::from_utf8_lossy(self.take(n)?).into_owned())
+    }
+
+    fn dict(&mut self) -> Result<HashMap<String, String>, String> {
+        let n = self.i32()?.max(0);
+        let mut out = HashMap::new();
+        for _ in 0..n {
+            let k = self.string()?;
+            out.insert(k, self.string()?);
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: &[u8; 4], content: &[u8], children: &[u8]) -> Vec<u8> {
+        let mut out = id.to_vec();
+        out.extend_from_slice(&(content.len() as i32).to_le_bytes());
+        out.extend_from_slice(&(children.len() as i32).to_le_bytes());
+        out.extend_from_slice(content);
+        out.extend_from_slice(children);
+        out
+    }
+
+    fn ints(v: &[i32]) -> Vec<u8> { v.iter().flat_map(|i| i.to_le_bytes()).collect() }
+
+    fn dict(pairs: &[(&str, &str)]) -> Vec<u8> {
+        let mut out = ints(&[pairs.len() as i32]);
+        for (k, v) in pairs {
+            for s in [k, v] {
+                out.extend(ints(&[s.len() as i32]));
+                out.extend_from_slice(s.as_bytes());
+            }
+        }
+        out
+    }
+
+    fn model(size: [i32; 3], voxels: &[[u8; 4]]) -> Vec<u8> {
+        let mut xyzi = ints(&[voxels.len() as i32]);
+        xyzi.extend(voxels.iter().flatten());
+        [chunk(b"SIZE", &ints(&size), &[]), chunk(b"XYZI", &xyzi, &[])].concat()
+    }
+
+    fn file(children: &[u8]) -> Vec<u8> {
+        [&b"VOX "[..], &ints(&[150]), &chunk(b"MAIN", &[], children)].concat()
+    }
+
+    fn transform(id: i32, child: i32, t: &str) -> Vec<u8> {
+        let content = [ints(&[id]), dict(&[]), ints(&[child, -1, 0, 1]), dict(&[("_t", t)])].concat();
+        chunk(b"nTRN", &content, &[])
+    }
+
+    fn sorted(mut v: Vec<((i32, i32, i32), u8)>) -> Vec<((i32, i32, i32), u8)> {
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn size_xyzi_rgba() {
+        let mut rgba = Vec::new();
+        for i in 0..256u32 { rgba.extend_from_slice(&[i as u8, 10, 20, 255]); }
+        let bytes = file(&[model([2, 3, 4], &[[0, 0, 0, 1], [1, 2, 3, 7]]), chunk(b"RGBA", &rgba, &[])].concat());
+        let vox = VoxFile::parse(&bytes).unwrap();
+
+        assert_eq!(vox.models.len(), 1);
+        assert_eq!(vox.models[0].size, [2, 3, 4]);
+        // La entrada i de RGBA es el índice de color i + 1
+        assert_eq!(vox.palette[1], [0, 10, 20, 255]);
+        assert_eq!(vox.palette[7], [6, 10, 20, 255]);
+        // Z arriba → Y arriba: (x, y, z) queda en (x, z, -y), desde la esquina mínima
+        assert_eq!(sorted(vox.voxels()), [((0, 0, 2), 1), ((1, 3, 0), 7)]);
+    }
+
+    #[test]
+    fn transforms_place_models() {
+        let graph = [
+            transform(0, 1, "0 0 0"),
+            chunk(b"nGRP", &[ints(&[1]), dict(&[]), ints(&[2, 2, 4])].concat(), &[]),
+            transform(2, 3, "0 0 0"),
+            chunk(b"nSHP", &[ints(&[3]), dict(&[]), ints(&[1, 0]), dict(&[])].concat(), &[]),
+            transform(4, 5, "10 -3 5"),
+            chunk(b"nSHP", &[ints(&[5]), dict(&[]), ints(&[1, 1]), dict(&[])].concat(), &[]),
+        ].concat();
+        let bytes = file(&[model([1, 1, 1], &[[0, 0, 0, 1]]), model([1, 1, 1], &[[0, 0, 0, 2]]), graph].concat());
+        let vox = VoxFile::parse(&bytes).unwrap();
+
+        assert_eq!(vox.models[1].translation, [10, -3, 5]);
+        assert_eq!(sorted(vox.voxels()), [((0, 0, 0), 1), ((10, 5, 3), 2)]);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(VoxFile::parse(b"RIFF").is_err());
+        assert!(VoxFile::parse(&file(&[])).is_err()); // sin modelos
+        let truncated = file(&model([1, 1, 1], &[[0, 0, 0, 1]]));
+        assert!(VoxFile::parse(&truncated[..truncated.len() - 2]).is_err());
+        assert!(VoxFile::parse(&file(&model([1, -1, 1], &[]))).is_err());
+
+        // Traslaciones enormes no deben desbordar al acumularse
+        let far = [transform(0, 1, "2147483647 0 0"), chunk(b"nSHP", &[ints(&[1]), dict(&[]), ints(&[1, 0]), dict(&[])].concat(), &[])].concat();
+        assert!(VoxFile::parse(&file(&[model([1, 1, 1], &[[0, 0, 0, 1]]), far].concat())).is_err());
+    }
+
+    #[test]
+    fn import_keeps_registered_customs() {
+        use crate::core::color::Color;
+        let vox = VoxFile::parse(&file(&model([3, 1, 1], &[[0, 0, 0, 1], [1, 0, 0, 2], [2, 0, 0, 3]]))).unwrap();
+        let marker = |r| Material::with_color(Color::new(r, 0, 0), 0.0, 1.0, 0.0, 0.0, 1.0);
+        let registry = || {
+            let mut reg = MaterialRegistry::new();
+            reg.set(BlockKind::Custom(0), marker(11));
+            reg.set(BlockKind::Custom(2), marker(22));
+            reg
+        };
+        let palette = HashMap::from([(3, BlockKind::Stone)]);
+        let kinds = |world: &World| (0..3).map(|x| world.get(x, 0, 0).unwrap()).collect::<Vec<_>>();
+
+        // Por defecto, después del último Custom registrado
+        let (mut world, mut reg) = (World::new(), registry());
+        let opts = VoxImport { palette: palette.clone(), ..Default::default() };
+        let summary = vox.import(&opts, &mut world, &mut reg).unwrap();
+        assert_eq!(kinds(&world), [BlockKind::Custom(3), BlockKind::Custom(4), BlockKind::Stone]);
+        assert_eq!((summary.blocks, summary.next_custom), (3, 5));
+
+        // Con un primer id explícito, los ya registrados se saltan
+        let (mut world, mut reg) = (World::new(), registry());
+        let opts = VoxImport { palette, first_custom: Some(0), ..Default::default() };
+        let summary = vox.import(&opts, &mut world, &mut reg).unwrap();
+        assert_eq!(kinds(&world), [BlockKind::Custom(1), BlockKind::Custom(3), BlockKind::Stone]);
+        assert_eq!(summary.next_custom, 4);
+        assert_eq!(reg.get(BlockKind::Custom(0)).unwrap().albedo.r, 11);
+        assert_eq!(reg.get(BlockKind::Custom(2)).unwrap().albedo.r, 22);
+    }
+}
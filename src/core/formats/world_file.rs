@@ -0,0 +1,301 @@
+//! Formato binario propio para guardar un `World` (`.diorama`).
+//!
+//! ```text
+//! "DIOR" | versión: u16 LE | zlib(cuerpo)
+//! cuerpo:  nombres: n, n × (largo, utf-8)    tipos de bloque según `BlockKind::name`
+//!          chunks:  n, n × chunk
+//! chunk:   cx, cy, cz: i32 LE
+//!          paleta: n, n × índice a nombres   la entrada 0 (implícita) es el vacío
+//!          corridas: (largo, entrada) ...    hasta cubrir las 4096 celdas en orden Y, Z, X
+//! ```
+//!
+//! Los números sin tipo son varints LEB128 de hasta 64 bits. Un chunk macizo
+//! ocupa una sola corrida, y los nombres hacen que el archivo no dependa del
+//! orden de `BlockKind`.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use crate::core::block::BlockKind;
+use crate::core::chunk::{Chunk, CHUNK_SHIFT, CHUNK_VOLUME};
+use crate::core::world::World;
+
+const MAGIC: &[u8; 4] = b"DIOR";
+pub const VERSION: u16 = 1;
+// Tope del cuerpo descomprimido: un archivo chico no puede pedir gigas
+const MAX_BODY: u64 = 512 << 20;
+
+impl World {
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_bytes()).map_err(|e| format!("No pude escribir {}: {}", path, e))
+    }
+
+    pub fn load(path: &str) -> Result<World, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("No pude leer {}: {}", path, e))?;
+        Self::from_bytes(&bytes).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// El mundo en el formato `.diorama` (sin el historial)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut names: Vec<BlockKind> = Vec::new();
+        let mut name_of: HashMap<BlockKind, usize> = HashMap::new();
+        let mut chunks = Vec::new();
+        for (key, chunk) in self.chunks() {
+            // Paleta sólo con las entradas en uso, en orden de aparición
+            let mut palette: Vec<usize> = Vec::new();
+            let mut runs: Vec<(usize, usize)> = Vec::new();
+            for i in 0..CHUNK_VOLUME {
+                let entry = match chunk.get(i) {
+                    None => 0,
+                    Some(kind) => {
+                        let name = *name_of.entry(kind).or_insert_with(|| { names.push(kind); names.len() - 1 });
+                        match palette.iter().position(|&n| n == name) {
+                            Some(p) => p + 1,
+                            None => { palette.push(name); palette.len() }
+                        }
+                    }
+                };
+                match runs.last_mut() {
+                    Some((len, e)) if *e == entry => *len += 1,
+                    _ => runs.push((1, entry)),
+                }
+            }
+            chunks.push((key, palette, runs));
+        }
+
+        let mut body = Vec::new();
+        put_varint(&mut body, names.len());
+        for kind in &names {
+            let name = kind.name();
+            put_varint(&mut body, name.len());
+            body.extend_from_slice(name.as_bytes());
+        }
+        put_varint(&mut body, chunks.len());
+        for ((cx, cy, cz), palette, runs) in chunks {
+            for c in [cx, cy, cz] { body.extend_from_slice(&c.to_le_bytes()); }
+            put_varint(&mut body, palette.len());
+            for n in palette { put_varint(&mut body, n); }
+            for (len, entry) in runs {
+                put_varint(&mut body, len);
+                put_varint(&mut body, entry);
+            }
+        }
+
+        let mut out = Vec::with_capacity(body.len() / 4 + 16);
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        let mut z = flate2::write::ZlibEncoder::new(out, flate2::Compression::default());
+        z.write_all(&body).expect("comprimir en memoria no falla");
+        z.finish().expect("comprimir en memoria no falla")
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<World, String> {
+        if bytes.len() < 6 || &bytes[..4] != MAGIC {
+            return Err("no es un archivo .diorama".to_string());
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version == 0 || version > VERSION {
+            return Err(format!("versión {} no soportada (la última es {})", version, VERSION));
+        }
+        let body = inflate(&bytes[6..], MAX_BODY)?;
+
+        let mut r = Reader { data: &body, pos: 0 };
+        let mut names = Vec::new();
+        for _ in 0..r.varint()? {
+            let len = r.varint()?;
+            let name = std::str::from_utf8(r.take(len)?).map_err(|_| "nombre de bloque inválido".to_string())?;
+            names.push(BlockKind::from_export_name(name).ok_or_else(|| format!("bloque desconocido \"{}\"", name))?);
+        }
+
+        let mut world = World::new();
+        let limit = i32::MAX >> CHUNK_SHIFT;
+        for _ in 0..r.varint()? {
+            let key = [r.i32()?, r.i32()?, r.i32()?];
+            if key.iter().any(|&c| c < -limit - 1 || c > limit) {
+                return Err(format!("chunk fuera de rango: {:?}", key));
+            }
+            let mut palette = vec![None];
+            for _ in 0..r.varint()? {
+                let n = r.varint()?;
+                palette.push(Some(*names.get(n).ok_or_else(|| format!("índice de nombre {} fuera de rango", n))?));
+            }
+            let mut i = 0;
+            while i < CHUNK_VOLUME {
+                let (len, entry) = (r.varint()?, r.varint()?);
+                if len == 0 || len > CHUNK_VOLUME - i {
+                    return Err(format!("corrida inválida en el chunk {:?}", key));
+                }
+                let kind = *palette.get(entry).ok_or_else(|| format!("entrada de paleta {} fuera de rango", entry))?;
+                if let Some(kind) = kind {
+                    for cell in i..i + len {
+                        let (x, y, z) = Chunk::local(cell);
+                        world.set((key[0] << CHUNK_SHIFT) + x, (key[1] << CHUNK_SHIFT) + y, (key[2] << CHUNK_SHIFT) + z, kind);
+                    }
+                }
+                i += len;
+            }
+        }
+        if r.pos != body.len() {
+            return Err("datos sobrantes al final".to_string());
+        }
+        Ok(world)
+    }
+}
+
+fn inflate(data: &[u8], limit: u64) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    flate2::read::ZlibDecoder::new(data).take(limit + 1).read_to_end(&mut body)
+        .map_err(|e| format!("no pude descomprimir: {}", e))?;
+    if body.len() as u64 > limit {
+        return Err(format!("el contenido descomprimido supera los {} MiB", limit >> 20));
+    }
+    Ok(body)
+}
+
+fn put_varint(out: &mut Vec<u8>, mut n: usize) {
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], String> {
+        let end = self.pos.checked_add(n).filter(|&e| e <= self.data.len()).ok_or("archivo truncado")?;
+        let out = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        let b = self.take(4)?;
+        Ok(i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Inversa de `put_varint`: hasta 64 bits (10 bytes)
+    fn varint(&mut self) -> Result<usize, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.take(1)?[0];
+            let bits = (b & 0x7f) as u64;
+            // El décimo byte sólo aporta el bit 63
+            if shift == 63 && bits > 1 { break; }
+            value |= bits << shift;
+            if b & 0x80 == 0 {
+                return usize::try_from(value).map_err(|_| "varint fuera de rango".to_string());
+            }
+        }
+        Err("varint demasiado largo".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Blocks = Vec<((i32, i32, i32), BlockKind)>;
+
+    fn blocks(w: &World) -> Blocks { w.blocks().collect() }
+
+    fn round_trip(w: &World) -> World {
+        World::from_bytes(&w.to_bytes()).expect("el mundo guardado se vuelve a leer")
+    }
+
+    #[test]
+    fn empty_world() {
+        let w = round_trip(&World::new());
+        assert!(w.is_empty());
+    }
+
+    #[test]
+    fn shapes_across_chunks() {
+        let mut w = World::new();
+        w.fill((-20, -5, -20), (20, -5, 20), BlockKind::Stone);
+        w.sphere((-16, 8, 16), 6.0, BlockKind::Leaves, false);
+        w.cylinder((15, -4, -15), 3.0, 12, BlockKind::Wood, true);
+        w.line((-30, 40, 2), (30, -40, 7), BlockKind::Lava);
+        w.set(0, 0, 0, BlockKind::Air);
+        w.set(1_000_000, -1_000_000, 7, BlockKind::Custom(42));
+
+        let back = round_trip(&w);
+        assert_eq!(back.len(), w.len());
+        assert_eq!(blocks(&back), blocks(&w));
+    }
+
+    #[test]
+    fn many_kinds_in_one_chunk() {
+        // Más de 256 tipos distintos y sin corridas largas
+        let mut w = World::new();
+        for i in 0..4096 {
+            let (x, y, z) = (i & 15, i >> 8, (i >> 4) & 15);
+            if (i * 7919) % 5 != 0 {
+                w.set(x, y, z, BlockKind::Custom(((i * 31) % 600) as u16));
+            }
+        }
+        assert_eq!(blocks(&round_trip(&w)), blocks(&w));
+    }
+
+    #[test]
+    fn file_round_trip_and_size() {
+        let mut w = World::new();
+        w.fill((0, 0, 0), (63, 15, 63), BlockKind::Dirt);
+        let path = std::env::temp_dir().join(format!("world_file_test_{}.diorama", std::process::id()));
+        let path = path.to_str().unwrap();
+        w.save(path).unwrap();
+        let size = std::fs::metadata(path).unwrap().len();
+        let back = World::load(path).unwrap();
+        std::fs::remove_file(path).ok();
+
+        assert_eq!(blocks(&back), blocks(&w));
+        // 16 chunks macizos: una corrida cada uno
+        assert!(size < 200, "{} bytes", size);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let mut w = World::new();
+        w.fill((0, 0, 0), (3, 3, 3), BlockKind::Grass);
+        let bytes = w.to_bytes();
+
+        assert!(World::from_bytes(b"PNG\0\0\0").is_err());
+        assert!(World::from_bytes(&bytes[..bytes.len() - 4]).is_err());
+
+        let mut newer = bytes.clone();
+        newer[4] = 0xff;
+        let err = World::from_bytes(&newer).err().unwrap();
+        assert!(err.contains("versión"), "{}", err);
+    }
+
+    #[test]
+    fn varint_round_trip() {
+        let values = [0, 1, 127, 128, 300, 16_383, 16_384, u32::MAX as usize, u32::MAX as usize + 1, usize::MAX / 3, usize::MAX];
+        let mut bytes = Vec::new();
+        for &v in &values { put_varint(&mut bytes, v); }
+        let mut r = Reader { data: &bytes, pos: 0 };
+        for &v in &values { assert_eq!(r.varint(), Ok(v)); }
+        assert_eq!(r.pos, bytes.len());
+
+        let mut max = Vec::new();
+        put_varint(&mut max, usize::MAX);
+        assert_eq!(max.len(), 10);
+        // 11 bytes, o un décimo byte con más de un bit, no son un varint
+        for bad in [&[0xff; 10][..], &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02], &[0x80]] {
+            assert!(Reader { data: bad, pos: 0 }.varint().is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn inflate_is_bounded() {
+        let mut z = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        z.write_all(&[0; 4096]).unwrap();
+        let data = z.finish().unwrap();
+        assert_eq!(inflate(&data, 4096).unwrap().len(), 4096);
+        assert!(inflate(&data, 4095).is_err());
+        assert!(inflate(&data[..data.len() / 2], 4096).is_err());
+    }
+}
@@ -1,23 +1,70 @@
+use nalgebra_glm as glm;
 use super::color::Color;
+use super::tonemap::ToneMapper;
 
+/// Framebuffer HDR: guarda radiancia lineal en f32 y, tras `resolve`, los
+/// pixeles ya mapeados y codificados en sRGB listos para presentar.
 pub struct Framebuffer {
     pub width: u32,
     pub height: u32,
+    hdr: Vec<glm::Vec3>,
     pixels: Vec<u32>,
 }
 
 impl Framebuffer {
     pub fn new(width: u32, height: u32) -> Self {
-        Self { width, height, pixels: vec![0; (width * height) as usize] }
+        let n = (width * height) as usize;
+        Self { width, height, hdr: vec![glm::Vec3::zeros(); n], pixels: vec![0; n] }
     }
     pub fn clear(&mut self, c: Color) {
+        self.hdr.fill(c.to_linear());
         self.pixels.fill(c.to_u32());
     }
+    /// Escribe un color ya presentable (sRGB); también actualiza el HDR
     pub fn set_pixel(&mut self, x: u32, y: u32, c: Color) {
         if x < self.width && y < self.height {
             let idx = (y * self.width + x) as usize;
+            self.hdr[idx] = c.to_linear();
             self.pixels[idx] = c.to_u32();
         }
     }
+    /// Escribe radiancia lineal; se ve después de `resolve`
+    pub fn set_radiance(&mut self, x: u32, y: u32, v: glm::Vec3) {
+        if x < self.width && y < self.height {
+            let idx = (y * self.width + x) as usize;
+            self.hdr[idx] = v;
+        }
+    }
+    pub fn radiance(&self) -> &[glm::Vec3] { &self.hdr }
     pub fn pixels(&self) -> &[u32] { &self.pixels }
+
+    /// Tone mapping + codificación sRGB del buffer HDR a `pixels`
+    pub fn resolve(&mut self, tone: ToneMapper, exposure: f32) {
+        for (p, v) in self.pixels.iter_mut().zip(&self.hdr) {
+            *p = Color::from_linear(&tone.apply(*v, exposure)).to_u32();
+        }
+    }
+
+    /// Guarda el framebuffer como imagen (formato según la extensión). `.hdr`
+    /// y `.exr` guardan la radiancia sin mapear; el resto, los pixeles resueltos.
+    pub fn save(&self, path: &str) -> image::ImageResult<()> {
+        let ext = std::path::Path::new(path)
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        if ext == "hdr" || ext == "exr" {
+            let data: Vec<f32> = self.hdr.iter().flat_map(|v| [v.x, v.y, v.z]).collect();
+            let img = image::Rgb32FImage::from_raw(self.width, self.height, data)
+                .expect("Tamaño de framebuffer inválido");
+            return image::DynamicImage::ImageRgb32F(img).save(path);
+        }
+
+        let mut rgb = Vec::with_capacity(self.pixels.len() * 3);
+        for &p in &self.pixels {
+            rgb.extend_from_slice(&[(p >> 16) as u8, (p >> 8) as u8, p as u8]);
+        }
+        let img = image::RgbImage::from_raw(self.width, self.height, rgb)
+            .expect("Tamaño de framebuffer inválido");
+        img.save(path)
+    }
 }
@@ -1,5 +1,6 @@
 use nalgebra_glm as glm;
 use crate::core::{intersect::{Intersect, Face}, material::Material};
+use crate::core::accel::aabb::Aabb;
 use super::RayIntersect;
 
 #[derive(Clone)]
@@ -7,11 +8,67 @@ pub struct Cube {
     pub min: glm::Vec3, //esquina minima
     pub max: glm::Vec3, //esquina maxima
     pub material: Material,
+    /// AO por vértice estilo Minecraft (ver `World::bake`): por cara, en el orden
+    /// de `Face`, y por esquina (bit 0 = alto en el primer eje tangente, bit 1 =
+    /// alto en el segundo), en niveles 0..=3
+    pub vertex_ao: Option<[[u8; 4]; 6]>,
 }
 
 impl Cube {
     pub fn new(min: glm::Vec3, max: glm::Vec3, material: Material) -> Self {
-        Self { min, max, material }
+        Self { min, max, material, vertex_ao: None }
+    }
+
+    /// Nivel de AO interpolado en el punto `p` de la cara `face`
+    fn vertex_ao_at(&self, p: &glm::Vec3, face: Face) -> f32 {
+        let Some(ao) = &self.vertex_ao else { return 3.0; };
+        let corners = ao[face as usize];
+        let k = face.axis();
+        let (a, b) = ((k + 1) % 3, (k + 2) % 3);
+        let fa = ((p[a] - self.min[a]) / (self.max[a] - self.min[a])).clamp(0.0, 1.0);
+        let fb = ((p[b] - self.min[b]) / (self.max[b] - self.min[b])).clamp(0.0, 1.0);
+        let c = corners.map(|v| v as f32);
+        let low = c[0] + (c[1] - c[0]) * fa;
+        let high = c[2] + (c[3] - c[2]) * fa;
+        low + (high - low) * fb
+    }
+
+    /// Normal, UV y cara para un punto sobre la superficie
+    fn surface(&self, p: &glm::Vec3) -> (glm::Vec3, (f32, f32), Face) {
+        let eps = 1e-4;
+        let size = self.max - self.min;
+        let local = (p - self.min).component_div(&size); // [0,1]^3
+
+        let face = if (p.x - self.min.x).abs() < eps {
+            Face::NegX
+        } else if (p.x - self.max.x).abs() < eps {
+            Face::PosX
+        } else if (p.y - self.min.y).abs() < eps {
+            Face::NegY
+        } else if (p.y - self.max.y).abs() < eps {
+            Face::PosY
+        } else if (p.z - self.min.z).abs() < eps {
+            Face::NegZ
+        } else {
+            Face::PosZ
+        };
+        let mut n = glm::Vec3::zeros();
+        n[face.axis()] = if face.is_positive() { 1.0 } else { -1.0 };
+        (n, face_uv(face, [local.x, local.y, local.z]), face)
+    }
+}
+
+/// UV de un punto de la cara `face` a partir de su posición local en [0,1]³
+/// (el mismo mapeo que usan el render y los exportadores de malla)
+pub fn face_uv(face: Face, local: [f32; 3]) -> (f32, f32) {
+    let [x, y, z] = local;
+    match face {
+        Face::NegX => (z, 1.0 - y),
+        Face::PosX => (1.0 - z, 1.0 - y),
+        Face::NegY => (x, 1.0 - z),
+        Face::PosY => (x, z),
+        Face::NegZ => (x, 1.0 - y),
+        Face::PosZ => (1.0 - x, 1.0 - y),
     }
 }
 
@@ -39,27 +96,19 @@ impl RayIntersect for Cube {
         if tzmin > tmin { tmin = tzmin; }
         if tzmax < tmax { tmax = tzmax; }
 
-        let t = if tmin > 0.001 { tmin } else if tmax > 0.001 { tmax } else { return Intersect::miss(); };
-        let p = ro + rd * t;
-
-        let eps = 1e-4;
-        let size = self.max - self.min;
-        let local = (p - self.min).component_div(&size); // [0,1]^3
-
-        let (n, uv, face) = if (p.x - self.min.x).abs() < eps {
-            (glm::vec3(-1.0, 0.0, 0.0), (local.z, 1.0 - local.y), Face::NegX)
-        } else if (p.x - self.max.x).abs() < eps {
-            (glm::vec3( 1.0, 0.0, 0.0), (1.0 - local.z, 1.0 - local.y), Face::PosX)
-        } else if (p.y - self.min.y).abs() < eps {
-            (glm::vec3(0.0, -1.0, 0.0), (local.x, 1.0 - local.z), Face::NegY)
-        } else if (p.y - self.max.y).abs() < eps {
-            (glm::vec3(0.0,  1.0, 0.0), (local.x, local.z), Face::PosY)
-        } else if (p.z - self.min.z).abs() < eps {
-            (glm::vec3(0.0, 0.0, -1.0), (local.x, 1.0 - local.y), Face::NegZ)
-        } else {
-            (glm::vec3(0.0, 0.0,  1.0), (1.0 - local.x, 1.0 - local.y), Face::PosZ)
-        };
-
-        Intersect::hit(t, p, n, uv, Some(face), self.material.clone())
+        // Entrada y salida; con alpha cutout, si el texel de entrada es hueco
+        // el rayo sigue hasta la cara de salida (el interior de las hojas)
+        for t in [tmin, tmax] {
+            if t <= 0.001 { continue; }
+            let p = ro + rd * t;
+            let (n, uv, face) = self.surface(&p);
+            if self.material.is_cutout() && self.material.coverage(uv, Some(face)) == 0.0 { continue; }
+            let mut hit = Intersect::hit(t, p, n, uv, Some(face), self.material.clone());
+            hit.vertex_ao = self.vertex_ao_at(&p, face);
+            return hit;
+        }
+        Intersect::miss()
     }
-}
\ No newline at end of file
+
+    fn bounds(&self) -> Aabb { Aabb::new(self.min, self.max) }
+}
@@ -3,7 +3,11 @@ pub mod cube;
 
 use nalgebra_glm as glm;
 use crate::core::intersect::Intersect;
+use crate::core::accel::aabb::Aabb;
 
 pub trait RayIntersect {
     fn ray_intersect(&self, ray_origin: &glm::Vec3, ray_dir: &glm::Vec3) -> Intersect;
+    /// Caja envolvente en mundo (la usa la BVH). Las primitivas sin límites
+    /// (un plano) devuelven `Aabb::infinite()` y se prueban contra todos los rayos
+    fn bounds(&self) -> Aabb;
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 use nalgebra_glm as glm;
 use crate::core::{intersect::Intersect, material::Material};
+use crate::core::accel::aabb::Aabb;
 use super::RayIntersect;
 
 pub struct Sphere { pub center: glm::Vec3, pub radius: f32, pub material: Material }
@@ -19,14 +20,24 @@ impl RayIntersect for Sphere {
         let mut t2 = (-b + sqrt_disc) / (2.0 * a);
         if t1 > t2 { core::mem::swap(&mut t1, &mut t2); }
 
-        let t = if t1 > 0.001 { t1 } else if t2 > 0.001 { t2 } else { return Intersect::miss(); };
-        let point = ro + rd * t;
-        let normal = glm::normalize(&(point - self.center));
+        // Con alpha cutout, un texel hueco deja pasar el rayo a la cara trasera
+        for t in [t1, t2] {
+            if t <= 0.001 { continue; }
+            let point = ro + rd * t;
+            let normal = glm::normalize(&(point - self.center));
 
-        // UV esférico (opcional)
-        let u = 0.5 + normal.z.atan2(normal.x) / (2.0 * std::f32::consts::PI);
-        let v = 0.5 - normal.y.asin() / std::f32::consts::PI;
+            // UV esférico (opcional)
+            let u = 0.5 + normal.z.atan2(normal.x) / (2.0 * std::f32::consts::PI);
+            let v = 0.5 - normal.y.asin() / std::f32::consts::PI;
 
-        Intersect::hit(t, point, normal, (u, v), None, self.material.clone())
+            if self.material.is_cutout() && self.material.coverage((u, v), None) == 0.0 { continue; }
+            return Intersect::hit(t, point, normal, (u, v), None, self.material.clone());
+        }
+        Intersect::miss()
+    }
+
+    fn bounds(&self) -> Aabb {
+        let r = glm::vec3(self.radius, self.radius, self.radius);
+        Aabb::new(self.center - r, self.center + r)
     }
 }
\ No newline at end of file
@@ -0,0 +1,390 @@
+//! Historial de cambios de un `World`: deshacer/rehacer y conjuntos de cambios.
+//!
+//! El historial está apagado por defecto (`World::enable_history`). Con él
+//! encendido, cada `set`/`remove` suelto es un paso y cada operación de edición
+//! (`fill`, `paste`, ...) o `batch` agrupa los suyos en uno solo. `World::diff`
+//! da el `ChangeSet` que lleva de un mundo a otro; se puede aplicar, invertir y
+//! guardar como JSON.
+
+use std::collections::{HashMap, VecDeque};
+use crate::core::block::BlockKind;
+use crate::core::formats::json::Value;
+use crate::core::world::World;
+
+type Pos = (i32, i32, i32);
+
+/// Cambio de una celda; None = vacía
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Change {
+    pub pos: Pos,
+    pub before: Option<BlockKind>,
+    pub after: Option<BlockKind>,
+}
+
+/// Cambios de celdas en el orden en que se hicieron
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChangeSet {
+    changes: Vec<Change>,
+}
+
+impl ChangeSet {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn changes(&self) -> &[Change] { &self.changes }
+
+    pub fn len(&self) -> usize { self.changes.len() }
+
+    pub fn is_empty(&self) -> bool { self.changes.is_empty() }
+
+    /// Agrega un cambio al final
+    pub fn push(&mut self, change: Change) { self.changes.push(change); }
+
+    /// Cambios que deshacen éste (en orden inverso)
+    pub fn inverse(&self) -> Self {
+        let changes = self.changes.iter().rev().map(|c| Change { pos: c.pos, before: c.after, after: c.before }).collect();
+        Self { changes }
+    }
+
+    /// `{ "changes": [[x, y, z, antes, después], ...] }` con los nombres de
+    /// `BlockKind::name` y null para las celdas vacías
+    pub fn to_json(&self) -> Value {
+        let kind = |k: Option<BlockKind>| k.map_or(Value::Null, |k| Value::String(k.name()));
+        let changes = self.changes.iter().map(|c| Value::Array(vec![
+            Value::Number(c.pos.0 as f64), Value::Number(c.pos.1 as f64), Value::Number(c.pos.2 as f64),
+            kind(c.before), kind(c.after),
+        ])).collect();
+        Value::Object(vec![("changes".to_string(), Value::Array(changes))])
+    }
+
+    pub fn from_json(v: &Value) -> Result<Self, String> {
+        let list = v.get("changes").and_then(Value::as_array).ok_or("falta la lista \"changes\"")?;
+        let mut out = Self::new();
+        for (i, c) in list.iter().enumerate() {
+            let bad = || format!("cambio {}: se esperaba [x, y, z, antes, después]", i);
+            let c = c.as_array().filter(|c| c.len() == 5).ok_or_else(bad)?;
+            let coord = |v: &Value| v.as_i32().ok_or_else(bad);
+            let pos = (coord(&c[0])?, coord(&c[1])?, coord(&c[2])?);
+            let kind = |v: &Value| -> Result<Option<BlockKind>, String> {
+                if v.is_null() { return Ok(None); }
+                let name = v.as_str().ok_or_else(bad)?;
+                BlockKind::from_export_name(name).map(Some).ok_or_else(|| format!("cambio {}: bloque desconocido \"{}\"", i, name))
+            };
+            out.push(Change { pos, before: kind(&c[3])?, after: kind(&c[4])? });
+        }
+        Ok(out)
+    }
+}
+
+/// Pasos para deshacer y rehacer de un `World`
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    undo: VecDeque<ChangeSet>, // el más viejo al frente
+    redo: Vec<ChangeSet>,
+    open: Option<Batch>, // lote en curso
+    depth: usize,        // lotes anidados
+    limit: usize,        // pasos que se conservan (0 = sin límite)
+}
+
+/// Lote abierto: una entrada por celda, con el `before` de su primer cambio
+#[derive(Clone, Debug, Default)]
+struct Batch {
+    set: ChangeSet,
+    index: HashMap<Pos, usize>,
+}
+
+impl Batch {
+    fn record(&mut self, change: Change) {
+        match self.index.get(&change.pos) {
+            Some(&i) => self.set.changes[i].after = change.after,
+            None => {
+                self.index.insert(change.pos, self.set.changes.len());
+                self.set.changes.push(change);
+            }
+        }
+    }
+
+    /// Los cambios, sin las celdas que volvieron a quedar como estaban
+    fn finish(mut self) -> ChangeSet {
+        self.set.changes.retain(|c| c.before != c.after);
+        self.set
+    }
+}
+
+impl History {
+    /// Pasos que se pueden deshacer
+    pub fn undo_len(&self) -> usize { self.undo.len() }
+
+    /// Pasos deshechos que se pueden rehacer
+    pub fn redo_len(&self) -> usize { self.redo.len() }
+
+    /// Pasos que se conservan (0 = sin límite)
+    pub fn limit(&self) -> usize { self.limit }
+
+    fn record(&mut self, change: Change) {
+        match &mut self.open {
+            Some(batch) => batch.record(change),
+            None => self.push_step(ChangeSet { changes: vec![change] }),
+        }
+    }
+
+    fn push_step(&mut self, step: ChangeSet) {
+        if step.is_empty() { return; }
+        self.redo.clear();
+        self.undo.push_back(step);
+        if self.limit > 0 && self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+}
+
+impl World {
+    /// Empieza a registrar cambios, conservando hasta `limit` pasos (0 = todos)
+    pub fn enable_history(&mut self, limit: usize) {
+        self.history = Some(History { limit, ..History::default() });
+    }
+
+    /// Deja de registrar y descarta los pasos guardados
+    pub fn disable_history(&mut self) { self.history = None; }
+
+    pub fn history(&self) -> Option<&History> { self.history.as_ref() }
+
+    pub fn can_undo(&self) -> bool { self.history.as_ref().is_some_and(|h| !h.undo.is_empty()) }
+
+    pub fn can_redo(&self) -> bool { self.history.as_ref().is_some_and(|h| !h.redo.is_empty()) }
+
+    /// Abre un lote: hasta el `end_batch` correspondiente, todos los cambios
+    /// forman un único paso. Los lotes se pueden anidar.
+    pub fn begin_batch(&mut self) {
+        if let Some(h) = &mut self.history {
+            h.depth += 1;
+            h.open.get_or_insert_with(Batch::default);
+        }
+    }
+
+    pub fn end_batch(&mut self) {
+        if let Some(h) = &mut self.history {
+            if h.depth == 0 { return; }
+            h.depth -= 1;
+            if h.depth == 0 && let Some(batch) = h.open.take() {
+                h.push_step(batch.finish());
+            }
+        }
+    }
+
+    /// Ejecuta `f` como un solo paso del historial
+    pub fn batch<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.begin_batch();
+        let r = f(self);
+        self.end_batch();
+        r
+    }
+
+    /// Deshace el último paso; false si no había
+    pub fn undo(&mut self) -> bool {
+        let Some(step) = self.history.as_mut().filter(|h| h.depth == 0).and_then(|h| h.undo.pop_back()) else { return false };
+        self.replay(&step.inverse());
+        if let Some(h) = &mut self.history { h.redo.push(step); }
+        true
+    }
+
+    /// Rehace el último paso deshecho; false si no había
+    pub fn redo(&mut self) -> bool {
+        let Some(step) = self.history.as_mut().filter(|h| h.depth == 0).and_then(|h| h.redo.pop()) else { return false };
+        self.replay(&step);
+        if let Some(h) = &mut self.history { h.undo.push_back(step); }
+        true
+    }
+
+    /// Cambios que convierten este mundo en `other`: primero las celdas de este
+    /// mundo que cambian o se vacían, después las que sólo ocupa `other`
+    /// (cada grupo en orden espacial)
+    pub fn diff(&self, other: &World) -> ChangeSet {
+        let mut out = ChangeSet::new();
+        for (p, k) in self.blocks() {
+            let after = other.get(p.0, p.1, p.2);
+            if after != Some(k) { out.push(Change { pos: p, before: Some(k), after }); }
+        }
+        for (p, k) in other.blocks() {
+            if self.get(p.0, p.1, p.2).is_none() { out.push(Change { pos: p, before: None, after: Some(k) }); }
+        }
+        out
+    }
+
+    /// Escribe el `after` de cada cambio (sin mirar `before`), como un paso del historial
+    pub fn apply(&mut self, changes: &ChangeSet) {
+        self.batch(|w| {
+            for c in &changes.changes {
+                let (x, y, z) = c.pos;
+                match c.after {
+                    Some(k) => w.set(x, y, z, k),
+                    None => { w.remove(x, y, z); }
+                }
+            }
+        });
+    }
+
+    pub(crate) fn record(&mut self, pos: Pos, before: Option<BlockKind>, after: Option<BlockKind>) {
+        if before == after { return; }
+        if let Some(h) = &mut self.history { h.record(Change { pos, before, after }); }
+    }
+
+    /// Aplica los cambios sin registrarlos
+    fn replay(&mut self, changes: &ChangeSet) {
+        let history = self.history.take();
+        self.apply(changes);
+        self.history = history;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::formats::json;
+
+    fn snapshot(w: &World) -> Vec<(Pos, BlockKind)> { w.blocks().collect() }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut w = World::new();
+        w.set(0, 0, 0, BlockKind::Stone); // antes de encender el historial: no cuenta
+        w.enable_history(0);
+        assert!(!w.can_undo() && !w.undo());
+
+        let s0 = snapshot(&w);
+        w.set(1, 0, 0, BlockKind::Dirt);
+        let s1 = snapshot(&w);
+        w.fill((-2, -2, -2), (2, -1, 2), BlockKind::Grass);
+        let s2 = snapshot(&w);
+        w.remove(0, 0, 0);
+        let s3 = snapshot(&w);
+        w.set(1, 0, 0, BlockKind::Dirt); // sin cambios: no es un paso
+        assert_eq!(w.history().map(History::undo_len), Some(3));
+
+        assert!(w.undo());
+        assert_eq!(snapshot(&w), s2);
+        assert!(w.undo());
+        assert_eq!(snapshot(&w), s1);
+        assert!(w.undo());
+        assert_eq!(snapshot(&w), s0);
+        assert!(!w.undo());
+        assert_eq!(w.history().map(|h| (h.undo_len(), h.redo_len())), Some((0, 3)));
+
+        assert!(w.redo());
+        assert!(w.redo());
+        assert_eq!(snapshot(&w), s2);
+        // Un cambio nuevo descarta lo que quedaba por rehacer
+        w.set(5, 5, 5, BlockKind::Lava);
+        assert!(!w.can_redo() && !w.redo());
+        assert!(w.undo());
+        assert_eq!(snapshot(&w), s2);
+        assert_ne!(s2, s3);
+    }
+
+    #[test]
+    fn limit_drops_oldest_steps() {
+        let mut w = World::new();
+        w.enable_history(3);
+        for x in 0..5 { w.set(x, 0, 0, BlockKind::Stone); }
+        assert_eq!(w.history().map(|h| (h.undo_len(), h.limit())), Some((3, 3)));
+        while w.undo() {}
+        assert_eq!(snapshot(&w), [((0, 0, 0), BlockKind::Stone), ((1, 0, 0), BlockKind::Stone)]);
+
+        w.disable_history();
+        assert!(w.history().is_none() && !w.can_redo());
+    }
+
+    #[test]
+    fn nested_batches_are_one_step() {
+        let mut w = World::new();
+        w.enable_history(0);
+        w.begin_batch();
+        w.set(0, 0, 0, BlockKind::Stone);
+        w.batch(|w| {
+            w.fill((0, 1, 0), (1, 1, 1), BlockKind::Dirt);
+            w.set(9, 9, 9, BlockKind::Water);
+        });
+        // Dentro de un lote abierto no se puede deshacer
+        assert!(!w.undo());
+        w.remove(9, 9, 9);
+        w.end_batch();
+        w.end_batch(); // sin lote abierto no hace nada
+
+        let h = w.history().unwrap();
+        assert_eq!(h.undo_len(), 1);
+        // La celda que volvió a quedar vacía no forma parte del paso
+        assert_eq!(h.undo[0].len(), 5);
+        assert!(h.undo[0].changes().iter().all(|c| c.pos != (9, 9, 9)));
+
+        assert!(w.undo());
+        assert!(w.is_empty());
+        assert!(w.redo());
+        assert_eq!(w.len(), 5);
+    }
+
+    #[test]
+    fn batch_keeps_first_before_and_last_after() {
+        let mut w = World::new();
+        w.set(0, 0, 0, BlockKind::Stone);
+        w.enable_history(0);
+        w.batch(|w| {
+            w.set(0, 0, 0, BlockKind::Dirt);
+            w.set(0, 0, 0, BlockKind::Lava);
+            w.set(1, 0, 0, BlockKind::Dirt);
+            w.set(1, 0, 0, BlockKind::Water);
+            // Vuelve a Stone: la celda no cambió
+            w.set(2, 0, 0, BlockKind::Dirt);
+            w.remove(2, 0, 0);
+        });
+        let step = &w.history().unwrap().undo[0];
+        assert_eq!(step.changes(), [
+            Change { pos: (0, 0, 0), before: Some(BlockKind::Stone), after: Some(BlockKind::Lava) },
+            Change { pos: (1, 0, 0), before: None, after: Some(BlockKind::Water) },
+        ]);
+    }
+
+    #[test]
+    fn diff_apply_and_inverse() {
+        let mut a = World::new();
+        a.fill((0, 0, 0), (3, 1, 3), BlockKind::Stone);
+        a.set(-20, 4, 7, BlockKind::Leaves);
+        let mut b = World::new();
+        b.fill((2, 0, 2), (5, 2, 5), BlockKind::Stone);
+        b.replace((2, 0, 2), (3, 1, 3), BlockKind::Stone, BlockKind::Dirt);
+        b.set(-20, 4, 7, BlockKind::Leaves);
+
+        let d = a.diff(&b);
+        assert!(a.diff(&a).is_empty());
+        assert!(d.changes().iter().all(|c| c.before != c.after && a.get(c.pos.0, c.pos.1, c.pos.2) == c.before));
+
+        let mut c = World::new();
+        c.fill((0, 0, 0), (3, 1, 3), BlockKind::Stone);
+        c.set(-20, 4, 7, BlockKind::Leaves);
+        c.enable_history(0);
+        c.apply(&d);
+        assert_eq!(snapshot(&c), snapshot(&b));
+        assert_eq!(c.history().map(History::undo_len), Some(1));
+        c.apply(&d.inverse());
+        assert_eq!(snapshot(&c), snapshot(&a));
+        assert_eq!(d.inverse().inverse(), d);
+        assert_eq!(b.diff(&a).len(), d.len());
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut set = ChangeSet::new();
+        set.push(Change { pos: (-3, 70, 12), before: None, after: Some(BlockKind::Stone) });
+        set.push(Change { pos: (0, 0, -1), before: Some(BlockKind::Custom(12)), after: None });
+        set.push(Change { pos: (i32::MIN, i32::MAX, 5), before: Some(BlockKind::Grass), after: Some(BlockKind::Water) });
+
+        let text = set.to_json().to_string();
+        assert!(text.starts_with(r#"{"changes":[[-3,70,12,null,"stone"]"#), "{}", text);
+        let back = ChangeSet::from_json(&json::parse(&text).unwrap()).unwrap();
+        assert_eq!(back, set);
+
+        for bad in [r#"{}"#, r#"{"changes":[[1,2,3,null]]}"#, r#"{"changes":[[1,2,3.5,null,null]]}"#,
+                    r#"{"changes":[[1,2,3,"no_existe",null]]}"#] {
+            assert!(ChangeSet::from_json(&json::parse(bad).unwrap()).is_err(), "{}", bad);
+        }
+    }
+}
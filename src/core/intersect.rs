@@ -1,9 +1,21 @@
 use nalgebra_glm as glm;
 use super::material::Material;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Face { NegX, PosX, NegY, PosY, NegZ, PosZ }
 
+impl Face {
+    pub const ALL: [Face; 6] = [Face::NegX, Face::PosX, Face::NegY, Face::PosY, Face::NegZ, Face::PosZ];
+
+    /// Eje de la normal (0 = X, 1 = Y, 2 = Z)
+    #[inline]
+    pub fn axis(self) -> usize { self as usize / 2 }
+
+    /// ¿La normal apunta hacia +eje?
+    #[inline]
+    pub fn is_positive(self) -> bool { self as usize % 2 == 1 }
+}
+
 #[derive(Clone)]
 pub struct Intersect {
     pub distance: f32,
@@ -13,11 +25,12 @@ pub struct Intersect {
     pub uv: (f32, f32),
     pub face: Option<Face>,
     pub material: Material,
+    pub vertex_ao: f32, // AO por vértice interpolada, 0..=3 (3 = sin oclusión)
 }
 
 impl Intersect {
     pub fn hit(distance: f32, point: glm::Vec3, normal: glm::Vec3, uv: (f32, f32), face: Option<Face>, material: Material) -> Self {
-        Self { distance, is_intersecting: true, point, normal, uv, face, material }
+        Self { distance, is_intersecting: true, point, normal, uv, face, material, vertex_ao: 3.0 }
     }
     pub fn miss() -> Self {
         Self {
@@ -28,6 +41,7 @@ impl Intersect {
             uv: (0.0, 0.0),
             face: None,
             material: Material::default_black(),
+            vertex_ao: 3.0,
         }
     }
 }
\ No newline at end of file
@@ -1,15 +1,194 @@
 use nalgebra_glm as glm;
 use crate::core::color::Color;
 
+/// Atenuación con la distancia: 1 / (constant + linear·d + quadratic·d²)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Attenuation {
+    /// Sin caída (el comportamiento original de `Light::point`)
+    pub const NONE: Self = Self { constant: 1.0, linear: 0.0, quadratic: 0.0 };
+    /// Inverso del cuadrado, suavizado para no explotar cerca de la luz
+    pub const INVERSE_SQUARE: Self = Self { constant: 1.0, linear: 0.0, quadratic: 1.0 };
+
+    #[inline]
+    pub fn factor(&self, d: f32) -> f32 {
+        1.0 / (self.constant + self.linear * d + self.quadratic * d * d).max(1e-6)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum LightKind {
+    /// Luz puntual omnidireccional
+    Point { position: glm::Vec3, attenuation: Attenuation },
+    /// Luz en el infinito (sol): rayos paralelos en `direction` (hacia donde viaja la luz)
+    Directional { direction: glm::Vec3 },
+    /// Foco: cono alrededor de `direction`, pleno hasta `inner_angle` y con caída
+    /// suave hasta `outer_angle` (semiángulos en radianes)
+    Spot {
+        position: glm::Vec3,
+        direction: glm::Vec3,
+        inner_angle: f32,
+        outer_angle: f32,
+        attenuation: Attenuation,
+    },
+    /// Rectángulo emisor de un solo lado (el de `edge_u × edge_v`)
+    Rect { center: glm::Vec3, edge_u: glm::Vec3, edge_v: glm::Vec3, attenuation: Attenuation },
+    /// Disco emisor de un solo lado (el de `normal`)
+    Disc { center: glm::Vec3, normal: glm::Vec3, radius: f32, attenuation: Attenuation },
+    /// Esfera emisora en todas direcciones
+    Sphere { center: glm::Vec3, radius: f32, attenuation: Attenuation },
+}
+
 #[derive(Clone, Copy)]
 pub struct Light {
-    pub position: glm::Vec3, // posición en el mundo
+    pub kind: LightKind,
     pub color: Color,
     pub intensity: f32,
 }
 
+/// Lo que ve un punto de una luz (o de una muestra de una luz de área):
+/// dirección hacia ella, distancia (infinita para la direccional) y radiancia
+/// incidente ya atenuada, sin sombras
+pub struct LightSample {
+    pub dir: glm::Vec3,
+    pub dist: f32,
+    pub radiance: glm::Vec3,
+}
+
 impl Light {
     pub fn point(position: glm::Vec3, color: Color, intensity: f32) -> Self {
-        Self { position, color, intensity }
+        Self { kind: LightKind::Point { position, attenuation: Attenuation::NONE }, color, intensity }
+    }
+
+    pub fn directional(direction: glm::Vec3, color: Color, intensity: f32) -> Self {
+        Self { kind: LightKind::Directional { direction: glm::normalize(&direction) }, color, intensity }
     }
-}
\ No newline at end of file
+
+    pub fn spot(position: glm::Vec3, direction: glm::Vec3, inner_angle: f32, outer_angle: f32, color: Color, intensity: f32) -> Self {
+        Self {
+            kind: LightKind::Spot {
+                position,
+                direction: glm::normalize(&direction),
+                inner_angle: inner_angle.min(outer_angle),
+                outer_angle,
+                attenuation: Attenuation::NONE,
+            },
+            color,
+            intensity,
+        }
+    }
+
+    /// Rectángulo centrado en `center` con lados `edge_u` y `edge_v`
+    pub fn rect(center: glm::Vec3, edge_u: glm::Vec3, edge_v: glm::Vec3, color: Color, intensity: f32) -> Self {
+        Self { kind: LightKind::Rect { center, edge_u, edge_v, attenuation: Attenuation::NONE }, color, intensity }
+    }
+
+    pub fn disc(center: glm::Vec3, normal: glm::Vec3, radius: f32, color: Color, intensity: f32) -> Self {
+        let normal = glm::normalize(&normal);
+        Self { kind: LightKind::Disc { center, normal, radius, attenuation: Attenuation::NONE }, color, intensity }
+    }
+
+    pub fn sphere(center: glm::Vec3, radius: f32, color: Color, intensity: f32) -> Self {
+        Self { kind: LightKind::Sphere { center, radius, attenuation: Attenuation::NONE }, color, intensity }
+    }
+
+    /// Cambia la atenuación (la direccional no tiene)
+    pub fn with_attenuation(mut self, att: Attenuation) -> Self {
+        match &mut self.kind {
+            LightKind::Point { attenuation, .. }
+            | LightKind::Spot { attenuation, .. }
+            | LightKind::Rect { attenuation, .. }
+            | LightKind::Disc { attenuation, .. }
+            | LightKind::Sphere { attenuation, .. } => *attenuation = att,
+            LightKind::Directional { .. } => {}
+        }
+        self
+    }
+
+    /// ¿Tiene superficie? (necesita varias muestras para sombras suaves)
+    pub fn is_area(&self) -> bool {
+        matches!(self.kind, LightKind::Rect { .. } | LightKind::Disc { .. } | LightKind::Sphere { .. })
+    }
+
+    /// Iluminación que llega a `p` (None si `p` queda fuera del cono, detrás de la
+    /// luz o en su origen). En luces de área `u` ∈ [0,1)² elige el punto de la
+    /// superficie; promediar muestras bien repartidas da la penumbra. Cada punto
+    /// se comporta como una luz puntual con la misma intensidad (y coseno de
+    /// emisión en rectángulos y discos).
+    pub fn sample(&self, p: glm::Vec3, u: [f32; 2]) -> Option<LightSample> {
+        let base = self.color.to_linear() * self.intensity;
+        // Punto de la superficie hacia el que va la muestra
+        let toward = |q: glm::Vec3, emit_normal: Option<glm::Vec3>, attenuation: Attenuation| {
+            let to = q - p;
+            let dist = glm::length(&to);
+            if dist <= 0.0 { return None; }
+            let dir = to / dist;
+            let cos_e = match emit_normal {
+                Some(nl) => glm::dot(&nl, &-dir),
+                None => 1.0,
+            };
+            if cos_e <= 0.0 { return None; }
+            Some(LightSample { dir, dist, radiance: base * (cos_e * attenuation.factor(dist)) })
+        };
+        match self.kind {
+            LightKind::Directional { direction } => {
+                Some(LightSample { dir: -direction, dist: f32::INFINITY, radiance: base })
+            }
+            LightKind::Point { position, attenuation } => {
+                let to = position - p;
+                let dist = glm::length(&to);
+                if dist <= 0.0 { return None; }
+                Some(LightSample { dir: to / dist, dist, radiance: base * attenuation.factor(dist) })
+            }
+            LightKind::Spot { position, direction, inner_angle, outer_angle, attenuation } => {
+                let to = position - p;
+                let dist = glm::length(&to);
+                if dist <= 0.0 { return None; }
+                let dir = to / dist;
+                // Caída suave (smoothstep) entre los cosenos de los dos ángulos
+                let cos = glm::dot(&-dir, &direction);
+                let (cos_in, cos_out) = (inner_angle.cos(), outer_angle.cos());
+                if cos <= cos_out { return None; }
+                let cone = if cos >= cos_in {
+                    1.0
+                } else {
+                    let x = (cos - cos_out) / (cos_in - cos_out).max(1e-6);
+                    x * x * (3.0 - 2.0 * x)
+                };
+                Some(LightSample { dir, dist, radiance: base * (cone * attenuation.factor(dist)) })
+            }
+            LightKind::Rect { center, edge_u, edge_v, attenuation } => {
+                let q = center + edge_u * (u[0] - 0.5) + edge_v * (u[1] - 0.5);
+                toward(q, Some(glm::normalize(&glm::cross(&edge_u, &edge_v))), attenuation)
+            }
+            LightKind::Disc { center, normal, radius, attenuation } => {
+                // Mapeo polar con área uniforme
+                let (t, b) = tangent_frame(normal);
+                let r = radius * u[0].sqrt();
+                let phi = 2.0 * std::f32::consts::PI * u[1];
+                toward(center + (t * phi.cos() + b * phi.sin()) * r, Some(normal), attenuation)
+            }
+            LightKind::Sphere { center, radius, attenuation } => {
+                // Punto uniforme en la esfera, llevado a la mitad que mira a `p`
+                let z = 1.0 - 2.0 * u[0];
+                let s = (1.0 - z * z).max(0.0).sqrt();
+                let phi = 2.0 * std::f32::consts::PI * u[1];
+                let mut d = glm::vec3(s * phi.cos(), s * phi.sin(), z);
+                if glm::dot(&d, &(p - center)) < 0.0 { d = -d; }
+                toward(center + d * radius, None, attenuation)
+            }
+        }
+    }
+}
+
+/// Dos vectores unitarios perpendiculares a `n` (y entre sí)
+fn tangent_frame(n: glm::Vec3) -> (glm::Vec3, glm::Vec3) {
+    let helper = if n.x.abs() > 0.9 { glm::vec3(0.0, 1.0, 0.0) } else { glm::vec3(1.0, 0.0, 0.0) };
+    let t = glm::normalize(&glm::cross(&helper, &n));
+    (t, glm::cross(&n, &t))
+}
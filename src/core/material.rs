@@ -1,8 +1,10 @@
+use nalgebra_glm as glm;
 use super::color::Color;
 use super::texture::Texture;
 use super::intersect::Face;
 
 #[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum AlbedoTex {
     None,
     Single(Texture),
@@ -13,6 +15,47 @@ pub enum AlbedoTex {
     },
 }
 
+impl AlbedoTex {
+    /// Textura que corresponde a la cara (None si no hay textura)
+    pub fn for_face(&self, face: Option<Face>) -> Option<&Texture> {
+        match self {
+            AlbedoTex::None => None,
+            AlbedoTex::Single(tex) => Some(tex),
+            AlbedoTex::Cube { nx, px, ny, py, nz, pz } => {
+                let f = face.unwrap_or(Face::PosZ);
+                Some(match f {
+                    Face::NegX => nx, Face::PosX => px,
+                    Face::NegY => ny, Face::PosY => py,
+                    Face::NegZ => nz, Face::PosZ => pz,
+                })
+            }
+        }
+    }
+
+    /// Color promedio lineal de todas las texturas (blanco si no hay)
+    pub fn average(&self) -> glm::Vec3 {
+        match self {
+            AlbedoTex::None => glm::vec3(1.0, 1.0, 1.0),
+            AlbedoTex::Single(tex) => tex.average_linear(),
+            AlbedoTex::Cube { nx, px, ny, py, nz, pz } => {
+                [nx, px, ny, py, nz, pz].iter().fold(glm::Vec3::zeros(), |acc, t| acc + t.average_linear()) / 6.0
+            }
+        }
+    }
+}
+
+/// Cómo se usa el alfa de la textura de albedo
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlphaMode {
+    /// Se ignora el alfa
+    Opaque,
+    /// Texels con alfa < threshold no existen (hojas, flores): los rayos, incluidos
+    /// los de sombra, pasan a través
+    Cutout { threshold: f32 },
+    /// El alfa mezcla la superficie con lo que hay detrás (vidrio teñido)
+    Blend,
+}
+
 #[derive(Clone)]
 pub struct Material {
     pub albedo: Color,
@@ -22,43 +65,122 @@ pub struct Material {
     pub transparency: f32,
     pub ior: f32, //indice de refraccion
     pub albedo_tex: AlbedoTex, //rexturas
+    pub emission: Color, // tinte de la emisión
+    pub emission_strength: f32, // 0 = no emite
+    pub emission_tex: AlbedoTex, // máscara/color de emisión por cara
+    pub alpha_mode: AlphaMode,
 }
 
 impl Material {
     // una sola textura
     pub fn with_texture(tex: Texture, specular: f32, shininess: f32, reflectivity: f32, transparency: f32, ior: f32) -> Self {
-        Self { albedo: Color::new(255,255,255), specular, shininess, reflectivity, transparency, ior, albedo_tex: AlbedoTex::Single(tex) }
+        Self {
+            albedo: Color::new(255,255,255), specular, shininess, reflectivity, transparency, ior, albedo_tex: AlbedoTex::Single(tex),
+            emission: Color::new(0,0,0), emission_strength: 0.0, emission_tex: AlbedoTex::None,
+            alpha_mode: AlphaMode::Opaque,
+        }
+    }
+
+    // color sólido, sin textura
+    pub fn with_color(albedo: Color, specular: f32, shininess: f32, reflectivity: f32, transparency: f32, ior: f32) -> Self {
+        Self {
+            albedo, specular, shininess, reflectivity, transparency, ior, albedo_tex: AlbedoTex::None,
+            emission: Color::new(0,0,0), emission_strength: 0.0, emission_tex: AlbedoTex::None,
+            alpha_mode: AlphaMode::Opaque,
+        }
     }
 
     // mas de una textura para cubo
+    #[allow(clippy::too_many_arguments)]
     pub fn with_cube_textures(nx: Texture, px: Texture, ny: Texture, py: Texture, nz: Texture, pz: Texture,
         specular: f32, shininess: f32, reflectivity: f32, transparency: f32, ior: f32) -> Self {
         Self {
             albedo: Color::new(255,255,255),
             specular, shininess, reflectivity, transparency, ior,
-            albedo_tex: AlbedoTex::Cube { nx, px, ny, py, nz, pz }
+            albedo_tex: AlbedoTex::Cube { nx, px, ny, py, nz, pz },
+            emission: Color::new(0,0,0), emission_strength: 0.0, emission_tex: AlbedoTex::None,
+            alpha_mode: AlphaMode::Opaque,
         }
     }
 
     // miss
     pub fn default_black() -> Self {
-        Self { albedo: Color::new(0,0,0), specular: 0.0, shininess: 1.0, reflectivity: 0.0, transparency: 0.0, ior: 1.0, albedo_tex: AlbedoTex::None }
+        Self {
+            albedo: Color::new(0,0,0), specular: 0.0, shininess: 1.0, reflectivity: 0.0, transparency: 0.0, ior: 1.0, albedo_tex: AlbedoTex::None,
+            emission: Color::new(0,0,0), emission_strength: 0.0, emission_tex: AlbedoTex::None,
+            alpha_mode: AlphaMode::Opaque,
+        }
     }
 
-    /// Devuelve el color base según UV (y cara si aplica)
-    pub fn sample_albedo(&self, uv: (f32,f32), face: Option<Face>) -> Color {
-        match &self.albedo_tex {
-            AlbedoTex::None => self.albedo,
-            AlbedoTex::Single(tex) => tex.sample(uv),
-            AlbedoTex::Cube { nx, px, ny, py, nz, pz } => {
-                let f = face.unwrap_or(Face::PosZ);
-                let t = match f {
-                    Face::NegX => nx, Face::PosX => px,
-                    Face::NegY => ny, Face::PosY => py,
-                    Face::NegZ => nz, Face::PosZ => pz,
-                };
-                t.sample(uv)
-            }
+    /// Emisión uniforme de color `color` (strength puede pasar de 1)
+    pub fn with_emission(mut self, color: Color, strength: f32) -> Self {
+        self.emission = color;
+        self.emission_strength = strength;
+        self.emission_tex = AlbedoTex::None;
+        self
+    }
+
+    /// Emisión tomada de texturas (una o una por cara), p.ej. la misma de albedo
+    pub fn with_emission_map(mut self, map: AlbedoTex, strength: f32) -> Self {
+        self.emission = Color::new(255,255,255);
+        self.emission_strength = strength;
+        self.emission_tex = map;
+        self
+    }
+
+    /// Usa el alfa de las texturas de albedo según `mode`
+    pub fn with_alpha(mut self, mode: AlphaMode) -> Self {
+        self.alpha_mode = mode;
+        self
+    }
+
+    /// Cobertura en el punto: 0 = hueco, 1 = sólido (fraccional sólo en `Blend`)
+    pub fn coverage(&self, uv: (f32,f32), face: Option<Face>) -> f32 {
+        if self.alpha_mode == AlphaMode::Opaque { return 1.0; }
+        let alpha = self.albedo_tex.for_face(face).map_or(1.0, |t| t.alpha(uv));
+        match self.alpha_mode {
+            AlphaMode::Cutout { threshold } => if alpha >= threshold { 1.0 } else { 0.0 },
+            _ => alpha,
+        }
+    }
+
+    /// ¿Hay que hacer la prueba de alfa al intersectar? (sólo `Cutout`)
+    #[inline]
+    pub fn is_cutout(&self) -> bool {
+        matches!(self.alpha_mode, AlphaMode::Cutout { .. })
+    }
+
+    /// ¿Tapa por completo lo que tiene detrás? (ni transparente ni con alpha)
+    pub fn is_opaque(&self) -> bool {
+        self.transparency <= 0.0 && self.alpha_mode == AlphaMode::Opaque
+    }
+
+    pub fn is_emissive(&self) -> bool {
+        self.emission_strength > 0.0 && (self.emission.r | self.emission.g | self.emission.b) > 0
+    }
+
+    /// Devuelve el color base (RGB lineal) según UV (y cara si aplica).
+    /// `footprint` es el tamaño del pixel sobre la superficie (elige el mip).
+    pub fn sample_albedo(&self, uv: (f32,f32), face: Option<Face>, footprint: f32) -> glm::Vec3 {
+        match self.albedo_tex.for_face(face) {
+            Some(tex) => tex.sample_lod(uv, tex.lod_for_footprint(footprint)),
+            None => self.albedo.to_linear(),
         }
     }
-}
\ No newline at end of file
+
+    /// Radiancia emitida en el punto (0 si el material no emite)
+    pub fn sample_emission(&self, uv: (f32,f32), face: Option<Face>) -> glm::Vec3 {
+        if !self.is_emissive() { return glm::Vec3::zeros(); }
+        let base = self.emission.to_linear() * self.emission_strength;
+        match self.emission_tex.for_face(face) {
+            Some(tex) => base.component_mul(&tex.sample_linear(uv)),
+            None => base,
+        }
+    }
+
+    /// Radiancia emitida promedio (para tratar el objeto como luz)
+    pub fn average_emission(&self) -> glm::Vec3 {
+        if !self.is_emissive() { return glm::Vec3::zeros(); }
+        (self.emission.to_linear() * self.emission_strength).component_mul(&self.emission_tex.average())
+    }
+}
@@ -6,8 +6,21 @@ pub struct MaterialRegistry {
     map: HashMap<BlockKind, Material>,
 }
 
+impl Default for MaterialRegistry {
+    fn default() -> Self { Self::new() }
+}
+
 impl MaterialRegistry {
     pub fn new() -> Self { Self { map: HashMap::new() } }
     pub fn set(&mut self, kind: BlockKind, mat: Material) { self.map.insert(kind, mat); }
     pub fn get(&self, kind: BlockKind) -> Option<&Material> { self.map.get(&kind) }
+
+    /// Primer `Custom(n)` por encima de todos los registrados
+    pub fn next_custom(&self) -> Option<u16> {
+        let last = self.map.keys().filter_map(|k| match k { BlockKind::Custom(n) => Some(*n), _ => None }).max();
+        match last {
+            None => Some(0),
+            Some(n) => n.checked_add(1),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,192 @@
+//! Malla de triángulos de un `World` con sólo las caras visibles, para
+//! exportar (OBJ, glTF).
+//!
+//! Una cara se descarta cuando el vecino la tapa: un bloque opaco, o uno del
+//! mismo tipo si el material es transparente (el agua contra agua no deja
+//! pared interna). Los recortes por alfa (hojas) conservan sus caras porque se
+//! ven a través. Las UV siguen el mapeo de `Cube`, así que cada bloque usa la
+//! textura entera.
+
+use std::collections::HashMap;
+use crate::core::block::BlockKind;
+use crate::core::geometry::cube::face_uv;
+use crate::core::intersect::Face;
+use crate::core::material::AlbedoTex;
+use crate::core::material_registry::MaterialRegistry;
+use crate::core::world::World;
+
+/// Triángulos que comparten material
+pub struct MeshGroup {
+    pub kind: BlockKind,
+    /// Cara del bloque cuando el material tiene una textura por cara (pasto);
+    /// None si todas las caras usan la misma
+    pub face: Option<Face>,
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>, // v = 1 arriba de la imagen, como en OBJ
+    pub indices: Vec<u32>,
+}
+
+impl MeshGroup {
+    /// Nombre del material (`grass_py` para las caras con textura propia)
+    pub fn material_name(&self) -> String {
+        match self.face {
+            None => self.kind.name(),
+            Some(f) => format!("{}_{}", self.kind.name(), ["nx", "px", "ny", "py", "nz", "pz"][f as usize]),
+        }
+    }
+}
+
+pub struct Mesh {
+    pub groups: Vec<MeshGroup>,
+}
+
+impl Mesh {
+    pub fn triangles(&self) -> usize {
+        self.groups.iter().map(|g| g.indices.len() / 3).sum()
+    }
+
+    /// (mínimo, máximo) de todas las posiciones; None si la malla está vacía
+    pub fn bounds(&self) -> Option<([f32; 3], [f32; 3])> {
+        let mut it = self.groups.iter().flat_map(|g| &g.positions);
+        let first = *it.next()?;
+        Some(it.fold((first, first), |(lo, hi), p| {
+            ([0, 1, 2].map(|k| lo[k].min(p[k])), [0, 1, 2].map(|k| hi[k].max(p[k])))
+        }))
+    }
+}
+
+impl World {
+    /// Caras visibles de los bloques con material en `reg`, agrupadas por material
+    pub fn mesh(&self, reg: &MaterialRegistry) -> Mesh {
+        let mut groups: Vec<MeshGroup> = Vec::new();
+        let mut slot: HashMap<(BlockKind, Option<Face>), usize> = HashMap::new();
+        for ((x, y, z), kind) in self.blocks() {
+            if kind == BlockKind::Air { continue; }
+            let Some(mat) = reg.get(kind) else { continue };
+            let neighbours = self.neighbours(x, y, z);
+            let per_face = matches!(mat.albedo_tex, AlbedoTex::Cube { .. }) || matches!(mat.emission_tex, AlbedoTex::Cube { .. });
+            for face in Face::ALL {
+                let k = face.axis();
+                let mut n = [x, y, z];
+                n[k] += if face.is_positive() { 1 } else { -1 };
+                let hidden = self.occludes(n, reg)
+                    || (neighbours[face as usize] == Some(kind) && !mat.is_cutout());
+                if hidden { continue; }
+
+                let key = (kind, per_face.then_some(face));
+                let g = *slot.entry(key).or_insert_with(|| {
+                    groups.push(MeshGroup { kind, face: key.1, positions: Vec::new(), normals: Vec::new(), uvs: Vec::new(), indices: Vec::new() });
+                    groups.len() - 1
+                });
+                push_face(&mut groups[g], [x, y, z], face);
+            }
+        }
+        Mesh { groups }
+    }
+}
+
+/// Agrega la cara como dos triángulos en sentido antihorario vistos desde fuera
+fn push_face(g: &mut MeshGroup, p: [i32; 3], face: Face) {
+    let k = face.axis();
+    let (a, b) = ((k + 1) % 3, (k + 2) % 3);
+    let mut normal = [0.0; 3];
+    normal[k] = if face.is_positive() { 1.0 } else { -1.0 };
+
+    // Esquinas en el plano (a, b); a × b = k, así que este orden mira hacia +k
+    let mut corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+    if !face.is_positive() { corners.reverse(); }
+
+    let base = g.positions.len() as u32;
+    for (ca, cb) in corners {
+        let mut local = [0.0f32; 3];
+        local[k] = if face.is_positive() { 1.0 } else { 0.0 };
+        local[a] = ca;
+        local[b] = cb;
+        g.positions.push([0, 1, 2].map(|i| p[i] as f32 + local[i]));
+        g.normals.push(normal);
+        let (u, v) = face_uv(face, local);
+        g.uvs.push([u, v]);
+    }
+    g.indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm as glm;
+    use crate::core::color::Color;
+    use crate::core::geometry::{cube::Cube, RayIntersect};
+    use crate::core::material::{AlphaMode, Material};
+
+    fn registry() -> MaterialRegistry {
+        let mut reg = MaterialRegistry::new();
+        reg.set(BlockKind::Stone, Material::with_color(Color::new(120, 120, 120), 0.0, 1.0, 0.0, 0.0, 1.0));
+        reg.set(BlockKind::Water, Material::with_color(Color::new(40, 80, 200), 0.0, 1.0, 0.0, 0.6, 1.33));
+        reg.set(BlockKind::Leaves, Material::with_color(Color::new(40, 160, 40), 0.0, 1.0, 0.0, 0.0, 1.0)
+            .with_alpha(AlphaMode::Cutout { threshold: 0.5 }));
+        reg
+    }
+
+    fn pair(kind: BlockKind) -> Mesh {
+        let mut world = World::new();
+        world.set(0, 0, 0, kind);
+        world.set(1, 0, 0, kind);
+        world.mesh(&registry())
+    }
+
+    fn v(p: [f32; 3]) -> glm::Vec3 { glm::vec3(p[0], p[1], p[2]) }
+
+    #[test]
+    fn shared_face_is_culled() {
+        // Opacos y transparentes del mismo tipo no dejan la pared interna;
+        // los recortes por alfa sí (se ve a través)
+        for (kind, faces) in [(BlockKind::Stone, 10), (BlockKind::Water, 10), (BlockKind::Leaves, 12)] {
+            let mesh = pair(kind);
+            assert_eq!(mesh.triangles(), faces * 2, "{:?}", kind);
+            // Caras con las 4 esquinas en el plano x = 1, entre los dos bloques
+            let inner = mesh.groups.iter().flat_map(|g| g.positions.chunks(4)).filter(|q| q.iter().all(|p| p[0] == 1.0)).count();
+            assert_eq!(inner, faces - 10, "{:?}", kind);
+        }
+        assert_eq!(pair(BlockKind::Stone).bounds(), Some(([0.0, 0.0, 0.0], [2.0, 1.0, 1.0])));
+
+        // Agua contra piedra: la cara del agua queda tapada, la de la piedra no
+        let mut world = World::new();
+        world.set(0, 0, 0, BlockKind::Stone);
+        world.set(1, 0, 0, BlockKind::Water);
+        let mesh = world.mesh(&registry());
+        let count = |kind| mesh.groups.iter().filter(|g| g.kind == kind).map(|g| g.indices.len() / 3).sum::<usize>();
+        assert_eq!((count(BlockKind::Stone), count(BlockKind::Water)), (12, 10));
+    }
+
+    #[test]
+    fn winding_normals_and_uvs() {
+        let mesh = pair(BlockKind::Stone);
+        let center = glm::vec3(1.0, 0.5, 0.5);
+        for g in &mesh.groups {
+            for tri in g.indices.chunks(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| v(g.positions[tri[i] as usize]));
+                let n = v(g.normals[tri[0] as usize]);
+                // Antihorario visto desde fuera: el producto cruz es la normal
+                let cross = glm::normalize(&glm::cross(&(b - a), &(c - a)));
+                assert!((cross - n).norm() < 1e-5, "{:?} vs {:?}", cross, n);
+                assert!(glm::dot(&n, &((a + b + c) / 3.0 - center)) > 0.0);
+            }
+            // La UV de un punto de la cara es la misma que da `Cube` al intersectarla
+            for quad in g.indices.chunks(6) {
+                let [i0, i1, _, _, _, i3] = [0, 1, 2, 3, 4, 5].map(|i| quad[i] as usize);
+                let (s, t) = (0.3, 0.8);
+                let p = v(g.positions[i0]) + (v(g.positions[i1]) - v(g.positions[i0])) * s + (v(g.positions[i3]) - v(g.positions[i0])) * t;
+                let uv = |i: usize| glm::vec2(g.uvs[i][0], g.uvs[i][1]);
+                let want = uv(i0) + (uv(i1) - uv(i0)) * s + (uv(i3) - uv(i0)) * t;
+
+                let n = v(g.normals[i0]);
+                let cell = (p - n * 0.5).map(f32::floor);
+                let cube = Cube::new(cell, cell + glm::vec3(1.0, 1.0, 1.0), Material::default_black());
+                let hit = cube.ray_intersect(&(p + n), &-n);
+                assert!(hit.is_intersecting);
+                assert!((glm::vec2(hit.uv.0, hit.uv.1) - want).norm() < 1e-4, "{:?} vs {:?}", hit.uv, want);
+            }
+        }
+    }
+}
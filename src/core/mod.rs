@@ -1,15 +1,27 @@
 pub mod color;
 pub mod framebuffer;
+pub mod tonemap;
 pub mod ray;
 pub mod intersect;
 pub mod material;
 pub mod light;
 pub mod scene;
 pub mod renderer;
+pub mod path_tracer;
+pub mod ao;
+pub mod sampling;
 pub mod geometry;
 pub mod camera;
 pub mod texture;
 pub mod block;
+pub mod chunk;
 pub mod world;
+pub mod edit;
+pub mod history;
+pub mod mesh;
 pub mod material_registry;
-pub mod skybox;
\ No newline at end of file
+pub mod skybox;
+pub mod sky;
+pub mod environment;
+pub mod accel;
+pub mod formats;
\ No newline at end of file
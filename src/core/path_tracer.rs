@@ -0,0 +1,226 @@
+//! Integrador de path tracing (Monte Carlo) usado por `Renderer` cuando
+//! `integrator == Integrator::PathTracer`.
+//!
+//! Cada material se interpreta como una mezcla estocástica de lóbulos, en el
+//! mismo orden de capas que el Whitted: dieléctrico (`transparency`), espejo
+//! (`reflectivity`), brillo Phong (`specular`) y difuso Lambert con el resto.
+//! Las luces puntuales y los emisores se muestrean con next-event estimation;
+//! la emisión encontrada por rebote sólo se suma después de un rebote especular
+//! (espejo/dieléctrico) o desde la cámara, para no contarla dos veces. El
+//! entorno se muestrea por importancia y se combina con el rebote mediante MIS.
+
+use std::f32::consts::PI;
+use nalgebra_glm as glm;
+use rand::{Rng, SeedableRng};
+use rand::rngs::SmallRng;
+use super::environment::power_heuristic;
+use super::intersect::Intersect;
+use super::ray::{Ray, RayCone};
+use super::renderer::{offset_origin, reflect, refract_with_fresnel, shadow_visibility, texture_footprint, MipSelection};
+use super::sampling::splitmix64;
+use super::scene::Scene;
+
+// Rebote a partir del cual aplica la ruleta rusa
+const RR_START: u32 = 3;
+
+/// RNG determinista por pixel y frame (reproducible entre corridas)
+pub(crate) fn pixel_rng(frame: u32, pixel: usize) -> SmallRng {
+    // Mezcla tipo splitmix para decorrelacionar semillas vecinas
+    SmallRng::seed_from_u64(splitmix64((frame as u64) << 32 ^ pixel as u64))
+}
+
+/// Radiancia (lineal, sin clamp) que llega por `ray`.
+pub(crate) fn trace_path(
+    scene: &Scene, mut ray: Ray, mut cone: RayCone, mips: MipSelection, max_bounces: u32, rng: &mut SmallRng,
+) -> glm::Vec3 {
+    let mut radiance = glm::Vec3::zeros();
+    let mut throughput = glm::vec3(1.0, 1.0, 1.0);
+    let mut count_emission = true;
+    let mut bsdf_pdf = 0.0; // densidad del último rebote no especular (para MIS con el entorno)
+
+    for bounce in 0..=max_bounces {
+        let hit = scene.closest_hit(&ray.origin, &ray.dir);
+        if !hit.is_intersecting {
+            // El cielo actúa como luz ambiental; el disco del sol sólo se ve
+            // directo o en reflejos especulares (si no, ya vino por NEE). Tras un
+            // rebote difuso/brillante el entorno también se muestreó por NEE: MIS
+            let sky = if count_emission {
+                scene.background(ray.dir)
+            } else {
+                let w = scene.environment_map().map_or(1.0, |env| power_heuristic(bsdf_pdf, env.pdf(ray.dir)));
+                scene.environment(ray.dir) * w
+            };
+            radiance += throughput.component_mul(&sky);
+            break;
+        }
+
+        let mat = &hit.material;
+        if count_emission {
+            radiance += throughput.component_mul(&mat.sample_emission(hit.uv, hit.face));
+        }
+        if bounce == max_bounces { break; }
+
+        // Normal del lado por el que llega el rayo
+        let n = if glm::dot(&ray.dir, &hit.normal) < 0.0 { hit.normal } else { -hit.normal };
+        let footprint = texture_footprint(mips, cone, &hit, ray.dir);
+        cone = cone.continued(hit.distance);
+
+        // Alpha blend: con probabilidad (1 - alfa) el rayo sigue de largo
+        let coverage = mat.coverage(hit.uv, hit.face);
+        if coverage < 1.0 && rng.random::<f32>() >= coverage {
+            ray = Ray::new(offset_origin(hit.point, n, ray.dir), ray.dir);
+            continue;
+        }
+
+        let kt = mat.transparency.clamp(0.0, 1.0);
+        let kr = mat.reflectivity.clamp(0.0, 1.0);
+        let ks = mat.specular.clamp(0.0, 1.0);
+        let p_t = kt;
+        let p_r = (1.0 - kt) * kr;
+        let p_s = (1.0 - kt) * (1.0 - kr) * ks;
+
+        let u: f32 = rng.random();
+        let new_dir;
+        if u < p_t {
+            // Dieléctrico: Fresnel decide entre reflejar y refractar
+            new_dir = match refract_with_fresnel(ray.dir, hit.normal, mat.ior.max(1e-3)) {
+                Some((t, fresnel)) if rng.random::<f32>() >= fresnel => t,
+                _ => reflect(ray.dir, n),
+            };
+            count_emission = true;
+        } else if u < p_t + p_r {
+            // Espejo perfecto
+            new_dir = reflect(ray.dir, n);
+            count_emission = true;
+        } else if u < p_t + p_r + p_s {
+            // Lóbulo Phong normalizado, muestreado alrededor de la reflexión
+            let shininess = mat.shininess.max(1.0);
+            let r = reflect(ray.dir, n);
+            radiance += throughput.component_mul(&direct_light(scene, &hit, n, rng, |l| {
+                phong_brdf(r, l, shininess)
+            }, |l| phong_pdf(r, l, shininess)));
+
+            let cos_a = rng.random::<f32>().powf(1.0 / (shininess + 1.0));
+            let dir = sample_around(r, cos_a, rng.random());
+            let cos_n = glm::dot(&dir, &n);
+            if cos_n <= 0.0 { break; }
+            // f·cos/pdf para Phong normalizado = (n+2)/(n+1)·cos
+            throughput *= (shininess + 2.0) / (shininess + 1.0) * cos_n;
+            new_dir = dir;
+            bsdf_pdf = phong_pdf(r, dir, shininess);
+            count_emission = false;
+        } else {
+            // Difuso (Lambert) con muestreo coseno
+            let albedo = mat.sample_albedo(hit.uv, hit.face, footprint);
+            let cos_pdf = |l: glm::Vec3| glm::dot(&n, &l).max(0.0) / PI;
+            radiance += throughput.component_mul(&albedo.component_mul(&direct_light(scene, &hit, n, rng, |_| 1.0 / PI, cos_pdf)));
+
+            let dir = sample_around(n, rng.random::<f32>().sqrt(), rng.random());
+            throughput = throughput.component_mul(&albedo);
+            new_dir = dir;
+            bsdf_pdf = cos_pdf(dir);
+            count_emission = false;
+        }
+
+        // Ruleta rusa: corta caminos que ya aportan poco sin sesgar el estimador
+        if bounce >= RR_START {
+            let q = throughput.max().clamp(0.05, 0.95);
+            if rng.random::<f32>() > q { break; }
+            throughput /= q;
+        }
+
+        let origin = offset_origin(hit.point, n, new_dir);
+        ray = Ray::new(origin, new_dir);
+    }
+
+    radiance
+}
+
+/// Next-event estimation: todas las luces puntuales, un emisor elegido según su potencia y
+/// una dirección del entorno según su luminancia. `brdf(l)` evalúa la BRDF (sin
+/// albedo) hacia la dirección de luz `l` y `pdf(l)` la densidad con la que el
+/// rebote habría elegido `l`.
+fn direct_light<F, P>(scene: &Scene, hit: &Intersect, n: glm::Vec3, rng: &mut SmallRng, brdf: F, pdf: P) -> glm::Vec3
+where
+    F: Fn(glm::Vec3) -> f32,
+    P: Fn(glm::Vec3) -> f32,
+{
+    let mut sum = glm::Vec3::zeros();
+
+    // Luces analíticas: misma convención de intensidad que el Whitted
+    // (la intensidad ya es irradiancia; por eso el factor π)
+    for light in scene.all_lights() {
+        // Luces de área: un punto al azar por rebote (converge al acumular)
+        let Some(ls) = light.sample(hit.point, [rng.random(), rng.random()]) else { continue; };
+        let cos = glm::dot(&n, &ls.dir);
+        if cos <= 0.0 { continue; }
+        let vis = shadow_visibility(scene, hit.point, n, ls.dir, ls.dist);
+        if vis == 0.0 { continue; }
+        sum += ls.radiance * (vis * cos * brdf(ls.dir) * PI);
+    }
+
+    // Emisores: uno elegido según su potencia, con un punto sobre su superficie
+    let u = [rng.random(), rng.random(), rng.random(), rng.random()];
+    if let Some((ldir, li)) = emitter_light(scene, hit.point, n, u) {
+        sum += li * (brdf(ldir) * glm::dot(&n, &ldir));
+    }
+
+    // Entorno: pesado por MIS con el rebote, que también puede escapar al cielo
+    if let Some((ldir, env_pdf)) = scene.environment_map().and_then(|env| env.sample([rng.random(), rng.random()])) {
+        let cos = glm::dot(&n, &ldir);
+        if cos > 0.0 {
+            let vis = shadow_visibility(scene, hit.point, n, ldir, f32::INFINITY);
+            if vis > 0.0 {
+                let w = power_heuristic(env_pdf, pdf(ldir));
+                sum += scene.environment(ldir) * (vis * cos * brdf(ldir) * w / env_pdf);
+            }
+        }
+    }
+
+    sum
+}
+
+#[inline]
+/// Luz directa de un emisor: elige uno con `u[0]` y un punto uniforme sobre su
+/// superficie con el resto. Devuelve la dirección hacia el punto y la radiancia
+/// que llega dividida por la densidad en ángulo sólido (0 si está tapado); el
+/// llamador multiplica por la BRDF y el coseno.
+pub(crate) fn emitter_light(scene: &Scene, p: glm::Vec3, n: glm::Vec3, u: [f32; 4]) -> Option<(glm::Vec3, glm::Vec3)> {
+    let (e, pmf) = scene.pick_emitter(u[0])?;
+    let q = e.sample_surface([u[1], u[2], u[3]]);
+    let to_q = q - p;
+    let dist = glm::length(&to_q);
+    if dist <= 1e-4 { return None; }
+    let ldir = to_q / dist;
+    if glm::dot(&n, &ldir) <= 0.0 { return None; }
+
+    // Visible sólo si lo primero que golpeamos es el punto muestreado
+    let lh = scene.closest_hit(&offset_origin(p, n, ldir), &ldir);
+    if !lh.is_intersecting || glm::length(&(lh.point - q)) >= 1e-3 { return None; }
+    let cos_e = glm::dot(&lh.normal, &-ldir);
+    if cos_e <= 0.0 { return None; }
+    let le = lh.material.sample_emission(lh.uv, lh.face);
+    Some((ldir, le * (cos_e * e.area() / (dist * dist * pmf))))
+}
+
+fn phong_pdf(r: glm::Vec3, l: glm::Vec3, shininess: f32) -> f32 {
+    let c = glm::dot(&r, &l).max(0.0);
+    (shininess + 1.0) / (2.0 * PI) * c.powf(shininess)
+}
+
+#[inline]
+fn phong_brdf(r: glm::Vec3, l: glm::Vec3, shininess: f32) -> f32 {
+    let c = glm::dot(&r, &l).max(0.0);
+    (shininess + 2.0) / (2.0 * PI) * c.powf(shininess)
+}
+
+/// Dirección con coseno `cos_t` respecto de `axis` y azimut `u` en [0,1)
+fn sample_around(axis: glm::Vec3, cos_t: f32, u: f32) -> glm::Vec3 {
+    let sin_t = (1.0 - cos_t * cos_t).max(0.0).sqrt();
+    let phi = 2.0 * PI * u;
+    // Base ortonormal alrededor del eje
+    let helper = if axis.x.abs() > 0.9 { glm::vec3(0.0, 1.0, 0.0) } else { glm::vec3(1.0, 0.0, 0.0) };
+    let t = glm::normalize(&glm::cross(&helper, &axis));
+    let b = glm::cross(&axis, &t);
+    glm::normalize(&(t * (sin_t * phi.cos()) + b * (sin_t * phi.sin()) + axis * cos_t))
+}
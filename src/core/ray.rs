@@ -6,3 +6,20 @@ impl Ray {
         Self { origin, dir: glm::normalize(&dir) }
     }
 }
+
+/// Cono alrededor del rayo (huella de un pixel) para elegir el nivel de mip
+#[derive(Clone, Copy, Debug)]
+pub struct RayCone {
+    pub width: f32,  // ancho en el origen del rayo
+    pub spread: f32, // crecimiento del ancho por unidad de distancia
+}
+
+impl RayCone {
+    /// Ancho del cono a distancia `t` del origen
+    #[inline]
+    pub fn width_at(&self, t: f32) -> f32 { self.width + self.spread * t }
+
+    /// Mismo ángulo, continuado desde un hit a distancia `t` (reflexión/refracción)
+    #[inline]
+    pub fn continued(&self, t: f32) -> Self { Self { width: self.width_at(t), spread: self.spread } }
+}
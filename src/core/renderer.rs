@@ -1,7 +1,12 @@
+use std::f32::consts::PI;
 use nalgebra_glm as glm;
-use super::{color::Color, framebuffer::Framebuffer, scene::Scene, ray::Ray, intersect::Intersect};
-use super::geometry::RayIntersect;
+use super::{framebuffer::Framebuffer, scene::Scene, ray::{Ray, RayCone}, intersect::Intersect};
+use super::tonemap::ToneMapper;
 use super::camera::OrbitCamera;
+use super::ao::{self, AmbientOcclusion};
+use super::path_tracer;
+use super::environment::EnvironmentMap;
+use super::sampling::{self, PixelSampler};
 use rayon::prelude::*;
 
 // Profundidad máxima de rayos secundarios
@@ -9,104 +14,220 @@ const MAX_DEPTH: u32 = 3;
 // Pequeño sesgo para evitar acne de auto-intersección
 const RAY_BIAS: f32 = 1e-4;
 
-pub struct Renderer { }
+/// Algoritmo de render seleccionable en tiempo de ejecución
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Integrator {
+    /// Whitted clásico: Phong + reflexión/refracción a profundidad fija
+    Whitted,
+    /// Path tracing Monte Carlo progresivo (acumula mientras la cámara está quieta)
+    PathTracer,
+}
+
+/// Cómo se estima el tamaño de un pixel sobre la superficie para elegir el mip
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MipSelection {
+    /// Ancho del cono del rayo proyectado sobre la superficie (crece en ángulos rasantes)
+    Footprint,
+    /// Sólo el ancho del cono a la distancia recorrida
+    Distance,
+}
+
+pub struct Renderer {
+    pub integrator: Integrator,
+    pub max_bounces: u32, // rebotes máximos del path tracer
+    pub tone_mapper: ToneMapper,
+    pub exposure: f32,    // multiplicador lineal antes del tone mapping
+    pub sampler: PixelSampler, // antialiasing (patrón, muestras y filtro)
+    pub mip_selection: MipSelection,
+    pub shadow_samples: u32, // rayos de sombra por luz de área (calidad de la penumbra)
+    pub ambient: f32,        // término ambiente del Whitted cuando no hay luz de entorno
+    pub ao: AmbientOcclusion,
+    pub env_samples: u32,    // rayos hacia el skybox/cielo por punto en Whitted (0 = término ambiente)
+    // Acumulación progresiva (sólo path tracing)
+    accum: Vec<(glm::Vec3, f32)>, // suma ponderada y suma de pesos del filtro
+    accum_frames: u32,
+    last_view: Option<ViewKey>,
+}
+
+/// Lo que invalida la acumulación si cambia entre frames
+#[derive(Clone, Copy, PartialEq)]
+struct ViewKey {
+    eye: glm::Vec3,
+    center: glm::Vec3,
+    width: u32,
+    height: u32,
+    sampler: PixelSampler,
+    scene: u64, // `Scene::generation`
+    max_bounces: u32,
+    mip_selection: MipSelection,
+}
+
+impl Default for Renderer {
+    fn default() -> Self { Self::new() }
+}
+
 impl Renderer {
-    pub fn new() -> Self { Self {} }
+    pub fn new() -> Self {
+        Self {
+            integrator: Integrator::Whitted,
+            max_bounces: 8,
+            tone_mapper: ToneMapper::Exposure,
+            exposure: 1.0,
+            sampler: PixelSampler::default(),
+            mip_selection: MipSelection::Footprint,
+            shadow_samples: 16,
+            ambient: 0.18,
+            ao: AmbientOcclusion::default(),
+            env_samples: 0, // la luz de entorno cuesta rayos por punto: se pide aparte
+            accum: Vec::new(),
+            accum_frames: 0,
+            last_view: None,
+        }
+    }
 
-    pub fn render_frame(&self, scene: &Scene, fb: &mut Framebuffer, cam: &OrbitCamera) {
+    /// Descarta las muestras acumuladas (p.ej. si la escena cambió)
+    pub fn reset_accumulation(&mut self) {
+        self.accum_frames = 0;
+        self.last_view = None;
+    }
+
+    /// Frames acumulados en la imagen actual del path tracer
+    pub fn accumulated_frames(&self) -> u32 { self.accum_frames }
+
+    pub fn render_frame(&mut self, scene: &Scene, fb: &mut Framebuffer, cam: &OrbitCamera) {
         let width  = fb.width as f32;
         let height = fb.height as f32;
         let aspect = width / height;
-    
+
         let cam_origin = cam.eye();
         let (right, up, forward) = cam.basis();
-    
-        // Precompute NDC for cols/rows 
+
         let w = fb.width as usize;
         let h = fb.height as usize;
-    
-        let mut sxs = Vec::with_capacity(w);
-        for x in 0..w {
-            // NDC x in [-1,1] with aspect
-            let sx = (2.0 * x as f32) / width - 1.0;
-            sxs.push(sx * aspect);
-        }
-        let mut sys = Vec::with_capacity(h);
-        for y in 0..h {
-            // NDC y in [-1,1]
-            let sy = 1.0 - (2.0 * y as f32) / height;
-            sys.push(sy);
-        }
-    
+
         // Buffer temporal para resultados por pixel (se llena en paralelo)
-        let mut scratch: Vec<Color> = vec![Color::new(0,0,0); w * h];
-    
+        let mut scratch: Vec<glm::Vec3> = vec![glm::Vec3::zeros(); w * h];
+
         // Datos inmutables que capturamos en el closure (Send + Sync)
         let r = right;   // copia por valor (Vec3)
         let u = up;
         let f = forward;
         let cam_o = cam_origin;
-    
-        scratch
-            .par_iter_mut()               // iteración paralela
-            .enumerate()
-            .for_each(|(idx, pix)| {
-                let y = idx / w;
-                let x = idx % w;
-    
-                let dir_world = glm::normalize(&(sxs[x] * r + sys[y] * u + f));
-                let ray = Ray::new(cam_o, dir_world);
-                let color = self.trace(&ray, scene, 0);
-                *pix = color;
-            });
-    
+        let sampler = self.sampler;
+        let spp = sampler.samples.max(1);
+        let mips = self.mip_selection;
+        // Un pixel subtiende ~2/alto unidades de NDC a distancia 1 de la cámara
+        let cone = RayCone { width: 0.0, spread: 2.0 / height };
+
+        // Rayo por la posición (px, py) en coordenadas de pixel (centro = x + 0.5)
+        let primary = |px: f32, py: f32| -> Ray {
+            let sx = ((2.0 * px) / width - 1.0) * aspect;   // NDC x con aspecto
+            let sy = 1.0 - (2.0 * py) / height;             // NDC y
+            Ray::new(cam_o, glm::normalize(&(sx * r + sy * u + f)))
+        };
+
+        match self.integrator {
+            Integrator::Whitted => {
+                self.reset_accumulation();
+                let this = &*self;
+                scratch
+                    .par_iter_mut()               // iteración paralela
+                    .enumerate()
+                    .for_each(|(idx, pix)| {
+                        let y = (idx / w) as u32;
+                        let x = (idx % w) as u32;
+
+                        // Promedio ponderado por el filtro de reconstrucción
+                        let mut sum = glm::Vec3::zeros();
+                        let mut wsum = 0.0;
+                        for k in 0..spp {
+                            let (dx, dy, wt) = sampler.sample(k, x, y);
+                            if wt <= 0.0 { continue; }
+                            let ray = primary(x as f32 + 0.5 + dx, y as f32 + 0.5 + dy);
+                            sum += wt * this.trace(&ray, cone, scene, 0);
+                            wsum += wt;
+                        }
+                        *pix = if wsum > 0.0 { sum / wsum } else { sum };
+                    });
+            }
+            Integrator::PathTracer => {
+                // Si la cámara, la escena o los ajustes cambiaron, empezamos de cero
+                let view = ViewKey {
+                    eye: cam_o, center: cam.center, width: fb.width, height: fb.height, sampler,
+                    scene: scene.generation(), max_bounces: self.max_bounces, mip_selection: self.mip_selection,
+                };
+                if self.last_view != Some(view) || self.accum.len() != w * h {
+                    self.accum.clear();
+                    self.accum.resize(w * h, (glm::Vec3::zeros(), 0.0));
+                    self.accum_frames = 0;
+                    self.last_view = Some(view);
+                }
+
+                let frame = self.accum_frames;
+                let max_bounces = self.max_bounces;
+
+                self.accum
+                    .par_iter_mut()
+                    .enumerate()
+                    .for_each(|(idx, (acc, wacc))| {
+                        let y = (idx / w) as u32;
+                        let x = (idx % w) as u32;
+                        let mut rng = path_tracer::pixel_rng(frame, idx);
+
+                        // Cada frame continúa la secuencia de muestras del pixel
+                        for k in 0..spp {
+                            let (dx, dy, wt) = sampler.sample(frame * spp + k, x, y);
+                            if wt <= 0.0 { continue; }
+                            let ray = primary(x as f32 + 0.5 + dx, y as f32 + 0.5 + dy);
+                            *acc += wt * path_tracer::trace_path(scene, ray, cone, mips, max_bounces, &mut rng);
+                            *wacc += wt;
+                        }
+                    });
+                self.accum_frames += 1;
+
+                scratch
+                    .par_iter_mut()
+                    .zip(self.accum.par_iter())
+                    .for_each(|(pix, (acc, wacc))| {
+                        *pix = if *wacc > 0.0 { acc / *wacc } else { *acc };
+                    });
+            }
+        }
+
         // Blit secuencial al framebuffer (barato vs. todo el cómputo anterior)
         for y in 0..h {
             let row = &scratch[y * w .. (y + 1) * w];
-            for x in 0..w {
-                fb.set_pixel(x as u32, y as u32, row[x]);
+            for (x, &c) in row.iter().enumerate() {
+                fb.set_radiance(x as u32, y as u32, c);
             }
         }
+        fb.resolve(self.tone_mapper, self.exposure);
     }
 
-    fn trace(&self, ray: &Ray, scene: &Scene, depth: u32) -> Color {
+    fn trace(&self, ray: &Ray, cone: RayCone, scene: &Scene, depth: u32) -> glm::Vec3 {
         if depth >= MAX_DEPTH {
-            if let Some(sb) = &scene.skybox {
-                return sb.sample(ray.dir);
-            }
-            return Color::new(135, 206, 235); // fallback cielo plano
+            return scene.background(ray.dir);
         }        
 
-        // --- Closest hit ---
-        let mut closest = Intersect::miss();
-        let mut zbuffer = f32::INFINITY;
-
-        // Spheres
-        for s in &scene.spheres {
-            let hit = s.ray_intersect(&ray.origin, &ray.dir);
-            if hit.is_intersecting && hit.distance < zbuffer {
-                zbuffer = hit.distance;
-                closest = hit;
-            }
-        }
-        // Cubes (versión estándar)
-        for b in &scene.cubes {
-            let hit = b.ray_intersect(&ray.origin, &ray.dir);
-            if hit.is_intersecting && hit.distance < zbuffer {
-                zbuffer = hit.distance;
-                closest = hit;
-            }
-        }
+        // --- Closest hit (grilla de vóxeles + primitivas sueltas) ---
+        let closest = scene.closest_hit(&ray.origin, &ray.dir);
 
         if !closest.is_intersecting {
-            if let Some(sb) = &scene.skybox {
-                return sb.sample(ray.dir);
-            }
-            return Color::new(135, 206, 235);
+            return scene.background(ray.dir);
         }
 
         // --- Shading local (Phong básico) ---
-        let local = self.shade_local(scene, &closest, ray.origin);
+        let footprint = texture_footprint(self.mip_selection, cone, &closest, ray.dir);
+        let mut local = self.shade_local(scene, &closest, ray.origin, footprint);
+        let cone = cone.continued(closest.distance);
+
+        // --- Alpha blend: mezcla con lo que hay detrás de la superficie ---
+        let coverage = closest.material.coverage(closest.uv, closest.face);
+        if coverage < 1.0 {
+            let behind_origin = offset_origin(closest.point, closest.normal, ray.dir);
+            let behind = self.trace(&Ray::new(behind_origin, ray.dir), cone, scene, depth + 1);
+            local = coverage * local + (1.0 - coverage) * behind;
+        }
 
         // --- Reflexión / Transparencia ---
         let kr = closest.material.reflectivity.clamp(0.0, 1.0);
@@ -118,15 +239,15 @@ impl Renderer {
         }
 
         let n = closest.normal; // normal saliente
-        let mut refl_col = Color::new(0,0,0);
-        let mut refr_col = Color::new(0,0,0);
+        let mut refl_col = glm::Vec3::zeros();
+        let mut refr_col = glm::Vec3::zeros();
 
         // Reflexión
         if kr > 0.0 {
             let refl_dir = reflect(ray.dir, n);
             let refl_origin = offset_origin(closest.point, n, refl_dir);
             let refl_ray = Ray::new(refl_origin, refl_dir);
-            refl_col = self.trace(&refl_ray, scene, depth + 1);
+            refl_col = self.trace(&refl_ray, cone, scene, depth + 1);
         }
 
         // Refracción (Snell + TIR)
@@ -136,7 +257,7 @@ impl Renderer {
                 fresnel = fres; // proporción reflejada física
                 let refr_origin = offset_origin(closest.point, -n, refr_dir);
                 let refr_ray = Ray::new(refr_origin, refr_dir);
-                refr_col = self.trace(&refr_ray, scene, depth + 1);
+                refr_col = self.trace(&refr_ray, cone, scene, depth + 1);
             } else {
                 // TIR: todo se refleja
                 fresnel = 1.0;
@@ -168,60 +289,128 @@ impl Renderer {
         w_local /= sum; w_refl /= sum; w_refr /= sum;
 
         // Composición final
-        return mix3(local, refl_col, refr_col, w_local, w_refl, w_refr);
+        mix3(local, refl_col, refr_col, w_local, w_refl, w_refr)
     }
 
-    fn shade_local(&self, scene: &Scene, hit: &Intersect, cam_origin: glm::Vec3) -> Color {
+    fn shade_local(&self, scene: &Scene, hit: &Intersect, cam_origin: glm::Vec3, footprint: f32) -> glm::Vec3 {
         // Albedo (textura o color sólido)
-        let albedo = hit.material.sample_albedo(hit.uv, hit.face).to_vec3();
-        let mut result = albedo * 0.18; // ambiente
+        let albedo = hit.material.sample_albedo(hit.uv, hit.face, footprint);
         let view_dir = glm::normalize(&(cam_origin - hit.point));
+        // Normal del lado del observador (caras interiores de hojas con cutout)
+        let n = if glm::dot(&view_dir, &hit.normal) < 0.0 { -hit.normal } else { hit.normal };
+
+        // Luz del entorno (sus rayos de sombra ya ocluyen) o ambiente plano con AO
+        let voxel_ao = if self.ao.voxel { ao::voxel_factor(hit.vertex_ao) } else { 1.0 };
+        let mut result = match scene.environment_map() {
+            Some(env) if self.env_samples > 0 => self.environment_light(scene, env, hit, n, view_dir, albedo) * voxel_ao,
+            _ => albedo * (self.ambient * self.ao.ray_traced(scene, hit.point, n) * voxel_ao),
+        };
+        result += hit.material.sample_emission(hit.uv, hit.face); // emisión propia
+
+        for light in scene.all_lights() {
+            // Luces de área: grilla estratificada de muestras sobre la superficie
+            let side = if light.is_area() { (self.shadow_samples.max(1) as f32).sqrt().ceil() as u32 } else { 1 };
+            let count = side * side;
+            let seed = sampling::point_seed(&hit.point);
+
+            for k in 0..count {
+                // Dirección, distancia y radiancia (atenuación y cono ya aplicados)
+                let u = if count == 1 { [0.5, 0.5] } else { sampling::stratified(k, side, seed) };
+                let Some(ls) = light.sample(hit.point, u) else { continue; };
+                let ldir = ls.dir;
+
+                // Difuso (Lambert)
+                let ndotl = glm::dot(&n, &ldir);
+                if ndotl <= 0.0 {
+                    // Si no hay difuso, tampoco hay especular Phong en este modelo
+                    continue;
+                }
+                let diff = ndotl;
+
+                // Visibilidad (0 en sombra, 1 visible)
+                let vis = shadow_visibility(scene, hit.point, n, ldir, ls.dist);
+                if vis == 0.0 { continue; }
+
+                let light_col = ls.radiance;
+                let mut add = albedo.component_mul(&light_col) * diff;
+
+                // Especular (Phong) sólo si el material lo soporta
+                if hit.material.specular > 0.0 {
+                    let r = reflect(-ldir, n);
+                    let specdot = glm::dot(&r, &view_dir).max(0.0);
+                    if specdot > 0.0 {
+                        let spec = specdot.powf(hit.material.shininess.max(1.0));
+                        add += light_col * (hit.material.specular * spec);
+                    }
+                }
 
-        for light in &scene.lights {
-            let ldir = glm::normalize(&(light.position - hit.point));
-            let n = hit.normal; // ya normalizada
-
-            // Visibilidad (0 en sombra, 1 visible)
-            let vis = shadow_visibility(scene, hit.point, n, light.position);
-            if vis == 0.0 { continue; }
-
-            // Difuso (Lambert)
-            let ndotl = glm::dot(&n, &ldir);
-            if ndotl <= 0.0 {
-                // Si no hay difuso, tampoco hay especular Phong en este modelo
-                continue;
+                // Aplica visibilidad (y el peso de la muestra)
+                result += (vis / count as f32) * add;
             }
-            let diff = ndotl;
-
-            let light_col = light.color.to_vec3() * light.intensity;
-            let mut add = albedo.component_mul(&light_col) * diff;
-
-            // Especular (Phong) sólo si el material lo soporta
-            if hit.material.specular > 0.0 {
-                let r = reflect(-ldir, n);
-                let specdot = glm::dot(&r, &view_dir).max(0.0);
-                if specdot > 0.0 {
-                    let spec = specdot.powf(hit.material.shininess.max(1.0));
-                    add += light_col * (hit.material.specular * spec);
-                }
+        }
+
+        // Objetos emisivos como luces de área: cada muestra elige un emisor
+        // según su potencia (misma estimación que el path tracer)
+        if !scene.emitters().is_empty() {
+            let side = (self.shadow_samples.max(1) as f32).sqrt().ceil() as u32;
+            let count = side * side;
+            let seed = sampling::point_seed(&hit.point) ^ 0xe317;
+            for k in 0..count {
+                let [u1, u2] = sampling::stratified(k, side, seed);
+                let (u0, u3) = sampling::hash2(seed, (k + count) as u64);
+                let Some((ldir, li)) = path_tracer::emitter_light(scene, hit.point, n, [u0, u1, u2, u3]) else { continue; };
+                result += albedo.component_mul(&li) * (glm::dot(&n, &ldir) / (PI * count as f32));
             }
+        }
+
+        result
+    }
 
-            // Aplica visibilidad
-            result += vis * add;
+    /// Difuso y brillo Phong iluminados por el fondo, con direcciones elegidas
+    /// según la luminancia del entorno (estratificadas por punto)
+    fn environment_light(
+        &self, scene: &Scene, env: &EnvironmentMap, hit: &Intersect, n: glm::Vec3, view_dir: glm::Vec3, albedo: glm::Vec3,
+    ) -> glm::Vec3 {
+        let side = (self.env_samples as f32).sqrt().ceil() as u32;
+        let count = side * side;
+        let seed = sampling::point_seed(&hit.point) ^ 0x5eed;
+        let shininess = hit.material.shininess.max(1.0);
+        let r = reflect(-view_dir, n);
+
+        let mut sum = glm::Vec3::zeros();
+        for k in 0..count {
+            let Some((dir, pdf)) = env.sample(sampling::stratified(k, side, seed)) else { continue; };
+            let cos = glm::dot(&n, &dir);
+            if cos <= 0.0 { continue; }
+            let vis = shadow_visibility(scene, hit.point, n, dir, f32::INFINITY);
+            if vis == 0.0 { continue; }
+            // Lambert + Phong normalizado (mismo lóbulo que el path tracer)
+            let spec = hit.material.specular
+                * (shininess + 2.0) / (2.0 * PI) * glm::dot(&r, &dir).max(0.0).powf(shininess);
+            let f = albedo / PI + glm::vec3(spec, spec, spec);
+            sum += scene.environment(dir).component_mul(&f) * (vis * cos / pdf);
         }
+        sum / count as f32
+    }
+}
 
-        Color::from_vec3(&result)
+/// Tamaño del pixel sobre la superficie golpeada (en unidades de mundo)
+pub(crate) fn texture_footprint(mode: MipSelection, cone: RayCone, hit: &Intersect, dir: glm::Vec3) -> f32 {
+    let width = cone.width_at(hit.distance);
+    match mode {
+        MipSelection::Footprint => width / glm::dot(&dir, &hit.normal).abs().max(0.05),
+        MipSelection::Distance => width,
     }
 }
 
 #[inline]
-fn reflect(i: glm::Vec3, n: glm::Vec3) -> glm::Vec3 {
+pub(crate) fn reflect(i: glm::Vec3, n: glm::Vec3) -> glm::Vec3 {
     glm::normalize(&(i - 2.0 * glm::dot(&i, &n) * n))
 }
 
 /// Devuelve (dir_refractada, fresnel_reflectance) o None si hay TIR.
 /// `ior` es n2/n1 (índice absoluto del material; tomamos n1=aire=1.0).
-fn refract_with_fresnel(i: glm::Vec3, n: glm::Vec3, ior: f32) -> Option<(glm::Vec3, f32)> {
+pub(crate) fn refract_with_fresnel(i: glm::Vec3, n: glm::Vec3, ior: f32) -> Option<(glm::Vec3, f32)> {
     // Normalizamos por seguridad
     let i = glm::normalize(&i);
     let mut n = glm::normalize(&n);
@@ -250,44 +439,28 @@ fn refract_with_fresnel(i: glm::Vec3, n: glm::Vec3, ior: f32) -> Option<(glm::Ve
     Some((t, fresnel))
 }
 
-fn shadow_visibility(scene: &Scene, p: glm::Vec3, n: glm::Vec3, light_pos: glm::Vec3) -> f32 {
-    let to_light = light_pos - p;
-    let dist = glm::length(&to_light);
+/// Fracción de luz que llega desde `p` en la dirección `ldir` (unitaria) hasta
+/// distancia `dist` (`f32::INFINITY` para luces direccionales).
+pub(crate) fn shadow_visibility(scene: &Scene, p: glm::Vec3, n: glm::Vec3, ldir: glm::Vec3, dist: f32) -> f32 {
     if dist <= 0.0 { return 1.0; }
 
-    let ldir = to_light / dist;
-
     let origin = offset_origin(p, n, ldir);
     let tmax = dist - RAY_BIAS;
 
     // Acumulamos visibilidad multiplicando transparencias de los bloqueadores
-    // (1.0 = luz totalmente visible, 0.0 = completamente en sombra)
+    // (1.0 = luz totalmente visible, 0.0 = completamente en sombra).
+    // Los huecos de alpha cutout ya no aparecen como hits; el alpha blend deja
+    // pasar la fracción no cubierta.
     let mut vis = 1.0_f32;
 
-    // Spheres
-    for s in &scene.spheres {
-        let h = s.ray_intersect(&origin, &ldir);
-        if h.is_intersecting && h.distance < tmax {
-            let t = h.material.transparency.clamp(0.0, 1.0);
-            if t <= 1e-3 { return 0.0; }     // bloqueador opaco: sombra dura
-            vis *= t;                         // semitransparente: atenúa la luz
-            if vis < 0.02 {
-                return 0.0;
-            }
-        }
-    }
-    // Cubess
-    for b in &scene.cubes {
-        let h = b.ray_intersect(&origin, &ldir);
-        if h.is_intersecting && h.distance < tmax {
-            let t = h.material.transparency.clamp(0.0, 1.0);
-            if t <= 1e-3 { return 0.0; }
-            vis *= t;
-            if vis < 0.02 {
-                return 0.0;
-            }
-        }
-    }
+    scene.for_each_hit(&origin, &ldir, tmax, |h| {
+        let coverage = h.material.coverage(h.uv, h.face);
+        let t = 1.0 - coverage * (1.0 - h.material.transparency.clamp(0.0, 1.0));
+        if t <= 1e-3 { vis = 0.0; return false; } // bloqueador opaco: sombra dura
+        vis *= t;                                   // semitransparente: atenúa la luz
+        if vis < 0.02 { vis = 0.0; return false; }
+        true
+    });
 
     vis.clamp(0.0, 1.0)
 }
@@ -295,19 +468,14 @@ fn shadow_visibility(scene: &Scene, p: glm::Vec3, n: glm::Vec3, light_pos: glm::
 /// Pequeño offset para evitar auto-colisión (acné). Empuja el origen
 /// a lo largo de la normal dependiendo del sentido del rayo.
 #[inline]
-fn offset_origin(p: glm::Vec3, n: glm::Vec3, dir: glm::Vec3) -> glm::Vec3 {
+pub(crate) fn offset_origin(p: glm::Vec3, n: glm::Vec3, dir: glm::Vec3) -> glm::Vec3 {
     let sign = if glm::dot(&dir, &n) >= 0.0 { 1.0 } else { -1.0 };
     p + n * (sign * RAY_BIAS)
 }
 
 #[inline]
-fn mix3(a: Color, b: Color, c: Color, wa: f32, wb: f32, wc: f32) -> Color {
-    // Normaliza en caso de redondeos
+fn mix3(a: glm::Vec3, b: glm::Vec3, c: glm::Vec3, wa: f32, wb: f32, wc: f32) -> glm::Vec3 {
+    // Normaliza en caso de redondeos (sin clamp: seguimos en HDR)
     let s = (wa + wb + wc).max(1e-6);
-    let wa = wa / s; let wb = wb / s; let wc = wc / s;
-
-    let av = a.to_vec3();
-    let bv = b.to_vec3();
-    let cv = c.to_vec3();
-    Color::from_vec3(&(wa * av + wb * bv + wc * cv))
+    (wa / s) * a + (wb / s) * b + (wc / s) * c
 }
\ No newline at end of file
@@ -0,0 +1,259 @@
+//! Supersampling por pixel: patrones de posiciones dentro del pixel y filtros
+//! de reconstrucción para promediar las muestras. También las grillas
+//! estratificadas que usan las luces de área.
+
+use nalgebra_glm as glm;
+
+/// Distribución de las muestras dentro de un pixel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SamplePattern {
+    /// Grilla regular (las pasadas siguientes desplazan la grilla)
+    Grid,
+    /// Grilla estratificada con jitter aleatorio dentro de cada celda
+    Jittered,
+    /// Secuencia de Halton (bases 2 y 3) rotada por pixel
+    Halton,
+    /// Secuencia R2 rotada con ruido de gradiente entrelazado: error con
+    /// distribución tipo blue-noise entre pixeles vecinos
+    BlueNoise,
+}
+
+/// Filtro de reconstrucción (peso de cada muestra según su offset al centro)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFilter {
+    /// Promedio simple dentro del pixel (radio 0.5)
+    Box,
+    /// Triangular de radio 1 pixel
+    Tent,
+    /// Gaussiano truncado de radio 1.5 pixeles
+    Gaussian,
+}
+
+impl PixelFilter {
+    /// Radio del soporte en pixeles
+    pub fn radius(self) -> f32 {
+        match self {
+            PixelFilter::Box => 0.5,
+            PixelFilter::Tent => 1.0,
+            PixelFilter::Gaussian => 1.5,
+        }
+    }
+
+    /// Peso separable para un offset (dx, dy) en pixeles desde el centro
+    pub fn weight(self, dx: f32, dy: f32) -> f32 {
+        let r = self.radius();
+        let w1 = |d: f32| -> f32 {
+            let d = d.abs();
+            if d > r { return 0.0; }
+            match self {
+                PixelFilter::Box => 1.0,
+                PixelFilter::Tent => r - d,
+                PixelFilter::Gaussian => {
+                    // Restamos el valor en el borde para que llegue a 0 sin salto
+                    const ALPHA: f32 = 2.0;
+                    ((-ALPHA * d * d).exp() - (-ALPHA * r * r).exp()).max(0.0)
+                }
+            }
+        };
+        w1(dx) * w1(dy)
+    }
+}
+
+/// Configuración de antialiasing del `Renderer`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PixelSampler {
+    pub pattern: SamplePattern,
+    pub samples: u32, // muestras por pixel y frame
+    pub filter: PixelFilter,
+}
+
+impl Default for PixelSampler {
+    /// Un rayo por el centro del pixel (sin antialiasing)
+    fn default() -> Self {
+        Self { pattern: SamplePattern::Grid, samples: 1, filter: PixelFilter::Box }
+    }
+}
+
+impl PixelSampler {
+    pub fn new(pattern: SamplePattern, samples: u32, filter: PixelFilter) -> Self {
+        Self { pattern, samples: samples.max(1), filter }
+    }
+
+    /// Muestra `i` del pixel `(x, y)`: offset en pixeles desde el centro y peso
+    /// del filtro. `i` puede seguir creciendo entre frames (path tracing).
+    pub fn sample(&self, i: u32, x: u32, y: u32) -> (f32, f32, f32) {
+        let (u, v) = self.point(i, x, y);
+        let r = self.filter.radius();
+        let dx = (2.0 * u - 1.0) * r;
+        let dy = (2.0 * v - 1.0) * r;
+        (dx, dy, self.filter.weight(dx, dy))
+    }
+
+    /// Posición en [0,1)² dentro del soporte del filtro
+    fn point(&self, i: u32, x: u32, y: u32) -> (f32, f32) {
+        let n = self.samples.max(1);
+        let pixel = (y as u64) << 32 | x as u64;
+        match self.pattern {
+            SamplePattern::Grid | SamplePattern::Jittered => {
+                let side = (n as f32).sqrt().ceil() as u32;
+                let cells = side * side;
+                let cell = i % cells;
+                let pass = i / cells;
+                let (cx, cy) = ((cell % side) as f32, (cell / side) as f32);
+                let (jx, jy) = match self.pattern {
+                    SamplePattern::Jittered => hash2(pixel, i as u64),
+                    // Primera pasada en los centros; las siguientes, grilla desplazada
+                    _ if pass == 0 => (0.5, 0.5),
+                    _ => hash2(pass as u64, 0),
+                };
+                ((cx + jx) / side as f32, (cy + jy) / side as f32)
+            }
+            SamplePattern::Halton => {
+                let (ox, oy) = hash2(pixel, u64::MAX);
+                (fract(radical_inverse(i + 1, 2) + ox), fract(radical_inverse(i + 1, 3) + oy))
+            }
+            SamplePattern::BlueNoise => {
+                // R2 (Roberts): constantes 1/φ₂ y 1/φ₂²
+                const A1: f32 = 0.754_877_7;
+                const A2: f32 = 0.569_840_3;
+                let o = interleaved_gradient_noise(x as f32, y as f32);
+                let k = i as f32;
+                (fract(0.5 + A1 * k + o), fract(0.5 + A2 * k + o * 0.618_034))
+            }
+        }
+    }
+}
+
+/// Muestra `k` de una grilla estratificada `side`×`side` en [0,1)², con jitter
+/// determinista a partir de `seed`
+pub(crate) fn stratified(k: u32, side: u32, seed: u64) -> [f32; 2] {
+    let (jx, jy) = hash2(seed, k as u64);
+    [((k % side) as f32 + jx) / side as f32, ((k / side) as f32 + jy) / side as f32]
+}
+
+/// Semilla a partir de un punto (decorrelaciona el jitter entre puntos vecinos)
+pub(crate) fn point_seed(p: &glm::Vec3) -> u64 {
+    splitmix64((p.x.to_bits() as u64) << 32 ^ (p.y.to_bits() as u64) << 16 ^ p.z.to_bits() as u64)
+}
+
+/// Inversa radical de `i` en la base dada (Van der Corput)
+fn radical_inverse(mut i: u32, base: u32) -> f32 {
+    let inv = 1.0 / base as f32;
+    let mut f = inv;
+    let mut r = 0.0;
+    while i > 0 {
+        r += (i % base) as f32 * f;
+        i /= base;
+        f *= inv;
+    }
+    r
+}
+
+/// Ruido de gradiente entrelazado (Jimenez 2014)
+fn interleaved_gradient_noise(x: f32, y: f32) -> f32 {
+    fract(52.982_918 * fract(0.067_110_56 * x + 0.005_837_15 * y))
+}
+
+#[inline]
+fn fract(x: f32) -> f32 { x - x.floor() }
+
+/// Dos valores uniformes en [0,1) a partir de una semilla (splitmix64)
+pub(crate) fn hash2(a: u64, b: u64) -> (f32, f32) {
+    let h = splitmix64(a ^ splitmix64(b));
+    let to_unit = |bits: u64| (bits >> 40) as f32 / (1u64 << 24) as f32;
+    (to_unit(h), to_unit(h << 24))
+}
+
+#[inline]
+pub(crate) fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATTERNS: [SamplePattern; 4] = [SamplePattern::Grid, SamplePattern::Jittered, SamplePattern::Halton, SamplePattern::BlueNoise];
+    const FILTERS: [PixelFilter; 3] = [PixelFilter::Box, PixelFilter::Tent, PixelFilter::Gaussian];
+
+    #[test]
+    fn points_stay_in_unit_square() {
+        for pattern in PATTERNS {
+            for samples in [1, 2, 4, 5, 9, 16, 33] {
+                let sampler = PixelSampler::new(pattern, samples, PixelFilter::Box);
+                for (x, y) in [(0, 0), (1, 0), (17, 5), (1299, 899), (u32::MAX, u32::MAX)] {
+                    // Varias pasadas: el path tracer sigue aumentando `i` entre frames
+                    for i in 0..samples * 3 {
+                        let (u, v) = sampler.point(i, x, y);
+                        assert!((0.0..1.0).contains(&u) && (0.0..1.0).contains(&v),
+                                "{:?} n={} pixel ({}, {}) i={}: ({}, {})", pattern, samples, x, y, i, u, v);
+                        // Los offsets quedan dentro del soporte del filtro
+                        let (dx, dy, w) = sampler.sample(i, x, y);
+                        assert!(dx.abs() <= 0.5 && dy.abs() <= 0.5 && w >= 0.0);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn single_grid_sample_is_the_centre() {
+        let sampler = PixelSampler::default();
+        assert_eq!(sampler.point(0, 3, 4), (0.5, 0.5));
+        assert_eq!(sampler.sample(0, 3, 4), (0.0, 0.0, 1.0));
+        // 4 muestras: los centros de una grilla de 2×2
+        let grid = PixelSampler::new(SamplePattern::Grid, 4, PixelFilter::Box);
+        let points: Vec<_> = (0..4).map(|i| grid.point(i, 0, 0)).collect();
+        assert_eq!(points, [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]);
+    }
+
+    #[test]
+    fn sequences_are_deterministic_per_pixel() {
+        for pattern in [SamplePattern::Halton, SamplePattern::BlueNoise, SamplePattern::Jittered] {
+            let sampler = PixelSampler::new(pattern, 8, PixelFilter::Tent);
+            let run = |x, y| (0..8).map(|i| sampler.point(i, x, y)).collect::<Vec<_>>();
+            assert_eq!(run(12, 7), run(12, 7), "{:?}", pattern);
+            // Pixeles distintos reciben secuencias distintas
+            assert_ne!(run(12, 7), run(13, 7), "{:?}", pattern);
+            assert_ne!(run(12, 7), run(12, 8), "{:?}", pattern);
+            // Dentro de un pixel, las muestras no se repiten
+            let pts = run(3, 3);
+            for (a, p) in pts.iter().enumerate() {
+                assert!(pts[a + 1..].iter().all(|q| q != p), "{:?}", pattern);
+            }
+        }
+    }
+
+    #[test]
+    fn filter_weights() {
+        for filter in FILTERS {
+            let r = filter.radius();
+            let peak = filter.weight(0.0, 0.0);
+            assert!(peak > 0.0, "{:?}", filter);
+            for i in 0..=40 {
+                let d = r * i as f32 / 40.0;
+                for (dx, dy) in [(d, 0.0), (0.0, -d), (d, d), (-d, d * 0.5)] {
+                    let w = filter.weight(dx, dy);
+                    assert!(w >= 0.0 && w <= peak, "{:?} ({}, {}): {}", filter, dx, dy, w);
+                }
+                // No crece al alejarse del centro
+                if i > 0 {
+                    let prev = r * (i - 1) as f32 / 40.0;
+                    assert!(filter.weight(d, 0.0) <= filter.weight(prev, 0.0), "{:?}", filter);
+                }
+            }
+            // Fuera del radio el peso es 0; en el borde lo es salvo para la caja
+            for d in [r * 1.001, r + 1.0, 10.0] {
+                assert_eq!(filter.weight(d, 0.0), 0.0, "{:?}", filter);
+                assert_eq!(filter.weight(0.0, -d), 0.0, "{:?}", filter);
+            }
+            if filter != PixelFilter::Box {
+                assert!(filter.weight(r, 0.0).abs() < 1e-6, "{:?}", filter);
+                assert!(filter.weight(r * 0.999, 0.0) < 0.01 * peak, "{:?}", filter);
+            }
+        }
+    }
+}
@@ -1,11 +1,331 @@
+use std::collections::HashSet;
+use nalgebra_glm as glm;
 use super::geometry::sphere::Sphere;
 use super::geometry::cube::Cube;
+use super::geometry::RayIntersect;
+use super::accel::aabb::Aabb;
+use super::accel::bvh::Bvh;
+use super::accel::voxel_grid::{unit_cell, VoxelGrid};
+use super::color::Color;
+use super::intersect::{Face, Intersect};
 use super::light::Light;
 use super::skybox::Skybox;
+use super::sky::ProceduralSky;
+use super::environment::EnvironmentMap;
+use super::tonemap::luminance;
+
+// Resolución del mapa lat-long de la luz de entorno
+const ENV_MAP_WIDTH: usize = 128;
+
+/// Forma de un objeto emisivo (para muestrear puntos sobre su superficie)
+#[derive(Clone, Copy)]
+pub enum EmitterShape {
+    /// Caja; sólo emiten las caras expuestas, en el orden de `Face`
+    Box { exposed: [bool; 6] },
+    Sphere { center: glm::Vec3, radius: f32 },
+}
+
+/// Objeto emisivo tratado como fuente de luz
+#[derive(Clone, Copy)]
+pub struct Emitter {
+    pub bounds: Aabb,
+    pub radiance: glm::Vec3, // radiancia promedio emitida
+    pub shape: EmitterShape,
+}
+
+impl Emitter {
+    /// Área de la superficie emisora
+    pub fn area(&self) -> f32 {
+        match self.shape {
+            EmitterShape::Box { exposed } => Face::ALL.iter().filter(|f| exposed[**f as usize]).map(|&f| self.face_area(f)).sum(),
+            EmitterShape::Sphere { radius, .. } => 4.0 * std::f32::consts::PI * radius * radius,
+        }
+    }
+
+    fn face_area(&self, face: Face) -> f32 {
+        let d = self.bounds.max - self.bounds.min;
+        let k = face.axis();
+        d[(k + 1) % 3] * d[(k + 2) % 3]
+    }
+
+    /// Punto uniforme (por área) sobre la superficie, a partir de 3 números en [0,1)
+    pub fn sample_surface(&self, u: [f32; 3]) -> glm::Vec3 {
+        match self.shape {
+            EmitterShape::Sphere { center, radius } => {
+                let z = 1.0 - 2.0 * u[0];
+                let r = (1.0 - z * z).max(0.0).sqrt();
+                let phi = 2.0 * std::f32::consts::PI * u[1];
+                center + radius * glm::vec3(r * phi.cos(), r * phi.sin(), z)
+            }
+            EmitterShape::Box { exposed } => {
+                let (lo, hi) = (self.bounds.min, self.bounds.max);
+                let d = hi - lo;
+                // Cara expuesta pesada por área
+                let mut pick = u[0] * self.area();
+                let mut face = Face::ALL.into_iter().rfind(|f| exposed[*f as usize]).unwrap_or(Face::PosY);
+                for f in Face::ALL {
+                    if !exposed[f as usize] { continue; }
+                    let area = self.face_area(f);
+                    if pick < area { face = f; break; }
+                    pick -= area;
+                }
+                let axis = face.axis();
+                let (a1, a2) = ((axis + 1) % 3, (axis + 2) % 3);
+                let mut p = glm::Vec3::zeros();
+                p[axis] = if face.is_positive() { hi[axis] } else { lo[axis] };
+                p[a1] = lo[a1] + u[1] * d[a1];
+                p[a2] = lo[a2] + u[2] * d[a2];
+                p
+            }
+        }
+    }
+}
+
+/// Primitiva de cualquier tipo agregada a `Scene::objects`
+pub type Object = Box<dyn RayIntersect + Send + Sync>;
 
 pub struct Scene {
     pub spheres: Vec<Sphere>,
     pub cubes: Vec<Cube>,
-    pub lights: Vec<Light>,
+    // Cualquier otra primitiva (va a la BVH con sus `bounds`); no se muestrea
+    // como emisor aunque su material emita
+    pub objects: Vec<Object>,
+    pub lights: Vec<Light>, // tras modificarlas, llamar `mark_changed`
     pub skybox: Option<Skybox>,
-}
\ No newline at end of file
+    // Cielo procedural (tiene prioridad sobre el skybox) y sus luces de sol/luna
+    sky: Option<ProceduralSky>,
+    sky_lights: Vec<Light>,
+    // Distribución del fondo para muestrear la luz de entorno (None sin skybox ni cielo)
+    env_map: Option<EnvironmentMap>,
+    // Aceleración: cubos unitarios en la grilla, el resto en una BVH
+    grid: VoxelGrid,
+    loose: Vec<Primitive>,
+    bvh: Bvh, // índices en `loose`
+    emitters: Vec<Emitter>,
+    emitter_cdf: Vec<f32>, // acumulada de la potencia de cada emisor, normalizada
+    generation: u64,       // cambia con cada modificación (invalida la acumulación)
+}
+
+/// Primitiva fuera de la grilla (índice en su lista de la escena)
+#[derive(Clone, Copy)]
+enum Primitive {
+    Cube(usize),
+    Sphere(usize),
+    Object(usize),
+}
+
+impl Scene {
+    pub fn new(spheres: Vec<Sphere>, cubes: Vec<Cube>, lights: Vec<Light>, skybox: Option<Skybox>) -> Self {
+        let mut scene = Self {
+            spheres, cubes, lights, skybox,
+            objects: Vec::new(),
+            sky: None,
+            sky_lights: Vec::new(),
+            env_map: None,
+            grid: VoxelGrid::empty(),
+            loose: Vec::new(),
+            bvh: Bvh::empty(),
+            emitters: Vec::new(),
+            emitter_cdf: Vec::new(),
+            generation: 0,
+        };
+        scene.rebuild_accel();
+        scene.rebuild_environment();
+        scene
+    }
+
+    /// Reconstruye grilla, BVH y lista de emisores; llamar después de
+    /// modificar `cubes`, `spheres` u `objects`.
+    pub fn rebuild_accel(&mut self) {
+        self.mark_changed();
+        let (grid, loose_cubes) = VoxelGrid::build(&self.cubes);
+        self.grid = grid;
+        self.loose = loose_cubes.into_iter().map(Primitive::Cube)
+            .chain((0..self.spheres.len()).map(Primitive::Sphere))
+            .chain((0..self.objects.len()).map(Primitive::Object))
+            .collect();
+        let bounds: Vec<_> = self.loose.iter().map(|&p| self.primitive(p).bounds()).collect();
+        self.bvh = Bvh::build(&bounds);
+
+        // Las caras de un bloque emisivo pegadas a un bloque opaco no iluminan
+        let opaque: HashSet<_> = self.cubes.iter().filter(|c| c.material.is_opaque()).filter_map(unit_cell).collect();
+        self.emitters.clear();
+        for c in &self.cubes {
+            if !c.material.is_emissive() { continue; }
+            let cell = unit_cell(c);
+            let exposed = Face::ALL.map(|f| {
+                let Some((x, y, z)) = cell else { return true; };
+                let s = if f.is_positive() { 1 } else { -1 };
+                let n = match f.axis() { 0 => (x + s, y, z), 1 => (x, y + s, z), _ => (x, y, z + s) };
+                !opaque.contains(&n)
+            });
+            if !exposed.contains(&true) { continue; }
+            self.emitters.push(Emitter {
+                bounds: c.bounds(),
+                radiance: c.material.average_emission(),
+                shape: EmitterShape::Box { exposed },
+            });
+        }
+        for s in &self.spheres {
+            if s.material.is_emissive() {
+                self.emitters.push(Emitter {
+                    bounds: s.bounds(),
+                    radiance: s.material.average_emission(),
+                    shape: EmitterShape::Sphere { center: s.center, radius: s.radius },
+                });
+            }
+        }
+
+        // Potencia emitida ~ radiancia × área; sin potencia, todos por igual
+        let power: Vec<f32> = self.emitters.iter().map(|e| luminance(&e.radiance).max(0.0) * e.area()).collect();
+        let total: f32 = power.iter().sum();
+        let mut acc = 0.0;
+        self.emitter_cdf = power.iter().map(|&p| {
+            acc += if total > 0.0 { p / total } else { 1.0 / power.len() as f32 };
+            acc
+        }).collect();
+    }
+
+    /// Pone (o quita) el cielo procedural y recalcula las luces de sol y luna
+    pub fn set_sky(&mut self, sky: Option<ProceduralSky>) {
+        self.sky = sky;
+        self.sky_lights = sky.map(|s| s.lights()).unwrap_or_default();
+        self.rebuild_environment();
+    }
+
+    /// Recalcula la distribución de la luz de entorno; llamar después de
+    /// cambiar `skybox`. El color plano de respaldo no ilumina.
+    pub fn rebuild_environment(&mut self) {
+        self.mark_changed();
+        self.env_map = if self.sky.is_some() || self.skybox.is_some() {
+            EnvironmentMap::build(ENV_MAP_WIDTH, ENV_MAP_WIDTH / 2, |d| self.environment(d))
+        } else {
+            None
+        };
+    }
+
+    /// Avisa que la escena cambió (p. ej. tras editar `lights`), para que el
+    /// path tracer descarte lo acumulado
+    pub fn mark_changed(&mut self) { self.generation += 1; }
+
+    /// Contador de modificaciones de la escena
+    pub fn generation(&self) -> u64 { self.generation }
+
+    /// Distribución para muestrear el entorno (ver `environment`)
+    pub fn environment_map(&self) -> Option<&EnvironmentMap> { self.env_map.as_ref() }
+
+    pub fn sky(&self) -> Option<&ProceduralSky> { self.sky.as_ref() }
+
+    /// Luces de la escena más las del cielo procedural
+    pub fn all_lights(&self) -> impl Iterator<Item = &Light> {
+        self.lights.iter().chain(&self.sky_lights)
+    }
+
+    /// Radiancia de fondo (lineal) para rayos que no golpean nada
+    pub fn background(&self, dir: glm::Vec3) -> glm::Vec3 {
+        if let Some(sky) = &self.sky {
+            return sky.radiance(dir, true);
+        }
+        match &self.skybox {
+            Some(sb) => sb.sample_linear(dir),
+            None => Color::new(135, 206, 235).to_linear(), // fallback cielo plano
+        }
+    }
+
+    /// Como `background`, pero sin el disco del sol: para rebotes que ya
+    /// muestrearon el sol como luz direccional
+    pub fn environment(&self, dir: glm::Vec3) -> glm::Vec3 {
+        match &self.sky {
+            Some(sky) => sky.radiance(dir, false),
+            None => self.background(dir),
+        }
+    }
+
+    /// Objetos emisivos de la escena
+    pub fn emitters(&self) -> &[Emitter] { &self.emitters }
+
+    /// Elige un emisor con probabilidad proporcional a su potencia, a partir de
+    /// `u` en [0,1). Devuelve el emisor y esa probabilidad.
+    pub fn pick_emitter(&self, u: f32) -> Option<(&Emitter, f32)> {
+        let i = self.emitter_cdf.partition_point(|&c| c <= u).min(self.emitters.len().checked_sub(1)?);
+        let prev = if i == 0 { 0.0 } else { self.emitter_cdf[i - 1] };
+        let pmf = self.emitter_cdf[i] - prev;
+        (pmf > 0.0).then(|| (&self.emitters[i], pmf))
+    }
+
+    fn primitive(&self, p: Primitive) -> &dyn RayIntersect {
+        match p {
+            Primitive::Cube(i) => &self.cubes[i],
+            Primitive::Sphere(i) => &self.spheres[i],
+            Primitive::Object(i) => self.objects[i].as_ref(),
+        }
+    }
+
+    /// Intersección más cercana del rayo con toda la escena.
+    pub fn closest_hit(&self, ro: &glm::Vec3, rd: &glm::Vec3) -> Intersect {
+        let mut closest = self.bvh.closest_hit(ro, rd, f32::INFINITY, |i| self.primitive(self.loose[i]).ray_intersect(ro, rd));
+
+        // La grilla sólo necesita buscar antes del mejor hit de la BVH
+        let hit = self.grid.closest_hit(&self.cubes, ro, rd, closest.distance);
+        if hit.is_intersecting { closest = hit; }
+        closest
+    }
+
+    /// Recorre los hits con distancia < tmax (sin orden garantizado entre
+    /// primitivas). `f` devuelve false para cortar el recorrido.
+    pub fn for_each_hit<F>(&self, ro: &glm::Vec3, rd: &glm::Vec3, tmax: f32, mut f: F)
+    where
+        F: FnMut(&Intersect) -> bool,
+    {
+        if !self.bvh.for_each_hit(ro, rd, tmax, |i| self.primitive(self.loose[i]).ray_intersect(ro, rd), &mut f) {
+            return;
+        }
+        self.grid.for_each_hit(&self.cubes, ro, rd, tmax, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::material::Material;
+
+    /// Cuadrado de lado 2 en el plano z = `z`, centrado en el eje Z
+    struct Quad { z: f32 }
+
+    impl RayIntersect for Quad {
+        fn ray_intersect(&self, ro: &glm::Vec3, rd: &glm::Vec3) -> Intersect {
+            let t = (self.z - ro.z) / rd.z;
+            let p = ro + rd * t;
+            if !(t > 1e-4 && t.is_finite()) || p.x.abs() > 1.0 || p.y.abs() > 1.0 { return Intersect::miss(); }
+            Intersect::hit(t, p, glm::vec3(0.0, 0.0, -rd.z.signum()), (0.0, 0.0), None, Material::default_black())
+        }
+
+        fn bounds(&self) -> Aabb { Aabb::new(glm::vec3(-1.0, -1.0, self.z), glm::vec3(1.0, 1.0, self.z)) }
+    }
+
+    #[test]
+    fn objects_join_the_bvh() {
+        let stone = Material::with_color(Color::new(90, 90, 90), 0.0, 1.0, 0.0, 0.0, 1.0);
+        let cube = Cube::new(glm::vec3(-0.5, -0.5, 4.0), glm::vec3(0.5, 0.5, 5.0), stone);
+        let mut scene = Scene::new(Vec::new(), vec![cube], Vec::new(), None);
+        let (ro, rd) = (glm::vec3(0.0, 0.0, 0.0), glm::vec3(0.0, 0.0, 1.0));
+        assert_eq!(scene.closest_hit(&ro, &rd).distance, 4.0);
+
+        // Sólo cuenta después de `rebuild_accel`
+        scene.objects.push(Box::new(Quad { z: 2.0 }));
+        scene.objects.push(Box::new(Quad { z: 7.0 }));
+        assert_eq!(scene.closest_hit(&ro, &rd).distance, 4.0);
+        let generation = scene.generation();
+        scene.rebuild_accel();
+        assert!(scene.generation() > generation);
+        assert_eq!(scene.closest_hit(&ro, &rd).distance, 2.0);
+
+        let mut hits = Vec::new();
+        scene.for_each_hit(&ro, &rd, f32::INFINITY, |h| { hits.push(h.distance); true });
+        hits.sort_by(f32::total_cmp);
+        assert_eq!(hits, [2.0, 4.0, 7.0]);
+
+        // Fuera de su caja el cuadrado no se prueba ni se golpea
+        assert!(!scene.closest_hit(&glm::vec3(3.0, 0.0, 0.0), &rd).is_intersecting);
+    }
+}
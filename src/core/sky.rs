@@ -0,0 +1,191 @@
+//! Cielo procedural de día y noche.
+//!
+//! De día usa el modelo analítico de Preetham (distribución de Perez para la
+//! luminancia y la cromaticidad, según la turbidez y la altura del sol). Al
+//! ponerse el sol se funde con un cielo nocturno con luna y estrellas que giran
+//! con la hora. Además de fondo, el cielo aporta la luz del sol/luna
+//! (direccionales) y, sin el disco solar, la luz de entorno de la escena.
+
+use std::f32::consts::PI;
+use nalgebra_glm as glm;
+use super::color::Color;
+use super::light::Light;
+use super::sampling::splitmix64;
+
+// Radiancia relativa: la luminancia de Preetham viene en kcd/m²
+const LUMINANCE_SCALE: f32 = 0.1;
+// Semiángulos aparentes (agrandados para que se vean en pantalla)
+const SUN_RADIUS: f32 = 0.02;
+const MOON_RADIUS: f32 = 0.03;
+const SUN_DISC_RADIANCE: f32 = 40.0;
+const NIGHT_SKY: [f32; 3] = [0.002, 0.003, 0.008];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProceduralSky {
+    pub time_of_day: f32, // horas en [0, 24): 6 amanecer, 12 mediodía, 18 atardecer
+    pub turbidity: f32,   // 2 = muy limpio, 10 = brumoso
+    pub latitude: f32,    // radianes; inclina el recorrido del sol (0 = pasa por el cénit)
+    pub stars: bool,
+}
+
+impl ProceduralSky {
+    pub fn new(time_of_day: f32) -> Self {
+        Self { time_of_day: time_of_day.rem_euclid(24.0), turbidity: 2.5, latitude: 30f32.to_radians(), stars: true }
+    }
+
+    /// Ángulo horario: 0 al mediodía, ±π/2 al amanecer/atardecer
+    fn hour_angle(&self) -> f32 {
+        (self.time_of_day - 12.0) / 24.0 * 2.0 * PI
+    }
+
+    /// Dirección hacia el sol (sale por +X y se pone por -X)
+    pub fn sun_direction(&self) -> glm::Vec3 {
+        let h = self.hour_angle();
+        glm::normalize(&glm::vec3(-h.sin(), h.cos() * self.latitude.cos(), h.cos() * self.latitude.sin()))
+    }
+
+    /// Dirección hacia la luna (opuesta al sol)
+    pub fn moon_direction(&self) -> glm::Vec3 {
+        -self.sun_direction()
+    }
+
+    /// 1 de día, 0 de noche, con transición en el crepúsculo
+    pub fn daylight(&self) -> f32 {
+        smoothstep(-0.12, 0.08, self.sun_direction().y)
+    }
+
+    /// Sol y luna como luces direccionales (sólo las que están sobre el horizonte)
+    pub fn lights(&self) -> Vec<Light> {
+        let mut out = Vec::new();
+        let sun = self.sun_direction();
+        if sun.y > 0.0 {
+            // El sol se enrojece y pierde fuerza cerca del horizonte
+            let warm = smoothstep(0.0, 0.35, sun.y);
+            let col = glm::lerp(&glm::vec3(1.0, 0.45, 0.2), &glm::vec3(1.0, 0.95, 0.88), warm);
+            out.push(Light::directional(-sun, Color::from_linear(&col), 1.2 * smoothstep(0.0, 0.12, sun.y)));
+        }
+        let moon = self.moon_direction();
+        if moon.y > 0.0 {
+            let col = glm::vec3(0.6, 0.7, 1.0);
+            out.push(Light::directional(-moon, Color::from_linear(&col), 0.12 * smoothstep(0.0, 0.12, moon.y)));
+        }
+        out
+    }
+
+    /// Radiancia lineal del cielo en `dir`; `with_sun` incluye el disco solar
+    /// (los integradores lo excluyen cuando el sol ya se muestrea como luz)
+    pub fn radiance(&self, dir: glm::Vec3, with_sun: bool) -> glm::Vec3 {
+        let d = glm::normalize(&dir);
+        let sun = self.sun_direction();
+        let day = self.daylight();
+
+        // Bajo el horizonte: suelo oscuro teñido por el cielo del horizonte
+        let horizon = glm::normalize(&glm::vec3(d.x, 0.0, d.z).add_scalar(1e-6));
+        let below = d.y < 0.0;
+        let view = if below { horizon } else { d };
+
+        let mut col = glm::make_vec3(&NIGHT_SKY);
+        if day > 0.0 {
+            col += self.preetham(view, sun) * day;
+        }
+        if below {
+            return col * 0.3;
+        }
+
+        // Disco del sol
+        let cos_sun = glm::dot(&d, &sun);
+        if with_sun && cos_sun > SUN_RADIUS.cos() {
+            let warm = smoothstep(0.0, 0.35, sun.y);
+            col += glm::lerp(&glm::vec3(1.0, 0.4, 0.15), &glm::vec3(1.0, 0.96, 0.9), warm) * SUN_DISC_RADIANCE;
+        }
+
+        let night = 1.0 - day;
+        if night > 0.0 {
+            // Luna
+            if glm::dot(&d, &self.moon_direction()) > MOON_RADIUS.cos() {
+                col += glm::vec3(0.75, 0.8, 0.9) * (1.5 * night);
+            }
+            // Estrellas: giran con la hora alrededor del eje del recorrido del sol
+            if self.stars {
+                col += glm::vec3(1.0, 1.0, 1.0) * (self.star_field(d) * night * smoothstep(0.0, 0.15, d.y));
+            }
+        }
+        col
+    }
+
+    // Modelo de Preetham (1999) para una dirección sobre el horizonte
+    fn preetham(&self, view: glm::Vec3, sun: glm::Vec3) -> glm::Vec3 {
+        let t = self.turbidity;
+        // El modelo no vale con el sol bajo el horizonte: lo dejamos rasante
+        let theta_s = sun.y.max(0.02).acos();
+        let theta = view.y.max(0.01).acos();
+        let sun_clamped = glm::normalize(&glm::vec3(sun.x, theta_s.cos(), sun.z));
+        let gamma = glm::dot(&view, &sun_clamped).clamp(-1.0, 1.0).acos();
+
+        let perez = |c: [f32; 5], theta: f32, gamma: f32| -> f32 {
+            (1.0 + c[0] * (c[1] / theta.cos().max(0.01)).exp())
+                * (1.0 + c[2] * (c[3] * gamma).exp() + c[4] * gamma.cos().powi(2))
+        };
+        let coef_y = [0.1787 * t - 1.4630, -0.3554 * t + 0.4275, -0.0227 * t + 5.3251, 0.1206 * t - 2.5771, -0.0670 * t + 0.3703];
+        let coef_x = [-0.0193 * t - 0.2592, -0.0665 * t + 0.0008, -0.0004 * t + 0.2125, -0.0641 * t - 0.8989, -0.0033 * t + 0.0452];
+        let coef_yc = [-0.0167 * t - 0.2608, -0.0950 * t + 0.0092, -0.0079 * t + 0.2102, -0.0441 * t - 1.6537, -0.0109 * t + 0.0529];
+
+        // Valores en el cénit
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_s);
+        let zen_y = ((4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192).max(0.0);
+        let (s, s2, s3) = (theta_s, theta_s * theta_s, theta_s * theta_s * theta_s);
+        let zen_x = t * t * (0.00166 * s3 - 0.00375 * s2 + 0.00209 * s)
+            + t * (-0.02903 * s3 + 0.06377 * s2 - 0.03202 * s + 0.00394)
+            + (0.11693 * s3 - 0.21196 * s2 + 0.06052 * s + 0.25886);
+        let zen_yc = t * t * (0.00275 * s3 - 0.00610 * s2 + 0.00317 * s)
+            + t * (-0.04214 * s3 + 0.08970 * s2 - 0.04153 * s + 0.00516)
+            + (0.15346 * s3 - 0.26756 * s2 + 0.06670 * s + 0.26688);
+
+        let lum = zen_y * perez(coef_y, theta, gamma) / perez(coef_y, 0.0, theta_s);
+        let x = zen_x * perez(coef_x, theta, gamma) / perez(coef_x, 0.0, theta_s);
+        let y = zen_yc * perez(coef_yc, theta, gamma) / perez(coef_yc, 0.0, theta_s);
+
+        xyy_to_linear_srgb(x, y, lum * LUMINANCE_SCALE)
+    }
+
+    // Puntos brillantes en una grilla sobre la esfera celeste
+    fn star_field(&self, d: glm::Vec3) -> f32 {
+        // Rotación del cielo: el mismo ángulo horario que mueve el sol
+        let h = self.hour_angle();
+        let (sin_h, cos_h) = h.sin_cos();
+        let axis = glm::vec3(0.0, -self.latitude.sin(), self.latitude.cos()); // polo celeste
+        let d = d * cos_h + glm::cross(&axis, &d) * sin_h + axis * (glm::dot(&axis, &d) * (1.0 - cos_h));
+
+        const CELLS: f32 = 180.0;
+        let p = d * CELLS;
+        let cell = p.map(|c| c.floor());
+        let key = ((cell.x as i64 as u64) << 42) ^ ((cell.y as i64 as u64) << 21) ^ (cell.z as i64 as u64);
+        let h = splitmix64(key);
+        // ~3% de las celdas tienen estrella, con posición y brillo propios
+        if h % 1000 >= 30 { return 0.0; }
+        let offset = glm::vec3(((h >> 10) & 0xff) as f32, ((h >> 18) & 0xff) as f32, ((h >> 26) & 0xff) as f32) / 255.0;
+        let center = cell + glm::vec3(0.2, 0.2, 0.2) + offset * 0.6;
+        let dist = glm::length(&(p - center));
+        let brightness = 0.3 + ((h >> 34) & 0xff) as f32 / 255.0 * 1.7;
+        brightness * (1.0 - smoothstep(0.05, 0.18, dist))
+    }
+}
+
+#[inline]
+fn smoothstep(e0: f32, e1: f32, x: f32) -> f32 {
+    let t = ((x - e0) / (e1 - e0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Cromaticidad (x, y) y luminancia Y a RGB lineal (primarios sRGB, D65)
+fn xyy_to_linear_srgb(x: f32, y: f32, lum: f32) -> glm::Vec3 {
+    if y <= 0.0 { return glm::Vec3::zeros(); }
+    let big_x = x / y * lum;
+    let big_z = (1.0 - x - y) / y * lum;
+    glm::vec3(
+        3.2406 * big_x - 1.5372 * lum - 0.4986 * big_z,
+        -0.9689 * big_x + 1.8758 * lum + 0.0415 * big_z,
+        0.0557 * big_x - 0.2040 * lum + 1.0570 * big_z,
+    )
+    .map(|c| c.max(0.0))
+}
@@ -1,19 +1,105 @@
 use nalgebra_glm as glm;
-use crate::core::{color::Color, texture::Texture};
+use crate::core::{color::Color, texture::{FilterMode, Texture, WrapMode}};
+
+/// Cómo está guardado el fondo
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum SkyboxLayout {
+    /// Seis caras en orden +X, -X, +Y, -Y, +Z, -Z
+    Cube([Texture; 6]),
+    /// Panorama lat-long (2:1): el centro mira a +Z y hacia la derecha siguen
+    /// +X y -Z, como en la fila central de una cruz; arriba es +Y
+    Equirect(Texture),
+}
 
 #[derive(Clone)]
 pub struct Skybox {
-    // Orden: +X, -X, +Y, -Y, +Z, -Z
-    pub faces: [Texture; 6],
+    pub layout: SkyboxLayout,
+    pub rotation: f32, // giro alrededor de +Y en radianes
 }
 
 impl Skybox {
+    /// Las caras usan wrap `Clamp` para que el filtrado no mezcle bordes opuestos
     pub fn new(px: Texture, nx: Texture, py: Texture, ny: Texture, pz: Texture, nz: Texture) -> Self {
-        Self { faces: [px, nx, py, ny, pz, nz] }
+        let faces = [px, nx, py, ny, pz, nz].map(|t| t.with_wrap(WrapMode::Clamp));
+        Self { layout: SkyboxLayout::Cube(faces), rotation: 0.0 }
+    }
+
+    /// Panorama equirectangular (lat-long); el wrap horizontal cierra la costura
+    pub fn equirect(tex: Texture) -> Self {
+        Self { layout: SkyboxLayout::Equirect(tex.with_wrap(WrapMode::Repeat)), rotation: 0.0 }
+    }
+
+    /// Cubemap en cruz: horizontal (4x3, fila del medio -X +Z +X -Z) o vertical
+    /// (3x4, con -Z abajo del todo y rotada 180°), +Y arriba y -Y abajo de +Z
+    pub fn cross(tex: Texture) -> Result<Self, String> {
+        let (w, h) = (tex.width, tex.height);
+        let (cols, rows) = if w * 3 == h * 4 { (4, 3) } else if w * 4 == h * 3 { (3, 4) } else {
+            return Err(format!("una cruz debe medir 4:3 o 3:4 (es {}x{})", w, h));
+        };
+        let s = w / cols;
+        let face = |c: u32, r: u32| tex.cropped(c * s, r * s, s, s);
+        // Las caras laterales del cubo se guardan con +Y en la fila de abajo
+        // (ver `face_uv`); techo y piso ya coinciden con la cruz
+        let side = |c: u32, r: u32| face(c, r).flipped_vertical();
+        let nz = if rows == 3 { side(3, 1) } else { face(1, 3).rotated_180().flipped_vertical() };
+        Ok(Self::new(side(2, 1), side(0, 1), face(1, 0), face(1, 2), side(1, 1), nz))
+    }
+
+    /// Carga un fondo de un solo archivo (LDR o .hdr/.exr) según su proporción:
+    /// 2:1 equirectangular, 4:3 o 3:4 cruz. Se filtra bilinealmente.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let tex = Texture::try_load(path)
+            .map_err(|e| format!("no pude abrir {}: {}", path, e))?
+            .with_filter(FilterMode::Bilinear);
+        Self::from_image(tex)
+    }
+
+    /// Como `load`, con la textura ya cargada
+    pub fn from_image(tex: Texture) -> Result<Self, String> {
+        if tex.width == tex.height * 2 {
+            Ok(Self::equirect(tex))
+        } else {
+            Self::cross(tex)
+        }
+    }
+
+    pub fn with_rotation(mut self, radians: f32) -> Self {
+        self.rotation = radians;
+        self
     }
 
-    /// Mapea dirección en mundo a (cara, uv) y samplea la textura correspondiente.
+    /// Color (sRGB, texel más cercano) en la dirección `dir`
     pub fn sample(&self, dir: glm::Vec3) -> Color {
+        let (tex, uv) = self.lookup(dir);
+        tex.sample(uv)
+    }
+
+    /// Radiancia lineal en la dirección `dir`, con el filtro de la textura
+    pub fn sample_linear(&self, dir: glm::Vec3) -> glm::Vec3 {
+        let (tex, uv) = self.lookup(dir);
+        tex.sample_linear(uv)
+    }
+
+    // Textura y uv para una dirección en mundo (ya descontada la rotación)
+    fn lookup(&self, dir: glm::Vec3) -> (&Texture, (f32, f32)) {
+        let d = glm::rotate_y_vec3(&glm::normalize(&dir), -self.rotation);
+        match &self.layout {
+            SkyboxLayout::Cube(faces) => {
+                let (face, uv) = Self::face_uv(d);
+                (&faces[face], uv)
+            }
+            SkyboxLayout::Equirect(tex) => {
+                use std::f32::consts::PI;
+                let u = (d.x.atan2(d.z) / (2.0 * PI) + 0.5).rem_euclid(1.0);
+                let v = d.y.clamp(-1.0, 1.0).asin() / PI + 0.5;
+                (tex, (u, v))
+            }
+        }
+    }
+
+    /// Mapea dirección en mundo a (cara, uv).
+    fn face_uv(dir: glm::Vec3) -> (usize, (f32, f32)) {
         let d = glm::normalize(&dir);
         let x = d.x; let y = d.y; let z = d.z;
         let ax = x.abs(); let ay = y.abs(); let az = z.abs();
@@ -53,6 +139,102 @@ impl Skybox {
         // Si tu sample usa t=0 arriba, invierte:
         t = 1.0 - t;
 
-        self.faces[face].sample((s, t))
+        (face, (s, t))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const S: u32 = 4; // lado de cada cara en texels
+
+    // Cruz de `cols`×`rows` caras: el texel (x, y) de la celda (c, r) tiene
+    // r = 10 + 20·celda y g = 100 + 50·cuadrante (0 arriba-izq., 1 arriba-der.,
+    // 2 abajo-izq., 3 abajo-der.); fuera de las caras queda negro
+    fn cross(cols: u32, rows: u32) -> Texture {
+        let (w, h) = (cols * S, rows * S);
+        let mut data = Vec::with_capacity((w * h * 4) as usize);
+        for y in 0..h {
+            for x in 0..w {
+                let cell = (y / S) * cols + x / S;
+                let quadrant = (y % S >= S / 2) as u32 * 2 + (x % S >= S / 2) as u32;
+                data.extend_from_slice(&[(10 + 20 * cell) as u8, (100 + 50 * quadrant) as u8, 0, 255]);
+            }
+        }
+        Texture::from_rgba8(w, h, data)
+    }
+
+    // (fila·10 + columna de la celda, cuadrante) que ve `dir`
+    fn seen(sky: &Skybox, cols: u32, dir: [f32; 3]) -> (u32, u32) {
+        let c = sky.sample(glm::vec3(dir[0], dir[1], dir[2]));
+        let cell = (c.r as u32 - 10) / 20;
+        (cell / cols * 10 + cell % cols, (c.g as u32 - 100) / 50)
+    }
+
+    // Vista desde adentro: mirando a +Z, +X queda a la derecha y +Y arriba.
+    // Cada dirección apunta hacia una esquina de su cara: (celda, cuadrante)
+    const SIDES: [([f32; 3], (u32, u32)); 5] = [
+        ([0.4, 0.5, 1.0], (11, 1)),   // +Z: arriba a la derecha (hacia +X)
+        ([1.0, 0.5, 0.4], (12, 0)),   // +X: su borde izquierdo toca +Z
+        ([-1.0, 0.5, 0.4], (10, 1)),  // -X: su borde derecho toca +Z
+        ([0.4, 1.0, 0.5], (1, 3)),    // +Y: su borde de abajo toca +Z
+        ([0.4, -1.0, 0.5], (21, 1)),  // -Y: su borde de arriba toca +Z
+    ];
+
+    #[test]
+    fn horizontal_cross() {
+        let sky = Skybox::cross(cross(4, 3)).unwrap();
+        for (dir, want) in SIDES {
+            assert_eq!(seen(&sky, 4, dir), want, "{:?}", dir);
+        }
+        // -Z, al final de la fila del medio: su borde izquierdo toca +X
+        assert_eq!(seen(&sky, 4, [0.4, 0.5, -1.0]), (13, 0));
+        assert_eq!(seen(&sky, 4, [-0.4, -0.5, -1.0]), (13, 3));
+    }
+
+    #[test]
+    fn vertical_cross() {
+        let sky = Skybox::cross(cross(3, 4)).unwrap();
+        for (dir, want) in SIDES {
+            assert_eq!(seen(&sky, 3, dir), want, "{:?}", dir);
+        }
+        // -Z cuelga debajo de -Y, girada 180°: su borde de arriba toca -Y
+        assert_eq!(seen(&sky, 3, [0.4, 0.5, -1.0]), (31, 3));
+        assert_eq!(seen(&sky, 3, [-0.4, -0.5, -1.0]), (31, 0));
+    }
+
+    #[test]
+    fn equirect_lookup() {
+        // 4×2: columna = r / 20, fila = g / 100
+        let mut data = Vec::new();
+        for y in 0..2u8 {
+            for x in 0..4u8 {
+                data.extend_from_slice(&[x * 20, y * 100, 0, 255]);
+            }
+        }
+        let sky = Skybox::from_image(Texture::from_rgba8(4, 2, data)).unwrap();
+        let cell = |sky: &Skybox, d: [f32; 3]| {
+            let c = sky.sample(glm::vec3(d[0], d[1], d[2]));
+            (c.r / 20, c.g / 100)
+        };
+        // El centro mira a +Z; a la derecha siguen +X y -Z; arriba es +Y
+        assert_eq!(cell(&sky, [0.1, 0.5, 1.0]), (2, 0));
+        assert_eq!(cell(&sky, [1.0, 0.5, -0.1]), (3, 0));
+        assert_eq!(cell(&sky, [-0.1, -0.5, -1.0]), (0, 1));
+        assert_eq!(cell(&sky, [-1.0, -0.5, 0.1]), (1, 1));
+        // Girado 90°, +X muestra lo que antes estaba en +Z
+        let turned = sky.with_rotation(std::f32::consts::FRAC_PI_2);
+        assert_eq!(cell(&turned, [1.0, 0.5, -0.1]), (2, 0));
+    }
+
+    #[test]
+    fn rejects_other_aspect_ratios() {
+        for (w, h) in [(5, 3), (8, 8), (12, 8)] {
+            let tex = Texture::from_rgba8(w, h, vec![0; (w * h * 4) as usize]);
+            let msg = format!("una cruz debe medir 4:3 o 3:4 (es {}x{})", w, h);
+            assert_eq!(Skybox::cross(tex.clone()).err(), Some(msg.clone()));
+            assert_eq!(Skybox::from_image(tex).err(), Some(msg));
+        }
+    }
+}
@@ -1,41 +1,363 @@
+use std::sync::Arc;
+use nalgebra_glm as glm;
 use crate::core::color::Color;
 
+/// Filtro de muestreo de la textura
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterMode {
+    /// Texel más cercano (pixel art nítido de cerca)
+    Nearest,
+    /// Interpolación entre los 4 texels vecinos
+    Bilinear,
+    /// Bilineal en los dos niveles de mip más cercanos, interpolados según el LOD
+    Trilinear,
+}
+
+/// Qué hacer con UV fuera de [0,1]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WrapMode {
+    Repeat,
+    Clamp,
+    Mirror,
+}
+
 #[derive(Clone)]
 pub struct Texture {
     pub width: u32,
     pub height: u32,
-    pub data: Vec<u8>, // RGBA8
+    pub data: Vec<u8>, // RGBA8 (en texturas HDR, la versión recortada a [0,1])
+    pub wrap: WrapMode,
+    // Privado: `mips` tiene que acompañar al filtro (ver `with_filter`)
+    filter: FilterMode,
+    // Radiancia lineal sin recortar de las texturas HDR (.hdr/.exr)
+    linear: Option<Arc<[glm::Vec3]>>,
+    // Cadena de mips en RGB lineal (nivel 0 = `data`); vacía con `Nearest`.
+    // Compartida: los materiales (y sus texturas) se clonan en cada hit
+    mips: Arc<[MipLevel]>,
+}
+
+#[derive(Clone)]
+struct MipLevel {
+    width: u32,
+    height: u32,
+    texels: Vec<glm::Vec3>,
 }
 
 impl Texture {
     pub fn load(path: &str) -> Self {
-        let img = image::open(path).expect(&format!("No pude abrir textura: {}", path));
+        Self::try_load(path).unwrap_or_else(|_| panic!("No pude abrir textura: {}", path))
+    }
+
+    /// Como `load`, pero devuelve el error en vez de abortar. Las imágenes en
+    /// punto flotante (Radiance .hdr, OpenEXR) conservan su rango completo.
+    pub fn try_load(path: &str) -> image::ImageResult<Self> {
+        use image::DynamicImage;
+        let img = image::open(path)?;
+        if let DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) = img {
+            let rgb = img.to_rgb32f();
+            let (w, h) = rgb.dimensions();
+            let texels = rgb.pixels().map(|p| glm::vec3(p[0], p[1], p[2])).collect();
+            return Ok(Self::from_linear(w, h, texels));
+        }
         let rgba = img.to_rgba8();
         let (w, h) = rgba.dimensions();
-        Self { width: w, height: h, data: rgba.into_raw() }
+        Ok(Self::from_rgba8(w, h, rgba.into_raw()))
     }
 
-    /// UV en [0,1] con wrap (nearest neighbor)
-    pub fn sample(&self, uv: (f32, f32)) -> Color {
-        let (mut u, mut v) = uv;
-        // wrap
-        u = u - u.floor();
-        v = v - v.floor();
+    /// Textura desde pixeles RGBA8 (sRGB), nearest + repeat
+    pub fn from_rgba8(width: u32, height: u32, data: Vec<u8>) -> Self {
+        Self { width, height, data, filter: FilterMode::Nearest, wrap: WrapMode::Repeat, linear: None, mips: Arc::from([]) }
+    }
+
+    /// Textura HDR desde radiancia lineal (fila 0 arriba), nearest + repeat
+    pub fn from_linear(width: u32, height: u32, texels: Vec<glm::Vec3>) -> Self {
+        let mut data = Vec::with_capacity(texels.len() * 4);
+        for t in &texels {
+            let c = Color::from_linear(t);
+            data.extend_from_slice(&[c.r, c.g, c.b, 255]);
+        }
+        Self { linear: Some(texels.into()), ..Self::from_rgba8(width, height, data) }
+    }
 
-        // Y invertida (v=0 arriba)
-        let x = (u * (self.width as f32 - 1.0)).round().clamp(0.0, self.width as f32 - 1.0) as u32;
-        let y = ((1.0 - v) * (self.height as f32 - 1.0)).round().clamp(0.0, self.height as f32 - 1.0) as u32;
+    /// ¿Guarda radiancia fuera de [0,1]?
+    pub fn is_hdr(&self) -> bool { self.linear.is_some() }
 
-        let idx = ((y * self.width + x) * 4) as usize;
+    /// Cambia el filtro; con `Bilinear`/`Trilinear` precalcula la cadena de mips.
+    /// Si se modifica `data` después, hay que volver a llamarlo.
+    pub fn with_filter(mut self, filter: FilterMode) -> Self {
+        self.filter = filter;
+        self.mips = match filter {
+            FilterMode::Nearest => Arc::from([]),
+            FilterMode::Bilinear => Arc::from([self.base_level()]),
+            FilterMode::Trilinear => build_mip_chain(self.base_level()).into(),
+        };
+        self
+    }
+
+    pub fn filter(&self) -> FilterMode { self.filter }
+
+    pub fn with_wrap(mut self, wrap: WrapMode) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Texel más cercano según el modo de wrap (ignora el filtro)
+    pub fn sample(&self, uv: (f32, f32)) -> Color {
+        let idx = self.texel_index(uv);
         Color::new(self.data[idx], self.data[idx + 1], self.data[idx + 2])
     }
 
+    /// Opacidad en [0,1] del texel (mismo muestreo que `sample`)
+    pub fn alpha(&self, uv: (f32, f32)) -> f32 {
+        self.data[self.texel_index(uv) + 3] as f32 / 255.0
+    }
+
+    /// Color en RGB lineal usando el filtro de la textura, en el nivel 0
+    pub fn sample_linear(&self, uv: (f32, f32)) -> glm::Vec3 {
+        self.sample_lod(uv, 0.0)
+    }
+
+    /// Color en RGB lineal con el nivel de detalle `lod` (0 = resolución completa).
+    /// Sólo `Trilinear` usa niveles por encima de 0.
+    pub fn sample_lod(&self, uv: (f32, f32), lod: f32) -> glm::Vec3 {
+        match self.filter {
+            FilterMode::Nearest => match &self.linear {
+                Some(texels) => texels[self.texel_index(uv) / 4],
+                None => self.sample(uv).to_linear(),
+            },
+            FilterMode::Bilinear => self.bilinear(0, uv),
+            FilterMode::Trilinear => {
+                let max = (self.mips.len() - 1) as f32;
+                let lod = lod.clamp(0.0, max);
+                let l0 = lod.floor();
+                let f = lod - l0;
+                let a = self.bilinear(l0 as usize, uv);
+                if f <= 0.0 { return a; }
+                glm::lerp(&a, &self.bilinear(l0 as usize + 1, uv), f)
+            }
+        }
+    }
+
+    /// LOD para una huella de `footprint` unidades de UV (≈ unidades de mundo
+    /// en un bloque de lado 1) cubierta por un pixel
+    pub fn lod_for_footprint(&self, footprint: f32) -> f32 {
+        (footprint * self.width.max(self.height) as f32).max(1e-8).log2()
+    }
+
+    /// Color promedio de la textura (lineal)
+    pub fn average_linear(&self) -> glm::Vec3 {
+        let n = (self.width as u64 * self.height as u64).max(1) as f32;
+        if let Some(texels) = &self.linear {
+            return texels.iter().sum::<glm::Vec3>() / n;
+        }
+        let mut sum = glm::Vec3::zeros();
+        for px in self.data.chunks_exact(4) {
+            sum += Color::new(px[0], px[1], px[2]).to_linear();
+        }
+        sum / n
+    }
+
+    /// Texels RGBA8 codificados como PNG (las HDR se exportan recortadas a [0,1])
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        use image::ImageEncoder;
+        let mut out = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut out)
+            .write_image(&self.data, self.width, self.height, image::ExtendedColorType::Rgba8)
+            .map_err(|e| format!("no pude codificar la textura: {}", e))?;
+        Ok(out)
+    }
+
     pub fn rotated_180(self) -> Self {
-        use image::{imageops, RgbaImage};
-        let img = RgbaImage::from_raw(self.width, self.height, self.data)
-            .expect("Texture buffer inválido");
-        let rot = imageops::rotate180(&img);
-        let (w, h) = rot.dimensions();
-        Self { width: w, height: h, data: rot.into_raw() }
-    }
-}
\ No newline at end of file
+        let (w, h) = (self.width, self.height);
+        self.remap(w, h, |x, y| (w - 1 - x, h - 1 - y))
+    }
+
+    /// Espejo vertical (fila de arriba abajo)
+    pub fn flipped_vertical(self) -> Self {
+        let (w, h) = (self.width, self.height);
+        self.remap(w, h, |x, y| (x, h - 1 - y))
+    }
+
+    /// Región `w`×`h` con esquina superior izquierda en (x0, y0)
+    pub fn cropped(&self, x0: u32, y0: u32, w: u32, h: u32) -> Self {
+        self.remap(w, h, |x, y| (x0 + x, y0 + y))
+    }
+
+    // Nueva textura de `w`×`h` cuyo texel (x, y) sale del texel `from(x, y)` de
+    // ésta; conserva filtro, wrap y el rango HDR
+    fn remap<F>(&self, w: u32, h: u32, from: F) -> Self
+    where
+        F: Fn(u32, u32) -> (u32, u32),
+    {
+        let src = |x: u32, y: u32| {
+            let (sx, sy) = from(x, y);
+            (sy * self.width + sx) as usize
+        };
+        let tex = match &self.linear {
+            Some(texels) => {
+                let out = (0..h).flat_map(|y| (0..w).map(move |x| (x, y))).map(|(x, y)| texels[src(x, y)]).collect();
+                Self::from_linear(w, h, out)
+            }
+            None => {
+                let mut out = Vec::with_capacity((w * h * 4) as usize);
+                for y in 0..h {
+                    for x in 0..w {
+                        let i = src(x, y) * 4;
+                        out.extend_from_slice(&self.data[i..i + 4]);
+                    }
+                }
+                Self::from_rgba8(w, h, out)
+            }
+        };
+        tex.with_wrap(self.wrap).with_filter(self.filter)
+    }
+
+    // Índice del primer byte del texel más cercano
+    fn texel_index(&self, uv: (f32, f32)) -> usize {
+        let u = wrap_unit(uv.0, self.wrap);
+        let v = wrap_unit(uv.1, self.wrap);
+
+        // Y invertida (v=0 arriba); el texel i cubre [i, i+1)/n, con centro en
+        // (i + 0.5)/n como en `bilinear`
+        let x = (u * self.width as f32).floor().clamp(0.0, self.width as f32 - 1.0) as u32;
+        let y = ((1.0 - v) * self.height as f32).floor().clamp(0.0, self.height as f32 - 1.0) as u32;
+
+        ((y * self.width + x) * 4) as usize
+    }
+
+    fn bilinear(&self, level: usize, uv: (f32, f32)) -> glm::Vec3 {
+        let mip = &self.mips[level];
+        // Centros de texel en (i + 0.5); Y invertida como en `sample`
+        let x = uv.0 * mip.width as f32 - 0.5;
+        let y = (1.0 - uv.1) * mip.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let texel = |xi: i64, yi: i64| -> glm::Vec3 {
+            let xi = wrap_index(xi, mip.width, self.wrap);
+            let yi = wrap_index(yi, mip.height, self.wrap);
+            mip.texels[(yi * mip.width + xi) as usize]
+        };
+        let top = glm::lerp(&texel(x0, y0), &texel(x0 + 1, y0), fx);
+        let bottom = glm::lerp(&texel(x0, y0 + 1), &texel(x0 + 1, y0 + 1), fx);
+        glm::lerp(&top, &bottom, fy)
+    }
+
+    fn base_level(&self) -> MipLevel {
+        if let Some(texels) = &self.linear {
+            return MipLevel { width: self.width, height: self.height, texels: texels.to_vec() };
+        }
+        let texels = self.data.chunks_exact(4).map(|px| Color::new(px[0], px[1], px[2]).to_linear()).collect();
+        MipLevel { width: self.width, height: self.height, texels }
+    }
+}
+
+/// Niveles sucesivos promediando bloques de 2x2 (en lineal) hasta 1x1
+fn build_mip_chain(base: MipLevel) -> Vec<MipLevel> {
+    let mut chain = vec![base];
+    loop {
+        let prev = chain.last().unwrap();
+        if prev.width == 1 && prev.height == 1 { break; }
+        let w = (prev.width / 2).max(1);
+        let h = (prev.height / 2).max(1);
+        let mut texels = Vec::with_capacity((w * h) as usize);
+        for y in 0..h {
+            for x in 0..w {
+                let mut sum = glm::Vec3::zeros();
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let sx = (2 * x + dx).min(prev.width - 1);
+                    let sy = (2 * y + dy).min(prev.height - 1);
+                    sum += prev.texels[(sy * prev.width + sx) as usize];
+                }
+                texels.push(sum * 0.25);
+            }
+        }
+        chain.push(MipLevel { width: w, height: h, texels });
+    }
+    chain
+}
+
+/// Coordenada continua llevada a [0,1] según el modo de wrap
+fn wrap_unit(t: f32, wrap: WrapMode) -> f32 {
+    match wrap {
+        WrapMode::Repeat => t - t.floor(),
+        WrapMode::Clamp => t.clamp(0.0, 1.0),
+        WrapMode::Mirror => {
+            let m = t.rem_euclid(2.0);
+            if m > 1.0 { 2.0 - m } else { m }
+        }
+    }
+}
+
+/// Índice de texel entero llevado a [0, n) según el modo de wrap
+fn wrap_index(i: i64, n: u32, wrap: WrapMode) -> u32 {
+    let n = n as i64;
+    let i = match wrap {
+        WrapMode::Repeat => i.rem_euclid(n),
+        WrapMode::Clamp => i.clamp(0, n - 1),
+        WrapMode::Mirror => {
+            let m = i.rem_euclid(2 * n);
+            if m < n { m } else { 2 * n - 1 - m }
+        }
+    };
+    i as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 4×2 con un color distinto por texel (en lineal son todos diferentes)
+    fn checker() -> Texture {
+        let mut data = Vec::new();
+        for i in 0..8u8 {
+            data.extend_from_slice(&[i * 30, 255 - i * 20, (i as u16 * 77 % 255) as u8, 255]);
+        }
+        Texture::from_rgba8(4, 2, data)
+    }
+
+    #[test]
+    fn bilinear_matches_nearest_at_texel_centres() {
+        let nearest = checker();
+        for filter in [FilterMode::Bilinear, FilterMode::Trilinear] {
+            for wrap in [WrapMode::Repeat, WrapMode::Clamp, WrapMode::Mirror] {
+                let nearest = nearest.clone().with_wrap(wrap);
+                let filtered = nearest.clone().with_filter(filter);
+                for y in 0..2 {
+                    for x in 0..4 {
+                        let uv = ((x as f32 + 0.5) / 4.0, 1.0 - (y as f32 + 0.5) / 2.0);
+                        let a = nearest.sample_linear(uv);
+                        let b = filtered.sample_linear(uv);
+                        assert!((a - b).abs().max() < 1e-5, "{:?} {:?} texel ({}, {}): {:?} vs {:?}", filter, wrap, x, y, a, b);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_covers_whole_texels() {
+        let rgb = |tex: &Texture, uv| { let c = tex.sample(uv); (c.r, c.g, c.b) };
+        let tex = checker();
+        // Justo a cada lado del borde entre los texels 0 y 1 de la fila de arriba
+        assert_eq!(rgb(&tex, (0.249, 0.9)), rgb(&tex, (0.01, 0.9)));
+        assert_eq!(rgb(&tex, (0.251, 0.9)), rgb(&tex, (0.49, 0.9)));
+        assert_ne!(rgb(&tex, (0.249, 0.9)), rgb(&tex, (0.251, 0.9)));
+        // v = 0 es la fila de abajo; u = 1 con clamp es la última columna
+        let clamp = tex.clone().with_wrap(WrapMode::Clamp);
+        assert_eq!(rgb(&clamp, (1.0, 0.0)), rgb(&tex, (0.9, 0.1)));
+    }
+
+    #[test]
+    fn filter_is_set_with_its_mips() {
+        let tex = checker();
+        assert_eq!(tex.filter(), FilterMode::Nearest);
+        let tri = tex.with_filter(FilterMode::Trilinear);
+        assert_eq!(tri.filter(), FilterMode::Trilinear);
+        // 4×2 → 2×1 → 1×1: el último nivel es el promedio
+        let avg = tri.average_linear();
+        assert!((tri.sample_lod((0.3, 0.3), 10.0) - avg).abs().max() < 1e-5);
+        assert_eq!(tri.clone().with_filter(FilterMode::Nearest).filter(), FilterMode::Nearest);
+    }
+}
@@ -0,0 +1,36 @@
+use nalgebra_glm as glm;
+
+/// Operador para llevar radiancia HDR a [0,1] antes de codificar a sRGB
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToneMapper {
+    /// Sólo exposición y recorte
+    Exposure,
+    /// Reinhard sobre la luminancia (conserva el tono)
+    Reinhard,
+    /// Aproximación filmic de ACES (Narkowicz)
+    Aces,
+}
+
+impl ToneMapper {
+    /// Aplica exposición (multiplicador lineal) y el operador
+    pub fn apply(self, hdr: glm::Vec3, exposure: f32) -> glm::Vec3 {
+        let c = hdr * exposure;
+        match self {
+            ToneMapper::Exposure => c,
+            ToneMapper::Reinhard => {
+                let l = luminance(&c);
+                if l <= 0.0 { c } else { c * (1.0 / (1.0 + l)) }
+            }
+            ToneMapper::Aces => c.map(|x| {
+                let x = x.max(0.0);
+                (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+            }),
+        }
+    }
+}
+
+/// Luminancia relativa (Rec. 709)
+#[inline]
+pub fn luminance(c: &glm::Vec3) -> f32 {
+    0.2126 * c.x + 0.7152 * c.y + 0.0722 * c.z
+}
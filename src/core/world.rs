@@ -1,32 +1,339 @@
-use std::collections::HashMap;
+//! Mundo de bloques guardado en chunks de 16³ con paleta (ver `chunk`): sólo
+//! existen los chunks con algún bloque, y recorrer el mundo sigue el orden
+//! espacial (chunk a chunk, y dentro de cada uno por Y, Z, X).
+
+use std::collections::BTreeMap;
+use std::fmt;
 use nalgebra_glm as glm;
+use crate::core::chunk::{Chunk, CHUNK_SHIFT, CHUNK_SIZE};
 use crate::core::geometry::cube::Cube;
+use crate::core::history::History;
 use crate::core::material_registry::MaterialRegistry;
-use crate::core::block::{Block, BlockKind};
+use crate::core::block::BlockKind;
+use crate::core::intersect::Face;
+
+type Pos = (i32, i32, i32);
+
+/// Coordenada de chunk; los campos en este orden hacen que el `BTreeMap`
+/// recorra por (Y, Z, X)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct ChunkKey {
+    y: i32,
+    z: i32,
+    x: i32,
+}
+
+impl ChunkKey {
+    fn pos(self) -> Pos { (self.x, self.y, self.z) }
+}
 
 pub struct World {
-    blocks: HashMap<(i32, i32, i32), Block>,
+    chunks: BTreeMap<ChunkKey, Chunk>,
+    len: usize,
+    pub(crate) history: Option<History>, // ver `history`
+}
+
+/// Uso de memoria de un `World` (ver `World::stats`)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WorldStats {
+    pub blocks: usize,
+    pub chunks: usize,
+    pub palette_entries: usize, // suma de las paletas de todos los chunks
+    pub bytes: usize,           // estimado: chunks, paletas, celdas y tabla de chunks
+    pub hashmap_bytes: usize,   // lo que ocuparían los mismos bloques en un HashMap por posición
+}
+
+impl fmt::Display for WorldStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bloques en {} chunks ({} entradas de paleta), {:.1} KiB (HashMap: {:.1} KiB)",
+            self.blocks, self.chunks, self.palette_entries, self.bytes as f64 / 1024.0, self.hashmap_bytes as f64 / 1024.0)
+    }
+}
+
+impl Default for World {
+    fn default() -> Self { Self::new() }
 }
 
 impl World {
-    pub fn new() -> Self { Self { blocks: HashMap::new() } }
+    pub fn new() -> Self { Self { chunks: BTreeMap::new(), len: 0, history: None } }
+
+    /// Chunk que contiene el bloque y posición dentro de él
+    #[inline]
+    fn split(x: i32, y: i32, z: i32) -> (ChunkKey, usize) {
+        let m = CHUNK_SIZE - 1;
+        (ChunkKey { x: x >> CHUNK_SHIFT, y: y >> CHUNK_SHIFT, z: z >> CHUNK_SHIFT }, Chunk::index(x & m, y & m, z & m))
+    }
 
     #[inline]
     pub fn set(&mut self, x: i32, y: i32, z: i32, kind: BlockKind) {
-        self.blocks.insert((x, y, z), Block::new(kind));
+        let (key, i) = Self::split(x, y, z);
+        let old = self.chunks.entry(key).or_default().set(i, Some(kind));
+        if old.is_none() { self.len += 1; }
+        self.record((x, y, z), old, Some(kind));
+    }
+
+    /// Quita el bloque en (x, y, z) y devuelve su tipo
+    pub fn remove(&mut self, x: i32, y: i32, z: i32) -> Option<BlockKind> {
+        let (key, i) = Self::split(x, y, z);
+        let chunk = self.chunks.get_mut(&key)?;
+        let old = chunk.set(i, None);
+        if old.is_some() {
+            self.len -= 1;
+            // Los chunks vacíos no se guardan
+            if chunk.is_empty() { self.chunks.remove(&key); }
+            self.record((x, y, z), old, None);
+        }
+        old
+    }
+
+    /// Tipo del bloque en (x, y, z), si hay uno
+    #[inline]
+    pub fn get(&self, x: i32, y: i32, z: i32) -> Option<BlockKind> {
+        let (key, i) = Self::split(x, y, z);
+        self.chunks.get(&key).and_then(|c| c.get(i))
+    }
+
+    /// Los 6 vecinos de (x, y, z) en el orden de `Face`; los que caen en el
+    /// mismo chunk no vuelven a buscarlo
+    pub fn neighbours(&self, x: i32, y: i32, z: i32) -> [Option<BlockKind>; 6] {
+        let (key, _) = Self::split(x, y, z);
+        let home = self.chunks.get(&key);
+        Face::ALL.map(|face| {
+            let mut p = [x, y, z];
+            p[face.axis()] += if face.is_positive() { 1 } else { -1 };
+            let (k, i) = Self::split(p[0], p[1], p[2]);
+            if k == key { home.and_then(|c| c.get(i)) } else { self.chunks.get(&k).and_then(|c| c.get(i)) }
+        })
+    }
+
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    pub fn clear(&mut self) {
+        if self.history.is_some() {
+            let removed: Vec<_> = self.blocks().collect();
+            self.batch(|w| for (p, k) in removed { w.record(p, Some(k), None); });
+        }
+        self.chunks.clear();
+        self.len = 0;
+    }
+
+    /// Posición y tipo de cada bloque en orden espacial
+    pub fn blocks(&self) -> impl Iterator<Item = (Pos, BlockKind)> + '_ {
+        self.chunks().flat_map(|(key, chunk)| {
+            chunk.iter().map(move |(i, kind)| (Self::world_pos(key, i), kind))
+        })
+    }
+
+    /// Bloques dentro de la caja `min`..=`max` (en orden espacial); sólo visita
+    /// los chunks que la tocan
+    pub fn blocks_in(&self, min: Pos, max: Pos) -> impl Iterator<Item = (Pos, BlockKind)> + '_ {
+        let lo = (min.0.min(max.0), min.1.min(max.1), min.2.min(max.2));
+        let hi = (min.0.max(max.0), min.1.max(max.1), min.2.max(max.2));
+        let inside = move |p: &Pos| p.0 >= lo.0 && p.0 <= hi.0 && p.1 >= lo.1 && p.1 <= hi.1 && p.2 >= lo.2 && p.2 <= hi.2;
+        let (clo, chi) = (Self::split(lo.0, lo.1, lo.2).0, Self::split(hi.0, hi.1, hi.2).0);
+        // Las claves van por Y primero: el rango acota las capas de chunks
+        let first = ChunkKey { y: clo.y, z: i32::MIN, x: i32::MIN };
+        let last = ChunkKey { y: chi.y, z: i32::MAX, x: i32::MAX };
+        self.chunks.range(first..=last)
+            .filter(move |(k, _)| k.x >= clo.x && k.x <= chi.x && k.z >= clo.z && k.z <= chi.z)
+            .flat_map(move |(&key, chunk)| {
+                chunk.iter().map(move |(i, kind)| (Self::world_pos(key.pos(), i), kind)).filter(move |(p, _)| inside(p))
+            })
+    }
+
+    /// Esquinas mínima y máxima (inclusive) de los bloques; None si está vacío
+    pub fn bounds(&self) -> Option<(Pos, Pos)> {
+        self.blocks().fold(None, |acc, (p, _)| Some(match acc {
+            None => (p, p),
+            Some((lo, hi)) => ((lo.0.min(p.0), lo.1.min(p.1), lo.2.min(p.2)), (hi.0.max(p.0), hi.1.max(p.1), hi.2.max(p.2))),
+        }))
+    }
+
+    /// Chunks ocupados con su coordenada de chunk, ordenados por (Y, Z, X)
+    pub fn chunks(&self) -> impl Iterator<Item = (Pos, &Chunk)> + '_ {
+        self.chunks.iter().map(|(k, c)| (k.pos(), c))
+    }
+
+    pub fn stats(&self) -> WorldStats {
+        // Los nodos del árbol guardan claves y chunks (se ignora su relleno)
+        let table = self.chunks.len() * std::mem::size_of::<(ChunkKey, Chunk)>();
+        let heap: usize = self.chunks.values().map(Chunk::heap_bytes).sum();
+        WorldStats {
+            blocks: self.len,
+            chunks: self.chunks.len(),
+            palette_entries: self.chunks.values().map(|c| c.palette().len()).sum(),
+            bytes: std::mem::size_of::<Self>() + table + heap,
+            // Una entrada (posición, bloque) y el byte de control por bloque, con
+            // la tabla al 7/8 de carga
+            hashmap_bytes: self.len * 8 / 7 * (std::mem::size_of::<(Pos, crate::core::block::Block)>() + 1),
+        }
     }
 
-    /// Transforma los bloques a Cubes “de mundo” (1 unidad por bloque)
+    #[inline]
+    fn world_pos(key: Pos, i: usize) -> Pos {
+        let (x, y, z) = Chunk::local(i);
+        ((key.0 << CHUNK_SHIFT) + x, (key.1 << CHUNK_SHIFT) + y, (key.2 << CHUNK_SHIFT) + z)
+    }
+
+    /// Transforma los bloques a Cubes “de mundo” (1 unidad por bloque), con la
+    /// AO por vértice calculada a partir de los vecinos
     pub fn bake(&self, reg: &MaterialRegistry) -> Vec<Cube> {
-        let mut out = Vec::with_capacity(self.blocks.len());
-        for (&(x, y, z), b) in &self.blocks {
-            if b.kind == BlockKind::Air { continue; }
-            if let Some(mat) = reg.get(b.kind) {
+        let mut out = Vec::with_capacity(self.len);
+        for ((x, y, z), kind) in self.blocks() {
+            if kind == BlockKind::Air { continue; }
+            if let Some(mat) = reg.get(kind) {
                 let min = glm::vec3(x as f32, y as f32, z as f32);
                 let max = min + glm::vec3(1.0, 1.0, 1.0);
-                out.push(Cube::new(min, max, mat.clone()));
+                let mut cube = Cube::new(min, max, mat.clone());
+                cube.vertex_ao = Some(self.vertex_ao([x, y, z], reg));
+                out.push(cube);
+            }
+        }
+        out
+    }
+
+    /// ¿El bloque en `p` es sólido a efectos de AO? (los transparentes y con
+    /// alpha no oscurecen a sus vecinos)
+    pub(crate) fn occludes(&self, p: [i32; 3], reg: &MaterialRegistry) -> bool {
+        let Some(kind) = self.get(p[0], p[1], p[2]) else { return false; };
+        if kind == BlockKind::Air { return false; }
+        reg.get(kind).is_some_and(|m| m.is_opaque())
+    }
+
+    /// Niveles de AO (0..=3) de las 4 esquinas de cada cara: dos laterales
+    /// ocupados encierran la esquina aunque la diagonal esté libre
+    fn vertex_ao(&self, p: [i32; 3], reg: &MaterialRegistry) -> [[u8; 4]; 6] {
+        let mut out = [[3u8; 4]; 6];
+        for face in Face::ALL {
+            let k = face.axis();
+            let (a, b) = ((k + 1) % 3, (k + 2) % 3);
+            // Capa de celdas del lado hacia el que mira la cara
+            let mut base = p;
+            base[k] += if face.is_positive() { 1 } else { -1 };
+            for (corner, level) in out[face as usize].iter_mut().enumerate() {
+                let da = if corner & 1 == 1 { 1 } else { -1 };
+                let db = if corner & 2 == 2 { 1 } else { -1 };
+                let mut s1 = base; s1[a] += da;
+                let mut s2 = base; s2[b] += db;
+                let mut c = base; c[a] += da; c[b] += db;
+                let (s1, s2, c) = (self.occludes(s1, reg), self.occludes(s2, reg), self.occludes(c, reg));
+                *level = if s1 && s2 { 0 } else { 3 - (s1 as u8 + s2 as u8 + c as u8) };
             }
         }
         out
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self) -> u32 {
+            self.0 = self.0.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+            (self.0 >> 33) as u32
+        }
+
+        fn range(&mut self, lo: i32, hi: i32) -> i32 { lo + (self.next() % (hi - lo + 1) as u32) as i32 }
+    }
+
+    const KINDS: [BlockKind; 4] = [BlockKind::Stone, BlockKind::Dirt, BlockKind::Water, BlockKind::Custom(7)];
+
+    /// Mundo y modelo con las mismas operaciones al azar alrededor del origen
+    fn random_world(seed: u64) -> (World, HashMap<Pos, BlockKind>, Lcg) {
+        let mut rng = Lcg(seed);
+        let mut world = World::new();
+        let mut model = HashMap::new();
+        for _ in 0..6000 {
+            let p = (rng.range(-40, 40), rng.range(-20, 20), rng.range(-40, 40));
+            if rng.next().is_multiple_of(4) {
+                assert_eq!(world.remove(p.0, p.1, p.2), model.remove(&p));
+            } else {
+                let k = KINDS[rng.next() as usize % KINDS.len()];
+                world.set(p.0, p.1, p.2, k);
+                model.insert(p, k);
+            }
+        }
+        (world, model, rng)
+    }
+
+    /// Orden espacial: chunk por (Y, Z, X) y dentro de él celda por (Y, Z, X)
+    fn spatial(p: Pos) -> (i32, i32, i32, i32, i32, i32) {
+        (p.1 >> 4, p.2 >> 4, p.0 >> 4, p.1 & 15, p.2 & 15, p.0 & 15)
+    }
+
+    #[test]
+    fn matches_hashmap_model() {
+        let (world, model, mut rng) = random_world(1);
+        assert_eq!(world.len(), model.len());
+
+        let blocks: Vec<_> = world.blocks().collect();
+        let mut expected: Vec<_> = model.iter().map(|(&p, &k)| (p, k)).collect();
+        expected.sort_by_key(|&(p, _)| spatial(p));
+        assert_eq!(blocks, expected);
+
+        for _ in 0..200 {
+            let a = (rng.range(-50, 50), rng.range(-25, 25), rng.range(-50, 50));
+            let b = (rng.range(-50, 50), rng.range(-25, 25), rng.range(-50, 50));
+            let (lo, hi) = ((a.0.min(b.0), a.1.min(b.1), a.2.min(b.2)), (a.0.max(b.0), a.1.max(b.1), a.2.max(b.2)));
+            let want: Vec<_> = expected.iter().copied()
+                .filter(|&(p, _)| p.0 >= lo.0 && p.0 <= hi.0 && p.1 >= lo.1 && p.1 <= hi.1 && p.2 >= lo.2 && p.2 <= hi.2)
+                .collect();
+            assert_eq!(world.blocks_in(a, b).collect::<Vec<_>>(), want, "{:?} {:?}", a, b);
+        }
+
+        for _ in 0..2000 {
+            let (x, y, z) = (rng.range(-42, 42), rng.range(-22, 22), rng.range(-42, 42));
+            assert_eq!(world.get(x, y, z), model.get(&(x, y, z)).copied());
+            let want = Face::ALL.map(|f| {
+                let mut p = [x, y, z];
+                p[f.axis()] += if f.is_positive() { 1 } else { -1 };
+                model.get(&(p[0], p[1], p[2])).copied()
+            });
+            assert_eq!(world.neighbours(x, y, z), want, "{:?}", (x, y, z));
+        }
+    }
+
+    #[test]
+    fn negative_coordinates_cross_chunk_borders() {
+        let mut world = World::new();
+        let points = [(-1, -1, -1), (0, 0, 0), (-16, 15, -17), (-17, -16, 16), (15, -1, 0)];
+        for (i, &(x, y, z)) in points.iter().enumerate() { world.set(x, y, z, KINDS[i % KINDS.len()]); }
+        for (i, &(x, y, z)) in points.iter().enumerate() { assert_eq!(world.get(x, y, z), Some(KINDS[i % KINDS.len()])); }
+
+        let keys: Vec<_> = world.chunks().map(|(k, _)| k).collect();
+        assert_eq!(keys, [(-1, -1, -1), (0, -1, 0), (-2, -1, 1), (-1, 0, -2), (0, 0, 0)]);
+
+        // Vecinos del otro lado del borde entre chunks -1 y 0
+        assert_eq!(world.neighbours(-1, -1, 0)[Face::NegZ as usize], Some(KINDS[0]));
+        assert_eq!(world.neighbours(-1, 0, 0)[Face::PosX as usize], Some(KINDS[1]));
+        assert_eq!(world.neighbours(0, -1, 0)[Face::PosY as usize], Some(KINDS[1]));
+        assert_eq!(world.bounds(), Some(((-17, -16, -17), (15, 15, 16))));
+    }
+
+    #[test]
+    fn remove_drops_empty_chunks() {
+        let mut world = World::new();
+        world.set(-5, 3, 20, BlockKind::Stone);
+        world.set(-6, 3, 20, BlockKind::Dirt);
+        world.set(40, 0, 0, BlockKind::Dirt);
+        assert_eq!(world.stats().chunks, 2);
+
+        assert_eq!(world.remove(-5, 3, 20), Some(BlockKind::Stone));
+        assert_eq!(world.remove(-5, 3, 20), None);
+        assert_eq!(world.stats().chunks, 2);
+        assert_eq!(world.remove(-6, 3, 20), Some(BlockKind::Dirt));
+        assert_eq!(world.chunks().map(|(k, _)| k).collect::<Vec<_>>(), [(2, 0, 0)]);
+
+        // Quitar donde nunca hubo nada no crea chunks
+        assert_eq!(world.remove(100, 100, 100), None);
+        assert_eq!(world.stats().chunks, 1);
+        assert_eq!(world.remove(40, 0, 0), Some(BlockKind::Dirt));
+        assert!(world.is_empty());
+        assert_eq!(world.chunks().count(), 0);
+    }
+}
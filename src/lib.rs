@@ -0,0 +1,56 @@
+//! Ray tracer de dioramas estilo Minecraft.
+//!
+//! La API pública se re-exporta en la raíz del crate; los módulos completos
+//! siguen disponibles bajo [`core`] para usos más finos (aceleración, formatos).
+//!
+//! ```no_run
+//! use minecraft_diorama::{BlockKind, Framebuffer, Light, Material, MaterialRegistry,
+//!     OrbitCamera, Renderer, Scene, Texture, World, Color};
+//! use nalgebra_glm as glm;
+//!
+//! let stone = Material::with_texture(Texture::load("assets/stone.png"), 0.04, 12.0, 0.02, 0.0, 1.0);
+//! let mut registry = MaterialRegistry::new();
+//! registry.set(BlockKind::Stone, stone);
+//!
+//! let mut world = World::new();
+//! world.set(0, 0, 0, BlockKind::Stone);
+//!
+//! let light = Light::point(glm::vec3(4.0, 6.0, 4.0), Color::new(255, 255, 255), 1.5);
+//! let scene = Scene::new(vec![], world.bake(&registry), vec![light], None);
+//!
+//! let camera = OrbitCamera::new(glm::vec3(0.0, 0.5, 0.0), 6.0, 1.0, 0.35);
+//! let mut fb = Framebuffer::new(640, 480);
+//! Renderer::new().render_frame(&scene, &mut fb, &camera);
+//! fb.save("out.png").unwrap();
+//! ```
+
+pub mod core;
+
+pub use crate::core::accel::aabb::Aabb;
+pub use crate::core::ao::AmbientOcclusion;
+pub use crate::core::block::{Block, BlockKind};
+pub use crate::core::camera::OrbitCamera;
+pub use crate::core::color::Color;
+pub use crate::core::edit::{Axis, Region};
+pub use crate::core::formats::anvil::{AnvilWorld, RegionFile};
+pub use crate::core::formats::minecraft::{BlockMapping, BlockState, ImportReport};
+pub use crate::core::formats::scene_file::SceneFile;
+pub use crate::core::formats::schematic::Schematic;
+pub use crate::core::formats::vox::{VoxFile, VoxImport};
+pub use crate::core::framebuffer::Framebuffer;
+pub use crate::core::geometry::{cube::Cube, sphere::Sphere, RayIntersect};
+pub use crate::core::history::{Change, ChangeSet, History};
+pub use crate::core::intersect::{Face, Intersect};
+pub use crate::core::light::{Attenuation, Light, LightKind};
+pub use crate::core::material::{AlbedoTex, AlphaMode, Material};
+pub use crate::core::material_registry::MaterialRegistry;
+pub use crate::core::mesh::{Mesh, MeshGroup};
+pub use crate::core::renderer::{Integrator, MipSelection, Renderer};
+pub use crate::core::sampling::{PixelFilter, PixelSampler, SamplePattern};
+pub use crate::core::scene::Scene;
+pub use crate::core::tonemap::ToneMapper;
+pub use crate::core::skybox::{Skybox, SkyboxLayout};
+pub use crate::core::sky::ProceduralSky;
+pub use crate::core::texture::{FilterMode, Texture, WrapMode};
+pub use crate::core::chunk::Chunk;
+pub use crate::core::world::{World, WorldStats};
@@ -1,39 +1,193 @@
 mod app;
-mod core;
 
 use nalgebra_glm as glm;
-use app::window::Window;
-use core::color::Color;
-use core::framebuffer::Framebuffer;
-use core::geometry::cube::Cube;
-use core::renderer::Renderer;
-use core::scene::Scene;
-use core::camera::OrbitCamera;
-use core::light::Light;
-
-// Mundo / materiales / texturas
-use core::texture::Texture;
-use core::material::Material;
-use core::material_registry::MaterialRegistry;
-use core::block::BlockKind;
-use core::world::World;
-use core::skybox::Skybox;
-
-use raylib::prelude::MouseButton;
+use app::cli::{Options, USAGE};
+use minecraft_diorama::core::formats::scene_file::{self, SceneFile};
+use minecraft_diorama::core::formats::{gltf, obj};
+use minecraft_diorama::{
+    AlbedoTex, AlphaMode, BlockKind, Color, Framebuffer, Integrator, Light, Material, MaterialRegistry,
+    OrbitCamera, ProceduralSky, Renderer, Scene, Skybox, Texture, World,
+};
 
 fn main() {
-    let width = 1300;
-    let height = 900;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+    let opts = match Options::parse(args) {
+        Ok(o) => o,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            std::process::exit(2);
+        }
+    };
+
+    // Escena desde archivo o el diorama por defecto
+    let (mut scene, mut camera, world, registry) = match &opts.scene {
+        Some(path) => match SceneFile::load(path) {
+            Ok(file) => {
+                for w in &file.warnings { eprintln!("aviso: {}", w); }
+                (file.scene, file.camera, file.world, file.registry)
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        },
+        // Cámara orbital (centro entre capas para ver ambos niveles)
+        None => {
+            let (scene, world, registry) = build_scene();
+            (scene, scene_file::default_camera(), world, registry)
+        }
+    };
+
+    if let Some(path) = &opts.export {
+        export_mesh(&world, &registry, path);
+        return;
+    }
+
+    if let Some(yaw) = opts.yaw { camera.yaw = yaw; }
+    if let Some(pitch) = opts.pitch { camera.pitch = pitch; }
+    if let Some(radius) = opts.radius { camera.radius = radius; }
+    if let Some(path) = &opts.skybox {
+        match Skybox::load(path) {
+            Ok(sb) => scene.skybox = Some(sb),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if opts.skybox.is_some() || opts.skybox_rotation != 0.0 {
+        if let Some(sb) = &mut scene.skybox { sb.rotation += opts.skybox_rotation.to_radians(); }
+        scene.rebuild_environment();
+    }
+    if let Some(time) = opts.time {
+        let mut sky = ProceduralSky::new(time);
+        sky.turbidity = opts.turbidity;
+        scene.set_sky(Some(sky));
+    }
+
+    let mut renderer = Renderer::new();
+    renderer.integrator = opts.integrator;
+    renderer.tone_mapper = opts.tone_mapper;
+    renderer.exposure = opts.exposure;
+    renderer.sampler = opts.sampler;
+    renderer.mip_selection = opts.mip_selection;
+    renderer.shadow_samples = opts.shadow_samples;
+    renderer.ao = opts.ao;
+    renderer.env_samples = opts.env_samples;
+
+    #[cfg(feature = "window")]
+    if !opts.headless {
+        run_window(&mut scene, &mut renderer, camera, &opts);
+        return;
+    }
+
+    render_headless(&scene, &mut renderer, &camera, &opts);
+}
+
+/// Escribe la malla de caras visibles de `world` según la extensión de `path`
+fn export_mesh(world: &World, registry: &MaterialRegistry, path: &str) {
+    let mesh = world.mesh(registry);
+    let res = if path.to_ascii_lowercase().ends_with(".obj") {
+        obj::write_obj(path, &mesh, registry)
+    } else {
+        gltf::write_gltf(path, &mesh, registry)
+    };
+    if let Err(e) = res {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    println!("Malla guardada en {} ({} triángulos)", path, mesh.triangles());
+}
+
+/// Renderiza un único frame y lo guarda en `opts.output` (sin ventana ni GPU)
+fn render_headless(scene: &Scene, renderer: &mut Renderer, camera: &OrbitCamera, opts: &Options) {
+    let mut fb = Framebuffer::new(opts.width, opts.height);
+    fb.clear(Color::new(135, 206, 235));
+
+    // El path tracer converge acumulando frames con la cámara quieta
+    let frames = if opts.integrator == Integrator::PathTracer { opts.spp } else { 1 };
+    for _ in 0..frames {
+        renderer.render_frame(scene, &mut fb, camera);
+    }
+
+    if let Err(e) = fb.save(&opts.output) {
+        eprintln!("No pude guardar {}: {}", opts.output, e);
+        std::process::exit(1);
+    }
+    println!("Render guardado en {} ({}x{})", opts.output, opts.width, opts.height);
+}
+
+#[cfg(feature = "window")]
+fn run_window(scene: &mut Scene, renderer: &mut Renderer, mut camera: OrbitCamera, opts: &Options) {
+    use app::window::Window;
+    use raylib::prelude::{KeyboardKey, MouseButton};
 
     // Ventana
-    let mut window = Window::new("Minecraft Diorama", width as usize, height as usize);
+    let mut window = Window::new("Minecraft Diorama", opts.width as usize, opts.height as usize);
 
     // Framebuffer
-    let mut fb = Framebuffer::new(width as u32, height as u32);
+    let mut fb = Framebuffer::new(opts.width, opts.height);
+
+    // Sensibilidades de cámara
+    let rot_sens = 0.005;
+    let zoom_sens = 0.1;
+
+    // switches para invertir
+    const INVERT_YAW: f32 = -1.0; 
+    const INVERT_PITCH: f32 = -1.0; 
+    const INVERT_SCROLL: f32 = -1.0; 
+
+    while window.is_open() {
+        // P alterna entre Whitted y path tracing
+        if window.is_key_pressed(KeyboardKey::KEY_P) {
+            renderer.integrator = match renderer.integrator {
+                Integrator::Whitted => Integrator::PathTracer,
+                Integrator::PathTracer => Integrator::Whitted,
+            };
+            println!("Integrador: {:?}", renderer.integrator);
+        }
+        // A recorre 1, 4, 9 y 16 muestras de antialiasing por pixel
+        if window.is_key_pressed(KeyboardKey::KEY_A) {
+            let side = (renderer.sampler.samples as f32).sqrt().round() as u32 % 4 + 1;
+            renderer.sampler.samples = side * side;
+            println!("Antialiasing: {} muestras ({:?}, {:?})",
+                renderer.sampler.samples, renderer.sampler.pattern, renderer.sampler.filter);
+        }
 
-    // Cámara orbital (centro entre capas para ver ambos niveles)
-    let mut camera = OrbitCamera::new(glm::vec3(0.0, 2.0, 0.0), 10.0, 1.0, 0.35);
+        // T adelanta media hora el cielo procedural
+        if window.is_key_pressed(KeyboardKey::KEY_T) {
+            if let Some(mut sky) = scene.sky().copied() {
+                sky.time_of_day = (sky.time_of_day + 0.5) % 24.0;
+                scene.set_sky(Some(sky));
+                println!("Hora: {:.1}", sky.time_of_day);
+            }
+        }
 
+        // Input cámara
+        let (dx, dy) = window.mouse_delta();
+        if window.is_mouse_down(MouseButton::MOUSE_BUTTON_RIGHT) || window.is_mouse_down(MouseButton::MOUSE_BUTTON_LEFT) {
+            camera.rotate(INVERT_YAW * dx * rot_sens, INVERT_PITCH * dy * rot_sens);
+        }
+
+        let wheel = window.mouse_wheel();
+        if wheel.abs() > 0.0 {
+            // antes: camera.zoom(1.0 - wheel * zoom_sens);
+            camera.zoom(1.0 - (INVERT_SCROLL * wheel) * zoom_sens);
+        }
+
+        // Cielo
+        fb.clear(Color::new(135, 206, 235));
+        renderer.render_frame(scene, &mut fb, &camera);
+        window.present(fb.pixels());
+    }
+}
+
+/// Diorama por defecto: materiales, mundo y sustituciones por posición
+fn build_scene() -> (Scene, World, MaterialRegistry) {
     // Cargar texturas
     let grass_top= Texture::load("assets/grass_top.png");
     let grass_side= Texture::load("assets/grass_side.png").rotated_180();
@@ -76,8 +230,9 @@ fn main() {
     // Diamond (opaco brillante)
     let diamond_mat = Material::with_texture(diamond_tex.clone(), 0.28, 90.0, 0.14, 0.0, 1.0);
 
-    // Lava (sin emisión aún)
-    let lava_mat = Material::with_texture(lava_tex.clone(), 0.10, 100.0, 0.01, 0.0, 1.0);
+    // Lava (emisiva: brilla con su propia textura e ilumina la caverna)
+    let lava_mat = Material::with_texture(lava_tex.clone(), 0.10, 100.0, 0.01, 0.0, 1.0)
+        .with_emission_map(AlbedoTex::Single(lava_tex.clone()), 1.6);
 
     // Water (físico)
     let water_mat = Material::with_texture(
@@ -92,245 +247,60 @@ fn main() {
     // Wood
     let wood_mat = Material::with_texture(wood_tex.clone(), 0.05, 16.0, 0.02, 0.0, 1.0);
 
-    // Leaves
-    let leaves_mat = Material::with_texture(leaves_tex.clone(), 0.07, 12.0, 0.01, 0.38, 1.40);
+    // Leaves (huecos de la textura recortados por alfa)
+    let leaves_mat = Material::with_texture(leaves_tex.clone(), 0.07, 12.0, 0.01, 0.0, 1.0)
+        .with_alpha(AlphaMode::Cutout { threshold: 0.5 });
         
 
-    // Registry base (Grass/Dirt/Stone). Iron/Diamond/Lava los pondremos por posición post-bake.
     let mut registry = MaterialRegistry::new();
-    registry.set(BlockKind::Grass, grass_mat.clone());
-    registry.set(BlockKind::Dirt,  dirt_mat.clone());
-    registry.set(BlockKind::Stone, stone_mat.clone());
-    registry.set(BlockKind::Water, water_mat.clone());
-    registry.set(BlockKind::Wood,   wood_mat.clone());
-    registry.set(BlockKind::Leaves, leaves_mat.clone());
-    registry.set(BlockKind::Lava, lava_mat.clone());
-    registry.set(BlockKind::Diamond, diamond_mat.clone());
-    registry.set(BlockKind::Iron, iron_mat.clone());
+    registry.set(BlockKind::Grass, grass_mat);
+    registry.set(BlockKind::Dirt,  dirt_mat);
+    registry.set(BlockKind::Stone, stone_mat);
+    registry.set(BlockKind::Water, water_mat);
+    registry.set(BlockKind::Wood,   wood_mat);
+    registry.set(BlockKind::Leaves, leaves_mat);
+    registry.set(BlockKind::Lava, lava_mat);
+    registry.set(BlockKind::Diamond, diamond_mat);
+    registry.set(BlockKind::Iron, iron_mat);
 
     // Construccion de diorama
     let mut world = World::new();
 
-    // Piso superior 5x5, con grass y water
-    for z in -2..=2 {
-        for x in -2..=2 {
-            let is_border = x == -2 || x == 2 || z == -2 || z == 2;
-            let is_center = x == 0 && z == 0;
-            if is_border || is_center {
-                world.set(x, 0, z, BlockKind::Grass);
-            } else {
-                world.set(x, 0, z, BlockKind::Water);
-            }
-        }
-    }
-
-    // Tronco del arbol
-    for y in 1..=3 {
-        world.set(0, y, 0, BlockKind::Wood);
-    }
-
-    // Copa de hojas
-    for dz in -1..=1 {
-        for dx in -1..=1 {
-            world.set(dx, 4, dz, BlockKind::Leaves);
-        }
-    }
-
-    world.set(-1, 3,  0, BlockKind::Leaves);
-    world.set( 1, 3,  0, BlockKind::Leaves);
-    world.set( 0, 3, -1, BlockKind::Leaves);
-    world.set( 0, 3,  1, BlockKind::Leaves);
+    // Piso superior 5x5: borde de grass, estanque de agua y el centro para el arbol
+    world.fill((-2, 0, -2), (2, 0, 2), BlockKind::Grass);
+    world.fill((-1, 0, -1), (1, 0, 1), BlockKind::Water);
+    world.set(0, 0, 0, BlockKind::Grass);
 
+    // Copa de hojas (cruz en y=3, capa 3x3 y punta) y tronco por dentro
+    world.cylinder((0, 3, 0), 1.0, 1, BlockKind::Leaves, false);
+    world.fill((-1, 4, -1), (1, 4, 1), BlockKind::Leaves);
     world.set(0, 5, 0, BlockKind::Leaves);
+    world.line((0, 1, 0), (0, 3, 0), BlockKind::Wood);
 
-    // Paredes traseras para rellenar 
-    {
-        let y_min = -4;
-        let y_max = -1;
-
-        let z_back = -2;
-        for x in -2..=2 {
-            for y in y_min..=y_max {
-                world.set(x, y, z_back, BlockKind::Stone);
-            }
-        }
-
-        let x_back = -2;
-        for z in -2..=2 {
-            for y in y_min..=y_max {
-                world.set(x_back, y, z, BlockKind::Stone);
-            }
-        }
-    }
-
-    // Construccion de columnas, mas para las estacas
-    let mut place_column = |x: i32, z: i32, dirt_n: i32, tail_kind: BlockKind, tail_n: i32| {
-        let mut y = -1;
-        for _ in 0..dirt_n { world.set(x, y, z, BlockKind::Dirt); y -= 1; }
-        for _ in 0..tail_n { world.set(x, y, z, tail_kind); y -= 1; }
-        // Si aún no llegamos a -5, completa con tail_kind (por seguridad)
-        while y >= -5 { world.set(x, y, z, tail_kind); y -= 1; }
-    };
+    // Paredes traseras y piso inferior de piedra, con la lava al centro
+    world.fill((-2, -4, -2), (2, -1, -2), BlockKind::Stone);
+    world.fill((-2, -4, -2), (-2, -1, 2), BlockKind::Stone);
+    world.fill((-2, -5, -2), (2, -5, 2), BlockKind::Stone);
+    world.fill((-1, -5, -1), (1, -5, 1), BlockKind::Lava);
 
-    // Columnas
-    // 2 dirt + 3 stone
-    place_column( 2, -2, 2, BlockKind::Stone, 3);
-    // 2 dirt + 3 iron
-    place_column(-2,  2, 2, BlockKind::Stone, 3);
-
-    // Piso inferior
-    // Donde S=Stone, L=Lava, D=Diamond.
-    #[derive(Clone, Copy)]
-    enum Cell { S, L }
-
-    let pattern: [[Cell; 5]; 5] = [
-        [Cell::S, Cell::S, Cell::S, Cell::S, Cell::S],
-        [Cell::S, Cell::L, Cell::L, Cell::L, Cell::S],
-        [Cell::S, Cell::L, Cell::L, Cell::L, Cell::S],
-        [Cell::S, Cell::L, Cell::L, Cell::L, Cell::S],
-        [Cell::S, Cell::S, Cell::S, Cell::S, Cell::S],
-    ];
-
-    // Esquinas del 5x5 (
-    let is_corner = |x: i32, z: i32| (x == -2 || x == 2) && (z == -2 || z == 2);
-
-    // Rango de mundo
-    for z in -2..=2 {
-        for x in -2..=2 {
-            if is_corner(x, z) { continue; } 
-
-            world.set(x, -5, z, BlockKind::Stone);
-        }
+    // Diamantes en la pared z=-2; la pared x=-2 lleva la misma veta girada
+    for (x, y) in [(-1, -2), (1, -2), (0, -3), (-1, -4), (1, -4)] {
+        world.set(x, y, -2, BlockKind::Diamond);
     }
+    let vein = world.copy((-1, -4, -2), (1, -1, -2)).expect("la veta es una caja de 3x4x1");
+    world.paste(&vein.rotated_y(1), (-2, -4, -1));
 
-    // Horneo a Cubes
-    let mut cubes = world.bake(&registry);
+    // Columnas de las esquinas: 2 de dirt arriba y, en la del frente, hierro abajo
+    world.fill((2, -2, -2), (2, -1, -2), BlockKind::Dirt);
+    world.fill((-2, -2, 2), (-2, -1, 2), BlockKind::Dirt);
+    world.fill((-2, -5, 2), (-2, -3, 2), BlockKind::Iron);
 
-    // Sustituciones por posición
-    for a in &mut cubes {
-        let x = a.min.x.round() as i32;
-        let y = a.min.y.round() as i32;
-        let z = a.min.z.round() as i32;
-
-        // Columna de la esquina C 
-        if x == -2 && z == 2 && (-5..=-3).contains(&y) {
-            a.material = iron_mat.clone();
-            continue;
-        }
-
-        if x == -2 && (-4..=-1).contains(&y) {
-            if z == 2 && (y == -1 || y == -2) {
-                a.material = dirt_mat.clone();
-                continue;
-            }
-            if (-1..=1).contains(&z) {
-                let col = (z + 1) as i32;
-                let row = (-y - 1) as i32;
-                let mat = match (row, col) {
-                    (0, _         ) => &stone_mat,
-                    (1, 0) | (1, 2) => &diamond_mat,
-                    (1, 1)         => &stone_mat,
-                    (2, 0) | (2, 2) => &stone_mat,
-                    (2, 1)         => &diamond_mat,
-                    (3, 0) | (3, 2) => &diamond_mat,
-                    (3, 1)         => &stone_mat,
-                    _               => &stone_mat,
-                };
-                a.material = mat.clone();
-                continue;
-            }
-        }
-
-        // Cara trasera
-        if z == -2 && (-4..=-1).contains(&y) && (-1..=1).contains(&x) {
-            let col = (x + 1) as i32;
-            let row = (-y - 1) as i32;
-            let mat = match (row, col) {
-                (0, _         ) => &stone_mat,
-                (1, 0) | (1, 2) => &diamond_mat,
-                (1, 1)         => &stone_mat,
-                (2, 0) | (2, 2) => &stone_mat,
-                (2, 1)         => &diamond_mat,
-                (3, 0) | (3, 2) => &diamond_mat,
-                (3, 1)         => &stone_mat,
-                _               => &stone_mat,
-            };
-            a.material = mat.clone();
-            continue;
-        }
-
-        // Piso inferior según la matriz
-        if y == -5 && !((x == -2 || x == 2) && (z == -2 || z == 2)) {
-            let ix = (x + 2) as usize;
-            let iz = (z + 2) as usize;
-
-            match pattern[iz][ix] {
-                Cell::S => { a.material = stone_mat.clone(); }
-                Cell::L => { a.material = lava_mat.clone(); }
-            }
-        }
-    }
-
-    // Relleno de esquinas faltantes en el piso inferio
-    let corners = [(-2, -2), (2, -2), (-2, 2), (2, 2)];
-
-    for (x, z) in corners {
-        let min = glm::vec3(x as f32, -5.0, z as f32);
-        let exists = cubes.iter().any(|a| {
-            (a.min.x - min.x).abs() < 1e-6 &&
-            (a.min.y - min.y).abs() < 1e-6 &&
-            (a.min.z - min.z).abs() < 1e-6
-        });
-        if exists { continue; }
-
-        let ix = (x + 2) as usize;
-        let iz = (z + 2) as usize;
-        let mat = match pattern[iz][ix] {
-            Cell::S => stone_mat.clone(),
-            Cell::L => lava_mat.clone(),
-        };
-
-        cubes.push(Cube::new(min, min + glm::vec3(1.0, 1.0, 1.0), mat));
-    }
+    let cubes = world.bake(&registry);
 
     // Luz
     let light0 = Light::point(glm::vec3( 4.0, 6.0,  4.0), Color::new(255, 255, 255), 1.5);
 
     // Escena final
-    let scene = Scene {
-        spheres: vec![],
-        cubes,
-        lights: vec![light0],
-        skybox: Some(skybox), 
-    };
-
-    let renderer = Renderer::new();
-
-    // Sensibilidades de cámara
-    let rot_sens = 0.005;
-    let zoom_sens = 0.1;
-
-    // switches para invertir
-    const INVERT_YAW: f32 = -1.0; 
-    const INVERT_PITCH: f32 = -1.0; 
-    const INVERT_SCROLL: f32 = -1.0; 
-
-    while window.is_open() {
-        // Input cámara
-        let (dx, dy) = window.mouse_delta();
-        if window.is_mouse_down(MouseButton::MOUSE_BUTTON_RIGHT) || window.is_mouse_down(MouseButton::MOUSE_BUTTON_LEFT) {
-            camera.rotate(INVERT_YAW * dx * rot_sens, INVERT_PITCH * dy * rot_sens);
-        }
-
-        let wheel = window.mouse_wheel();
-        if wheel.abs() > 0.0 {
-            // antes: camera.zoom(1.0 - wheel * zoom_sens);
-            camera.zoom(1.0 - (INVERT_SCROLL * wheel) * zoom_sens);
-        }
-
-        // Cielo
-        fb.clear(Color::new(135, 206, 235));
-        renderer.render_frame(&scene, &mut fb, &camera);
-        window.present(fb.pixels());
-    }
-}
\ No newline at end of file
+    // Escena final (construye la grilla de vóxeles para el DDA)
+    (Scene::new(vec![], cubes, vec![light0], Some(skybox)), world, registry)
+}